@@ -0,0 +1,145 @@
+//! Serialization, decoding and write throughput of the save pipeline on payloads of a few
+//! sizes, in both save formats.
+//!
+//! Sinks never compress what they write, the only compression is the zip of
+//! `BugReportPlugin`, whose deflate levels are compared on the same saves.
+
+use async_std::task::block_on;
+use bevy_io_sink::{codec, FileSink, IoWriter, JournalSink, Migrations};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde::{Deserialize, Serialize};
+use std::{
+    hint::black_box,
+    io::{Cursor, Write},
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Payload {
+    name: String,
+    position: [f32; 3],
+    inventory: Vec<u32>,
+    samples: Vec<f32>,
+}
+
+impl Payload {
+    fn with_len(len: usize) -> Self {
+        Self {
+            name: "player".to_string(),
+            position: [1.0, 2.0, 3.0],
+            inventory: (0..len as u32).collect(),
+            samples: (0..len).map(|i| i as f32 * 0.5).collect(),
+        }
+    }
+}
+
+const SIZES: [usize; 3] = [16, 1024, 64 * 1024];
+
+fn serialize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("serialize");
+    for len in SIZES {
+        let payload = Payload::with_len(len);
+        let bytes = serde_json::to_vec(&payload).unwrap().len();
+        group.throughput(Throughput::Bytes(bytes as u64));
+        group.bench_with_input(BenchmarkId::new("json", len), &payload, |b, payload| {
+            b.iter(|| serde_json::to_vec(black_box(payload)).unwrap())
+        });
+        group.bench_with_input(
+            BenchmarkId::new("json_pretty", len),
+            &payload,
+            |b, payload| b.iter(|| serde_json::to_vec_pretty(black_box(payload)).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+fn decode(c: &mut Criterion) {
+    let migrations = Migrations::default();
+    let mut group = c.benchmark_group("decode");
+    for len in SIZES {
+        let bytes = serde_json::to_vec(&Payload::with_len(len)).unwrap();
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_with_input(BenchmarkId::new("json", len), &bytes, |b, bytes| {
+            b.iter(|| codec::decode::<Payload>(black_box(bytes), &migrations).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn write(c: &mut Criterion) {
+    let dir = std::env::temp_dir().join("bevy_io_sink_bench");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut group = c.benchmark_group("serialize_and_write");
+    for len in SIZES {
+        let payload = Payload::with_len(len);
+        let bytes = serde_json::to_vec(&payload).unwrap().len();
+        group.throughput(Throughput::Bytes(bytes as u64));
+
+        let mut file_sink = FileSink::<Payload>::new(dir.join(format!("file_{len}.json")));
+        block_on(IoWriter::<Payload>::init(&mut file_sink)).unwrap();
+        let mut toggle = false;
+        group.bench_with_input(BenchmarkId::new("file", len), &payload, |b, payload| {
+            b.iter(|| {
+                // Alternate payloads so the unchanged-content check doesn't skip the write.
+                let mut payload = payload.clone();
+                toggle = !toggle;
+                payload.position[0] = toggle as u8 as f32;
+                block_on(file_sink.write(payload)).unwrap()
+            })
+        });
+
+        let mut journal = JournalSink::new(dir.join(format!("journal_{len}.jsonl")));
+        block_on(IoWriter::<Payload>::init(&mut journal)).unwrap();
+        group.bench_with_input(BenchmarkId::new("journal", len), &payload, |b, payload| {
+            b.iter(|| block_on(journal.write(payload.clone())).unwrap())
+        });
+    }
+    group.finish();
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Zip `bytes` as a single entry, like `BugReportPlugin` does with each save.
+fn zip_save(bytes: &[u8], options: SimpleFileOptions) -> Vec<u8> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file("save.json", options).unwrap();
+    zip.write_all(bytes).unwrap();
+    zip.finish().unwrap().into_inner()
+}
+
+fn compress(c: &mut Criterion) {
+    let levels = [
+        (
+            "stored",
+            SimpleFileOptions::default().compression_method(CompressionMethod::Stored),
+        ),
+        (
+            "deflate_1",
+            SimpleFileOptions::default().compression_level(Some(1)),
+        ),
+        (
+            "deflate_6",
+            SimpleFileOptions::default().compression_level(Some(6)),
+        ),
+        (
+            "deflate_9",
+            SimpleFileOptions::default().compression_level(Some(9)),
+        ),
+    ];
+
+    let mut group = c.benchmark_group("bug_report_zip");
+    for len in SIZES {
+        let bytes = serde_json::to_vec_pretty(&Payload::with_len(len)).unwrap();
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        for (name, options) in levels {
+            group.bench_with_input(BenchmarkId::new(name, len), &bytes, |b, bytes| {
+                b.iter(|| zip_save(black_box(bytes), options))
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, serialize, decode, write, compress);
+criterion_main!(benches);
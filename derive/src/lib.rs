@@ -0,0 +1,118 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Field, Fields};
+
+/// Implements `Serialize`, `Deserialize` and `bevy_io_sink::Persist` for a struct with named
+/// fields, so it can be registered with `app.add_plugins(PlayerState::persist("player.json"))`.
+///
+/// Fields marked `#[persist(skip)]` are left out of the save and restored from `Default`.
+#[proc_macro_derive(Persist, attributes(persist))]
+pub fn derive_persist(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let ident = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "Persist cannot be derived for generic types",
+        ));
+    }
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            ident.span(),
+            "Persist can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new(
+            data.fields.span(),
+            "Persist can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut names = Vec::new();
+    let mut types = Vec::new();
+    let mut skipped = Vec::new();
+    for field in &fields.named {
+        if is_skipped(field)? {
+            skipped.push(&field.ident);
+        } else {
+            names.push(&field.ident);
+            types.push(&field.ty);
+        }
+    }
+
+    Ok(quote! {
+        const _: () = {
+            use ::bevy_io_sink::__private::serde;
+
+            #[derive(serde::Serialize)]
+            #[serde(crate = "::bevy_io_sink::__private::serde")]
+            struct Saved<'a> {
+                #( #names: &'a #types, )*
+                #[serde(skip)]
+                _marker: ::core::marker::PhantomData<&'a ()>,
+            }
+
+            #[derive(serde::Deserialize)]
+            #[serde(crate = "::bevy_io_sink::__private::serde")]
+            struct Loaded {
+                #( #names: #types, )*
+            }
+
+            impl serde::Serialize for #ident {
+                fn serialize<S: serde::Serializer>(
+                    &self,
+                    serializer: S,
+                ) -> ::core::result::Result<S::Ok, S::Error> {
+                    serde::Serialize::serialize(
+                        &Saved {
+                            #( #names: &self.#names, )*
+                            _marker: ::core::marker::PhantomData,
+                        },
+                        serializer,
+                    )
+                }
+            }
+
+            impl<'de> serde::Deserialize<'de> for #ident {
+                fn deserialize<D: serde::Deserializer<'de>>(
+                    deserializer: D,
+                ) -> ::core::result::Result<Self, D::Error> {
+                    let loaded = <Loaded as serde::Deserialize>::deserialize(deserializer)?;
+                    ::core::result::Result::Ok(Self {
+                        #( #names: loaded.#names, )*
+                        #( #skipped: ::core::default::Default::default(), )*
+                    })
+                }
+            }
+
+            impl ::bevy_io_sink::Persist for #ident {}
+        };
+    })
+}
+
+fn is_skipped(field: &Field) -> syn::Result<bool> {
+    let mut skip = false;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("persist"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown persist attribute, expected `skip`"))
+            }
+        })?;
+    }
+    Ok(skip)
+}
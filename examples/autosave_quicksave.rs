@@ -0,0 +1,87 @@
+//! Headless autosave + quicksave round trip.
+//!
+//! `Progress` is autosaved whenever it changes (at most 5 times per second), and a
+//! quicksave is forced on frame 120. The app exits once the quicksave has been written
+//! and then checks the file on disk, so `cargo run --example autosave_quicksave` doubles
+//! as an end-to-end test.
+use bevy::{app::ScheduleRunnerPlugin, diagnostic::FrameCount, log::LogPlugin, prelude::*};
+use bevy_io_sink::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Default, Clone, Resource, Debug)]
+struct Progress {
+    ticks: u32,
+    quicksaved_at: Option<u32>,
+}
+
+const QUICKSAVE_FRAME: u32 = 120;
+const TIMEOUT_FRAME: u32 = 600;
+
+fn main() -> AppExit {
+    let path = std::env::temp_dir().join("bevy_io_sink_autosave_quicksave.json");
+    let _ = std::fs::remove_file(&path);
+
+    let exit =
+        App::new()
+            .add_plugins(LogPlugin::default())
+            .add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(
+                Duration::from_secs_f64(1.0 / 60.0),
+            )))
+            .add_plugins(
+                FileSinkPlugin::<Progress>::new(path.clone())
+                    .with_sync_on_change(true)
+                    .with_max_writes_per_second(5.0),
+            )
+            .add_systems(
+                Update,
+                (tick, quicksave, exit_after_quicksave)
+                    .chain()
+                    .run_if(resource_exists::<Progress>),
+            )
+            .run();
+    if exit.is_error() {
+        return exit;
+    }
+
+    let saved: Progress = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    info!("on disk: {saved:?}");
+    assert_eq!(saved.quicksaved_at, Some(QUICKSAVE_FRAME));
+    assert!(saved.ticks >= QUICKSAVE_FRAME);
+    AppExit::Success
+}
+
+fn tick(mut progress: ResMut<Progress>) {
+    progress.ticks += 1;
+}
+
+fn quicksave(mut progress: ResMut<Progress>, sender: Res<IoSender<Progress>>, sinks: Res<IoSinks>) {
+    if progress.ticks != QUICKSAVE_FRAME {
+        return;
+    }
+    progress.quicksaved_at = Some(progress.ticks);
+    // Bypass the autosave rate limit and make sure the write reaches the disk.
+    if let Err(err) = sender.enqueue(progress.clone()) {
+        error!("{err}");
+    }
+    sinks.flush(SinkTag::Save);
+}
+
+fn exit_after_quicksave(
+    progress: Res<Progress>,
+    frames: Res<FrameCount>,
+    mut completed: EventReader<SaveCompleted<Progress>>,
+    mut failed: EventReader<SinkFailed<Progress>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for failure in failed.read() {
+        error!("{:?} failed: {}", failure.phase, failure.error);
+        exit.write(AppExit::error());
+    }
+    if progress.quicksaved_at.is_some() && completed.read().count() > 0 {
+        exit.write(AppExit::Success);
+    } else if frames.0 > TIMEOUT_FRAME {
+        error!("quicksave was not written within {TIMEOUT_FRAME} frames");
+        exit.write(AppExit::error());
+    }
+}
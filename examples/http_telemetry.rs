@@ -0,0 +1,146 @@
+//! Headless telemetry over HTTP.
+//!
+//! Starts a tiny HTTP server on a local port, POSTs one `FrameSample` per frame to it
+//! through an [`HttpSink`] tagged as telemetry, exits once the server has received them all
+//! and then checks they arrived complete and in order.
+//! `cargo run --example http_telemetry --features http` doubles as an end-to-end test.
+use bevy::{app::ScheduleRunnerPlugin, diagnostic::FrameCount, log::LogPlugin, prelude::*};
+use bevy_io_sink::{prelude::*, HttpSink};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct FrameSample {
+    frame: u32,
+    delta_secs: f32,
+}
+
+const SAMPLES: u32 = 30;
+const TIMEOUT_FRAME: u32 = 600;
+
+/// Request bodies received by the server, in order.
+#[derive(Resource, Clone, Default)]
+struct Received(Arc<Mutex<Vec<String>>>);
+
+impl Received {
+    fn len(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+}
+
+fn main() -> AppExit {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/telemetry", listener.local_addr().unwrap());
+    let received = Received::default();
+    let server = received.clone();
+    std::thread::spawn(move || serve(listener, server));
+
+    let exit = App::new()
+        .add_plugins(LogPlugin::default())
+        .add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Default::default())))
+        .add_plugins(
+            IoSinkPlugin::<FrameSample, _>::new(HttpSink::new(url))
+                .with_tag(SinkTag::Telemetry)
+                .with_retry(RetryPolicy::new(3)),
+        )
+        .insert_resource(received.clone())
+        .add_systems(Update, (sample, exit_when_received).chain())
+        .run();
+    if exit.is_error() {
+        return exit;
+    }
+
+    let bodies = received.0.lock().unwrap();
+    info!("the server received {} samples", bodies.len());
+    let frames: Vec<u32> = bodies
+        .iter()
+        .map(|body| serde_json::from_str::<FrameSample>(body).unwrap().frame)
+        .collect();
+    assert_eq!(frames, (0..SAMPLES).collect::<Vec<_>>());
+    AppExit::Success
+}
+
+/// Answers every POST with `204 No Content`, keeping its body.
+fn serve(listener: TcpListener, received: Received) {
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let received = received.clone();
+        std::thread::spawn(move || {
+            let mut response = stream.try_clone().unwrap();
+            let mut reader = BufReader::new(stream);
+            // The client keeps the connection alive, answer every request sent on it.
+            while let Some(body) = read_request(&mut reader) {
+                received.0.lock().unwrap().push(body);
+                if response
+                    .write_all(b"HTTP/1.1 204 No Content\r\n\r\n")
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        });
+    }
+}
+
+/// The body of the next request, `None` once the connection is closed.
+fn read_request(reader: &mut BufReader<TcpStream>) -> Option<String> {
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().ok()?;
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+    String::from_utf8(body).ok()
+}
+
+fn sample(frames: Res<FrameCount>, time: Res<Time>, sender: Res<IoSender<FrameSample>>) {
+    if frames.0 >= SAMPLES {
+        return;
+    }
+    let sample = FrameSample {
+        frame: frames.0,
+        delta_secs: time.delta_secs(),
+    };
+    if let Err(err) = sender.enqueue(sample) {
+        error!("{err}");
+    }
+}
+
+fn exit_when_received(
+    frames: Res<FrameCount>,
+    received: Res<Received>,
+    mut failed: EventReader<SinkFailed<FrameSample>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for failure in failed.read() {
+        error!("{:?} failed: {}", failure.phase, failure.error);
+        exit.write(AppExit::error());
+    }
+    if received.len() >= SAMPLES as usize {
+        exit.write(AppExit::Success);
+    } else if frames.0 > TIMEOUT_FRAME {
+        error!(
+            "the server only received {} of {SAMPLES} samples",
+            received.len()
+        );
+        exit.write(AppExit::error());
+    }
+}
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
-use bevy_io_sink::*;
+use bevy_io_sink::prelude::*;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Default, Clone, Resource, Deserialize, Debug, Reflect)]
@@ -49,8 +49,7 @@ fn setup(mut commands: Commands, state: Res<PlayerState>) {
 fn player_movement(
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mut query: Query<(&mut Transform, &mut PositionTimer), With<Player>>,
-    sender: Res<IoSender<PlayerState>>,
-    mut state: ResMut<PlayerState>,
+    mut state: Saver<PlayerState>,
     time: Res<Time>,
 ) {
     for (mut transform, mut timer) in query.iter_mut() {
@@ -78,7 +77,7 @@ fn player_movement(
         }
 
         if timer.just_finished() {
-            let _ = sender.try_send(state.clone());
+            let _ = state.save();
         }
     }
 }
@@ -0,0 +1,138 @@
+//! Headless save slots menu.
+//!
+//! Plays through what a load game menu does with [`SaveSlots`]: the initial slot is loaded
+//! at startup, a second slot is created and switched to, the game switches back to the
+//! first one and finds its progress intact, then deletes the second slot. Every step waits
+//! for the previous one to complete, so `cargo run --example save_slots` doubles as an
+//! end-to-end test.
+use bevy::{app::ScheduleRunnerPlugin, diagnostic::FrameCount, log::LogPlugin, prelude::*};
+use bevy_io_sink::{prelude::*, LoadSet, SlotChange, SlotChanged, SlotFailed, SlotRecord};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Default, Clone, Resource, Debug, PartialEq)]
+struct Progress {
+    level: u32,
+}
+
+const TIMEOUT_FRAME: u32 = 600;
+
+/// The menu actions played through, in order.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+enum Step {
+    #[default]
+    LoadInitial,
+    SaveFirst,
+    CreateSecond,
+    SaveSecond,
+    SwitchBack,
+    DeleteSecond,
+}
+
+fn main() -> AppExit {
+    let dir = std::env::temp_dir().join("bevy_io_sink_save_slots");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let exit =
+        App::new()
+            .add_plugins(LogPlugin::default())
+            .add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(
+                Duration::from_secs_f64(1.0 / 60.0),
+            )))
+            .add_plugins(SaveSlotsPlugin::<Progress>::new(dir.clone()))
+            // After the switched-to slot is in the world, so changes made here are saved
+            // to it in the same frame.
+            .add_systems(PreUpdate, play_menu.after(LoadSet))
+            .add_systems(Update, exit_on_failure)
+            .run();
+    if exit.is_error() {
+        return exit;
+    }
+
+    let mut saves: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    saves.sort();
+    info!("saves left: {saves:?}");
+    assert_eq!(saves, ["slot1.json"]);
+    AppExit::Success
+}
+
+fn play_menu(
+    mut step: Local<Step>,
+    slots: Res<SaveSlots<Progress>>,
+    mut progress: Option<ResMut<Progress>>,
+    mut changed: EventReader<SlotChanged<Progress>>,
+    mut saved: EventReader<SaveCompleted<SlotRecord<Progress>>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let saved = saved.read().count() > 0;
+    for SlotChanged { change, .. } in changed.read() {
+        info!("{change:?}");
+        let progress = progress.as_deref_mut();
+        *step = match (*step, change) {
+            (Step::LoadInitial, SlotChange::Switched { to, .. }) => {
+                assert_eq!(to, "slot1");
+                progress.unwrap().level = 1;
+                Step::SaveFirst
+            }
+            (Step::CreateSecond, SlotChange::Created(slot)) => {
+                slots.switch(slot.clone());
+                Step::CreateSecond
+            }
+            (Step::CreateSecond, SlotChange::Switched { to, .. }) => {
+                assert_eq!(to, "slot2");
+                let progress = progress.unwrap();
+                assert_eq!(*progress, Progress::default());
+                progress.level = 2;
+                Step::SaveSecond
+            }
+            (Step::SwitchBack, SlotChange::Switched { to, .. }) => {
+                assert_eq!(to, "slot1");
+                assert_eq!(progress.unwrap().level, 1);
+                slots.delete("slot2");
+                Step::DeleteSecond
+            }
+            (Step::DeleteSecond, SlotChange::Deleted(_)) => {
+                assert_eq!(slots.slots(), ["slot1"]);
+                exit.write(AppExit::Success);
+                return;
+            }
+            (step, change) => panic!("unexpected {change:?} at {step:?}"),
+        };
+    }
+
+    // Wait for the progress to be on disk before leaving its slot.
+    match *step {
+        Step::SaveFirst if saved => {
+            slots.create("slot2");
+            *step = Step::CreateSecond;
+        }
+        Step::SaveSecond if saved => {
+            slots.switch("slot1");
+            *step = Step::SwitchBack;
+        }
+        _ => {}
+    }
+}
+
+fn exit_on_failure(
+    frames: Res<FrameCount>,
+    mut slot_failed: EventReader<SlotFailed<Progress>>,
+    mut sink_failed: EventReader<SinkFailed<SlotRecord<Progress>>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for failure in slot_failed.read() {
+        error!("slot `{}` failed: {}", failure.slot, failure.error);
+        exit.write(AppExit::error());
+    }
+    for failure in sink_failed.read() {
+        error!("{:?} failed: {}", failure.phase, failure.error);
+        exit.write(AppExit::error());
+    }
+    if frames.0 > TIMEOUT_FRAME {
+        error!("the menu didn't complete within {TIMEOUT_FRAME} frames");
+        exit.write(AppExit::error());
+    }
+}
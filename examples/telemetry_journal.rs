@@ -0,0 +1,72 @@
+//! Headless telemetry journal.
+//!
+//! Appends one `FrameSample` per frame to a JSON-lines journal, exits once they have all
+//! been written, then reads the journal back and checks it has no sequence gaps.
+//! `cargo run --example telemetry_journal` doubles as an end-to-end test.
+use bevy::{app::ScheduleRunnerPlugin, diagnostic::FrameCount, log::LogPlugin, prelude::*};
+use bevy_io_sink::{load_journal, prelude::*};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Resource, Debug)]
+struct FrameSample {
+    frame: u32,
+    delta_secs: f32,
+}
+
+const SAMPLES: u32 = 30;
+const TIMEOUT_FRAME: u32 = 600;
+
+fn main() -> AppExit {
+    let path = std::env::temp_dir().join("bevy_io_sink_telemetry.jsonl");
+    let _ = std::fs::remove_file(&path);
+
+    let exit = App::new()
+        .add_plugins(LogPlugin::default())
+        .add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Default::default())))
+        .add_plugins(JournalSinkPlugin::<FrameSample>::new(path.clone()))
+        .add_systems(Update, (sample, exit_when_written).chain())
+        .run();
+    if exit.is_error() {
+        return exit;
+    }
+
+    let report = futures_lite::future::block_on(load_journal::<FrameSample>(path)).unwrap();
+    info!(
+        "{} records, {} gaps, {} corrupt lines",
+        report.records.len(),
+        report.gaps.len(),
+        report.corrupt_lines
+    );
+    assert_eq!(report.records.len(), SAMPLES as usize);
+    assert!(report.gaps.is_empty());
+    assert_eq!(report.corrupt_lines, 0);
+    AppExit::Success
+}
+
+fn sample(frames: Res<FrameCount>, time: Res<Time>, sender: Res<IoSender<FrameSample>>) {
+    if frames.0 >= SAMPLES {
+        return;
+    }
+    let sample = FrameSample {
+        frame: frames.0,
+        delta_secs: time.delta_secs(),
+    };
+    if let Err(err) = sender.enqueue(sample) {
+        error!("{err}");
+    }
+}
+
+fn exit_when_written(
+    mut written: Local<u32>,
+    frames: Res<FrameCount>,
+    mut completed: EventReader<SaveCompleted<FrameSample>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    *written += completed.read().count() as u32;
+    if *written >= SAMPLES {
+        exit.write(AppExit::Success);
+    } else if frames.0 > TIMEOUT_FRAME {
+        error!("only {} of {SAMPLES} samples were written", *written);
+        exit.write(AppExit::error());
+    }
+}
@@ -0,0 +1,104 @@
+//! Visit counter persisted in the browser's `localStorage`.
+//!
+//! On `wasm32` with the `wasm` feature, [`FileSinkPlugin`] stores the save under a
+//! `localStorage` key named after its path instead of in a file. `Visits` is loaded, counts
+//! this run and is saved, then the app exits; reload the page to see the count go up, or
+//! look for the `bevy_io_sink/visits.json` key in the browser's storage inspector.
+//!
+//! ```sh
+//! cargo run --example wasm_local_storage --features wasm --target wasm32-unknown-unknown
+//! ```
+//!
+//! with a runner such as `wasm-server-runner`. Natively the same code saves to a file in
+//! the temp directory, so `cargo run --example wasm_local_storage --features wasm` doubles
+//! as an end-to-end test: it checks the count went up by one.
+use bevy::{app::ScheduleRunnerPlugin, diagnostic::FrameCount, log::LogPlugin, prelude::*};
+use bevy_io_sink::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Default, Clone, Resource, Debug)]
+struct Visits {
+    count: u32,
+}
+
+const TIMEOUT_FRAME: u32 = 600;
+
+/// The `localStorage` key in browsers.
+#[cfg(target_arch = "wasm32")]
+fn save_path() -> PathBuf {
+    PathBuf::from("bevy_io_sink/visits.json")
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_path() -> PathBuf {
+    std::env::temp_dir().join("bevy_io_sink/visits.json")
+}
+
+fn main() -> AppExit {
+    let previous = read_visits();
+
+    let exit = App::new()
+        .add_plugins(LogPlugin::default())
+        .add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Default::default())))
+        .add_plugins(FileSinkPlugin::<Visits>::new(save_path()).with_sync_on_change(true))
+        .add_systems(
+            Update,
+            (count_visit, exit_when_saved)
+                .chain()
+                .run_if(resource_exists::<Visits>),
+        )
+        .run();
+    // Browsers return right away and keep running the app, there is nothing to check yet.
+    if cfg!(target_arch = "wasm32") || exit.is_error() {
+        return exit;
+    }
+
+    let count = read_visits();
+    info!("visit {count} saved to {}", save_path().display());
+    assert_eq!(count, previous + 1);
+    AppExit::Success
+}
+
+/// The count saved by previous runs, `0` on the first.
+#[cfg(not(target_arch = "wasm32"))]
+fn read_visits() -> u32 {
+    std::fs::read(save_path())
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<Visits>(&bytes).ok())
+        .map_or(0, |visits| visits.count)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_visits() -> u32 {
+    0
+}
+
+fn count_visit(mut loaded: EventReader<LoadCompleted<Visits>>, mut visits: ResMut<Visits>) {
+    for completed in loaded.read() {
+        visits.count += 1;
+        info!(
+            "visit {} (loaded from {:?})",
+            visits.count, completed.source
+        );
+    }
+}
+
+fn exit_when_saved(
+    visits: Res<Visits>,
+    frames: Res<FrameCount>,
+    mut completed: EventReader<SaveCompleted<Visits>>,
+    mut failed: EventReader<SinkFailed<Visits>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for failure in failed.read() {
+        error!("{:?} failed: {}", failure.phase, failure.error);
+        exit.write(AppExit::error());
+    }
+    if visits.count > 0 && completed.read().count() > 0 {
+        exit.write(AppExit::Success);
+    } else if frames.0 > TIMEOUT_FRAME {
+        error!("the visit was not saved within {TIMEOUT_FRAME} frames");
+        exit.write(AppExit::error());
+    }
+}
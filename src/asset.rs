@@ -0,0 +1,140 @@
+use crate::{
+    load, ready::LoadTrackerPlugin, FileSink, IoSender, IoSinkPlugin, IoSinks, LoadSet, LoadTracker,
+};
+use async_channel::{unbounded, Receiver};
+use async_std::path::PathBuf;
+use bevy::{prelude::*, tasks::IoTaskPool};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// The asset persisted by an [`AssetPersistPlugin<A>`]. Inserted with a new handle if the save
+/// is loaded before one was provided.
+#[derive(Resource, Debug, Clone)]
+pub struct PersistedAsset<A: Asset>(pub Handle<A>);
+
+/// A copy of the persisted asset, sent through the sink of an [`AssetPersistPlugin`].
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AssetSnapshot<A>(pub A);
+
+/// Saves the asset behind [`PersistedAsset<A>`] whenever it is modified, and restores it into
+/// [`Assets<A>`] at startup, e.g. user-tuned post-processing settings.
+///
+/// ```ignore
+/// let handle = app.world_mut().resource_mut::<Assets<Grading>>().add(Grading::default());
+/// app.insert_resource(PersistedAsset(handle))
+///     .add_plugins(AssetPersistPlugin::<Grading>::new("saves/grading.json"));
+/// ```
+pub struct AssetPersistPlugin<A> {
+    path: PathBuf,
+    _marker: PhantomData<fn() -> A>,
+}
+
+impl<A> AssetPersistPlugin<A> {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct AssetReceiver<A>(Receiver<Option<A>>);
+
+impl<A> Plugin for AssetPersistPlugin<A>
+where
+    A: Asset + Clone + Serialize + DeserializeOwned,
+{
+    fn build(&self, app: &mut App) {
+        let sink = FileSink::<AssetSnapshot<A>>::new(self.path.clone());
+        app.add_plugins(IoSinkPlugin::<AssetSnapshot<A>, _>::new(sink));
+        app.world_mut()
+            .resource_mut::<IoSinks>()
+            .set_path::<AssetSnapshot<A>>(self.path.clone());
+
+        if !app.is_plugin_added::<LoadTrackerPlugin>() {
+            app.add_plugins(LoadTrackerPlugin);
+        }
+        app.world_mut()
+            .resource_mut::<LoadTracker>()
+            .register::<AssetSnapshot<A>>();
+
+        let (tx, rx) = unbounded();
+        app.insert_resource(AssetReceiver::<A>(rx));
+        app.add_systems(PreUpdate, restore_asset::<A>.in_set(LoadSet));
+        // Never before the load, the initial asset would overwrite the saved one.
+        app.add_systems(
+            Update,
+            save_asset::<A>
+                .run_if(|tracker: Res<LoadTracker>| tracker.is_loaded::<AssetSnapshot<A>>()),
+        );
+
+        let path = self.path.clone();
+        app.add_systems(Startup, move || {
+            let path = path.clone();
+            let tx = tx.clone();
+            IoTaskPool::get()
+                .spawn(async move {
+                    let asset = load::read_save::<A>(&path).await.unwrap_or_else(|e| {
+                        error!("asset save {} is unreadable: {e}", path.display());
+                        None
+                    });
+                    let _ = tx.send(asset).await;
+                })
+                .detach();
+        });
+    }
+}
+
+fn restore_asset<A>(
+    mut commands: Commands,
+    receiver: Res<AssetReceiver<A>>,
+    persisted: Option<Res<PersistedAsset<A>>>,
+    mut assets: ResMut<Assets<A>>,
+    mut tracker: ResMut<LoadTracker>,
+) where
+    A: Asset,
+{
+    let Ok(asset) = receiver.0.try_recv() else {
+        return;
+    };
+    if let Some(asset) = asset {
+        match persisted {
+            Some(persisted) => {
+                assets.insert(persisted.0.id(), asset);
+            }
+            None => commands.insert_resource(PersistedAsset(assets.add(asset))),
+        }
+    }
+    tracker.mark_loaded::<AssetSnapshot<A>>();
+}
+
+fn save_asset<A>(
+    sender: Res<IoSender<AssetSnapshot<A>>>,
+    persisted: Option<Res<PersistedAsset<A>>>,
+    assets: Res<Assets<A>>,
+    mut events: EventReader<AssetEvent<A>>,
+) where
+    A: Asset + Clone,
+{
+    let Some(persisted) = persisted else {
+        events.clear();
+        return;
+    };
+    let id = persisted.0.id();
+    let modified = events
+        .read()
+        .filter(|event| event.is_added(id) || event.is_modified(id))
+        .count()
+        > 0;
+    if !modified && !persisted.is_changed() {
+        return;
+    }
+    let Some(asset) = assets.get(id) else {
+        return;
+    };
+    if let Err(err) = sender.enqueue(AssetSnapshot(asset.clone())) {
+        error!("{err}");
+    }
+}
@@ -0,0 +1,323 @@
+use crate::{
+    codec, FileSinkPlugin, IoSinkError, IoSinkStats, LoadFailed, LoadTracker, SinkFailed,
+    WorldSaveExt,
+};
+use async_std::path::PathBuf;
+use bevy::{
+    app::{AppExit, TaskPoolPlugin},
+    ecs::event::EventCursor,
+    log::LogPlugin,
+    prelude::*,
+    time::TimePlugin,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fmt, fs,
+    io::ErrorKind,
+    path::Path,
+    str::FromStr,
+    thread,
+    time::{Duration, Instant},
+};
+
+const DEFAULT_BATCH_TIMEOUT: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// One step of a [`BatchRunner`] script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOperation {
+    /// Load every save the way the game does at startup, failing on unreadable ones.
+    Load,
+    /// Decode every save file through the migration chain without loading it.
+    Verify,
+    /// Load every save and write it back in the current schema.
+    Migrate,
+    /// Write every loaded resource as pretty JSON into the directory, one file per save.
+    Export(PathBuf),
+}
+
+/// Parses `load`, `verify`, `migrate` and `export=<dir>`.
+impl FromStr for BatchOperation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some(("export", dir)) => Ok(Self::Export(dir.into())),
+            None if s == "load" => Ok(Self::Load),
+            None if s == "verify" => Ok(Self::Verify),
+            None if s == "migrate" => Ok(Self::Migrate),
+            _ => Err(format!("unknown batch operation `{s}`")),
+        }
+    }
+}
+
+impl fmt::Display for BatchOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Load => f.write_str("load"),
+            Self::Verify => f.write_str("verify"),
+            Self::Migrate => f.write_str("migrate"),
+            Self::Export(dir) => write!(f, "export={}", dir.display()),
+        }
+    }
+}
+
+/// The type-erased operations on one persisted resource.
+struct BatchJob {
+    name: &'static str,
+    path: PathBuf,
+    verify: Box<dyn Fn() -> Result<(), IoSinkError>>,
+    /// Load and write errors since the last call.
+    errors: Box<dyn FnMut(&World) -> Vec<String>>,
+    save: fn(&World) -> Result<bool, String>,
+    written: fn(&World) -> u64,
+    export: fn(&World, &Path) -> Result<bool, IoSinkError>,
+}
+
+/// Runs a script of persistence operations against a game's saves, using the game's own
+/// types and [`FileSinkPlugin`] configurations in a minimal headless app, e.g. to verify or
+/// migrate saves in a build pipeline.
+///
+/// ```ignore
+/// fn main() -> AppExit {
+///     BatchRunner::new()
+///         .with_resource(settings_plugin())
+///         .with_resource(FileSinkPlugin::<Progress>::new("saves/progress.json"))
+///         .with_args(std::env::args().skip(1))
+///         .run()
+/// }
+/// ```
+pub struct BatchRunner {
+    app: App,
+    jobs: Vec<BatchJob>,
+    operations: Vec<BatchOperation>,
+    timeout: Duration,
+    loaded: bool,
+    /// An argument passed to [`BatchRunner::with_args`] that isn't an operation.
+    invalid_arg: Option<String>,
+}
+
+impl Default for BatchRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatchRunner {
+    pub fn new() -> Self {
+        let mut app = App::new();
+        app.add_plugins((LogPlugin::default(), TaskPoolPlugin::default(), TimePlugin));
+        Self {
+            app,
+            jobs: Vec::new(),
+            operations: Vec::new(),
+            timeout: DEFAULT_BATCH_TIMEOUT,
+            loaded: false,
+            invalid_arg: None,
+        }
+    }
+
+    /// Include the save of `R`. Only the operations of the script write to it, syncing and
+    /// autosave are turned off and a missing save is left missing.
+    pub fn with_resource<R>(mut self, plugin: FileSinkPlugin<R>) -> Self
+    where
+        R: DeserializeOwned + Clone + Serialize + Resource + Default,
+    {
+        let plugin = plugin.read_only();
+        let path = plugin.path().to_owned();
+        let migrations = plugin.migrations().clone();
+        let verify_path = path.clone();
+        let mut load_failures = EventCursor::<LoadFailed<R>>::default();
+        let mut write_failures = EventCursor::<SinkFailed<R>>::default();
+
+        self.jobs.push(BatchJob {
+            name: std::any::type_name::<R>(),
+            path,
+            verify: Box::new(move || {
+                let bytes = match fs::read(&verify_path) {
+                    Ok(bytes) => bytes,
+                    Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+                    Err(e) => return Err(e.into()),
+                };
+                codec::decode::<R>(&bytes, &migrations).map(|_| ())
+            }),
+            errors: Box::new(move |world| {
+                let load = load_failures
+                    .read(world.resource::<Events<LoadFailed<R>>>())
+                    .map(|failed| failed.error.to_string());
+                let write = write_failures
+                    .read(world.resource::<Events<SinkFailed<R>>>())
+                    .map(|failed| failed.error.to_string());
+                load.chain(write).collect()
+            }),
+            save: |world| {
+                if !world.contains_resource::<R>() {
+                    return Ok(false);
+                }
+                world
+                    .save_resource::<R>()
+                    .map(|()| true)
+                    .map_err(|e| e.to_string())
+            },
+            written: |world| world.resource::<IoSinkStats<R>>().written,
+            export: |world, path| {
+                let Some(value) = world.get_resource::<R>() else {
+                    return Ok(false);
+                };
+                let json = serde_json::to_vec_pretty(value).map_err(IoSinkError::serialization)?;
+                fs::write(path, json)?;
+                Ok(true)
+            },
+        });
+        self.app.add_plugins(plugin);
+        self
+    }
+
+    pub fn with_operation(mut self, operation: BatchOperation) -> Self {
+        self.operations.push(operation);
+        self
+    }
+
+    /// Parse the script from command line arguments, see [`BatchOperation::from_str`]. An
+    /// unknown argument fails the run before anything is touched.
+    pub fn with_args(mut self, args: impl IntoIterator<Item = String>) -> Self {
+        for arg in args {
+            match arg.parse() {
+                Ok(operation) => self.operations.push(operation),
+                Err(e) => {
+                    self.invalid_arg.get_or_insert(e);
+                }
+            }
+        }
+        self
+    }
+
+    /// How long loads and writes may take before the run fails, 30 seconds by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run every operation in order, stopping at the first that fails.
+    pub fn run(mut self) -> AppExit {
+        if let Some(e) = self.invalid_arg.take() {
+            error!("{e}");
+            return AppExit::error();
+        }
+        if self.jobs.is_empty() {
+            error!("nothing to do, no resource was added to the batch");
+            return AppExit::error();
+        }
+        self.app.finish();
+        self.app.cleanup();
+
+        for operation in std::mem::take(&mut self.operations) {
+            info!("{operation}");
+            let result = match &operation {
+                BatchOperation::Load => self.load(),
+                BatchOperation::Verify => self.verify(),
+                BatchOperation::Migrate => self.migrate(),
+                BatchOperation::Export(dir) => self.export(dir),
+            };
+            if let Err(e) = result {
+                error!("{operation} failed: {e}");
+                return AppExit::error();
+            }
+        }
+        AppExit::Success
+    }
+
+    fn update(&mut self) -> Result<(), String> {
+        self.app.update();
+        let world = self.app.world();
+        let errors: Vec<_> = self
+            .jobs
+            .iter_mut()
+            .flat_map(|job| {
+                (job.errors)(world)
+                    .into_iter()
+                    .map(|e| format!("{}: {e}", job.name))
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join(", "))
+        }
+    }
+
+    /// Update until `done`, failing on any load or write error.
+    fn update_until(&mut self, what: &str, done: impl Fn(&World) -> bool) -> Result<(), String> {
+        let started = Instant::now();
+        loop {
+            self.update()?;
+            if done(self.app.world()) {
+                return Ok(());
+            }
+            if started.elapsed() > self.timeout {
+                return Err(format!("timed out waiting for {what}"));
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    fn load(&mut self) -> Result<(), String> {
+        if self.loaded {
+            return Ok(());
+        }
+        self.update_until("the saves to load", |world| {
+            world.resource::<LoadTracker>().all_loaded()
+        })?;
+        self.loaded = true;
+        Ok(())
+    }
+
+    fn verify(&mut self) -> Result<(), String> {
+        let errors: Vec<_> = self
+            .jobs
+            .iter()
+            .filter_map(|job| {
+                let error = (job.verify)().err()?;
+                Some(format!("{}: {error}", job.path.display()))
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join(", "))
+        }
+    }
+
+    fn migrate(&mut self) -> Result<(), String> {
+        self.load()?;
+        let mut expected = Vec::new();
+        for job in &self.jobs {
+            let world = self.app.world();
+            if (job.save)(world).map_err(|e| format!("{}: {e}", job.name))? {
+                expected.push(((job.written)(world) + 1, job.written));
+            }
+        }
+        self.update_until("the migrated saves to be written", |world| {
+            expected
+                .iter()
+                .all(|(target, written)| written(world) >= *target)
+        })
+    }
+
+    fn export(&mut self, dir: &PathBuf) -> Result<(), String> {
+        self.load()?;
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        for job in &self.jobs {
+            let Some(file_name) = job.path.file_name() else {
+                continue;
+            };
+            let target = Path::new(dir).join(file_name);
+            match (job.export)(self.app.world(), &target) {
+                Ok(true) => info!("exported {} to {}", job.name, target.display()),
+                Ok(false) => warn!("{} has no save to export", job.name),
+                Err(e) => return Err(format!("{}: {e}", job.name)),
+            }
+        }
+        Ok(())
+    }
+}
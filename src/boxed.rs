@@ -0,0 +1,54 @@
+use crate::{IoWriter, SinkResult, WriterCapabilities};
+use std::{future::Future, pin::Pin};
+
+type WriterFuture<'a> = Pin<Box<dyn Future<Output = SinkResult> + Send + 'a>>;
+
+/// [`IoWriter`] with boxed futures, so writers of different types can sit in one list.
+pub(crate) trait DynWriter<R>: Send + Sync + 'static {
+    fn init(&mut self) -> WriterFuture<'_>;
+    fn write(&mut self, data: R) -> WriterFuture<'_>;
+    fn flush(&mut self) -> WriterFuture<'_>;
+    fn close(&mut self) -> WriterFuture<'_>;
+    fn last_write_len(&self) -> Option<u64>;
+    fn last_write_changed(&self) -> Option<u64>;
+    fn capabilities(&self) -> WriterCapabilities;
+    fn wipe(&mut self) -> WriterFuture<'_>;
+}
+
+impl<R, W> DynWriter<R> for W
+where
+    R: Send + Sync + 'static,
+    W: IoWriter<R>,
+{
+    fn init(&mut self) -> WriterFuture<'_> {
+        Box::pin(IoWriter::init(self))
+    }
+
+    fn write(&mut self, data: R) -> WriterFuture<'_> {
+        Box::pin(IoWriter::write(self, data))
+    }
+
+    fn flush(&mut self) -> WriterFuture<'_> {
+        Box::pin(IoWriter::flush(self))
+    }
+
+    fn close(&mut self) -> WriterFuture<'_> {
+        Box::pin(IoWriter::close(self))
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        IoWriter::last_write_len(self)
+    }
+
+    fn last_write_changed(&self) -> Option<u64> {
+        IoWriter::last_write_changed(self)
+    }
+
+    fn capabilities(&self) -> WriterCapabilities {
+        IoWriter::capabilities(self)
+    }
+
+    fn wipe(&mut self) -> WriterFuture<'_> {
+        Box::pin(IoWriter::wipe(self))
+    }
+}
@@ -0,0 +1,268 @@
+use crate::{IoSinkError, IoWriter, SinkControl, SinkResult, WriterCapabilities};
+use async_channel::Sender;
+use bevy::prelude::*;
+use std::{collections::VecDeque, marker::PhantomData, time::Duration};
+
+const DEFAULT_CAPACITY: usize = 64;
+
+/// Holds messages back and hands them to the inner writer in one go on `flush`, or once
+/// `capacity` messages are waiting, so append-style writers such as
+/// [`JournalSink`](crate::JournalSink) or a network stream write in batches.
+///
+/// Nothing flushes on its own: pair it with
+/// [`IoSinkPlugin::with_flush_interval`](crate::IoSinkPlugin::with_flush_interval) so
+/// messages don't wait for the next full batch. Messages still waiting are written before
+/// the writer closes.
+///
+/// Acknowledgements are deferred: once a message is queued its `write` succeeds, so
+/// [`SaveCompleted<R>`](crate::SaveCompleted) and [`IoSinkStats<R>`](crate::IoSinkStats)
+/// count it before it reaches the inner writer. A failed batch stays queued and only `flush`
+/// and `close` report the error, so retries and dead letters never see a message twice.
+/// Nothing is dropped: while `capacity` messages wait and the batch still fails, `write`
+/// fails with [`IoSinkError::BufferFull`] without queuing the message, which is then
+/// retried or dead-lettered like any failed write.
+///
+/// ```ignore
+/// let sink = BufferedSink::new(JournalSink::new("logs/events.jsonl")).with_capacity(256);
+/// app.add_plugins(
+///     IoSinkPlugin::<GameEvent, _>::new(sink).with_flush_interval(Duration::from_secs(2)),
+/// );
+/// ```
+pub struct BufferedSink<R, W> {
+    inner: W,
+    pending: VecDeque<R>,
+    capacity: usize,
+    last_write_len: Option<u64>,
+    last_write_changed: Option<u64>,
+}
+
+impl<R, W> BufferedSink<R, W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            pending: VecDeque::new(),
+            capacity: DEFAULT_CAPACITY,
+            last_write_len: None,
+            last_write_changed: None,
+        }
+    }
+
+    /// Messages held back before they're written without waiting for a flush, 64 by
+    /// default.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    /// Messages written to this sink but not to the inner writer yet.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+impl<R, W> BufferedSink<R, W>
+where
+    R: Clone + Send + Sync + 'static,
+    W: IoWriter<R>,
+{
+    /// Write every waiting message in order, stopping at the first failure with the failed
+    /// message kept first in line.
+    async fn drain(&mut self) -> SinkResult {
+        let mut len = None;
+        let mut changed = None;
+        while let Some(msg) = self.pending.front() {
+            self.inner.write(msg.clone()).await?;
+            self.pending.pop_front();
+            len = add(len, self.inner.last_write_len());
+            changed = add(changed, self.inner.last_write_changed());
+        }
+        self.last_write_len = len;
+        self.last_write_changed = changed;
+        Ok(())
+    }
+}
+
+fn add(total: Option<u64>, bytes: Option<u64>) -> Option<u64> {
+    match (total, bytes) {
+        (Some(total), Some(bytes)) => Some(total + bytes),
+        (total, bytes) => total.or(bytes),
+    }
+}
+
+impl<R, W> IoWriter<R> for BufferedSink<R, W>
+where
+    R: Clone + Send + Sync + 'static,
+    W: IoWriter<R>,
+{
+    async fn init(&mut self) -> SinkResult {
+        self.inner.init().await
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        // The batch failed before, make room by writing it rather than dropping from it.
+        let drained = self.pending.len() >= self.capacity;
+        if drained {
+            if let Err(e) = self.drain().await {
+                warn!("buffered sink could not write its batch, rejecting the message: {e}");
+                return Err(IoSinkError::BufferFull {
+                    capacity: self.capacity,
+                });
+            }
+        }
+        self.pending.push_back(data);
+        if self.pending.len() >= self.capacity {
+            match self.drain().await {
+                Ok(()) => return Ok(()),
+                Err(e) => warn!("buffered sink could not write its batch, keeping it: {e}"),
+            }
+        }
+        if !drained {
+            self.last_write_len = Some(0);
+            self.last_write_changed = Some(0);
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        self.drain().await?;
+        self.inner.flush().await
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        self.drain().await?;
+        self.inner.close().await
+    }
+
+    /// Bytes of the batch the most recent `write` handed over, `0` if it only queued.
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+
+    fn last_write_changed(&self) -> Option<u64> {
+        self.last_write_changed
+    }
+
+    fn capabilities(&self) -> WriterCapabilities {
+        self.inner.capabilities()
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        self.pending.clear();
+        self.inner.wipe().await
+    }
+}
+
+/// Sends [`SinkControl::Flush`] to the sink of `R` on a fixed interval, see
+/// [`IoSinkPlugin::with_flush_interval`](crate::IoSinkPlugin::with_flush_interval).
+#[derive(Resource)]
+pub(crate) struct FlushTimer<R> {
+    timer: Timer,
+    control: Sender<SinkControl>,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R> FlushTimer<R> {
+    pub(crate) fn new(interval: Duration, control: Sender<SinkControl>) -> Self {
+        Self {
+            timer: Timer::new(interval, TimerMode::Repeating),
+            control,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Ticks on real time, so a paused game still gets its buffered messages written.
+pub(crate) fn flush_periodically<R: Send + Sync + 'static>(
+    mut flush: ResMut<FlushTimer<R>>,
+    time: Res<Time<Real>>,
+) {
+    if !flush.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    if let Err(err) = flush.control.try_send(SinkControl::Flush) {
+        error!("{}: {err}", std::any::type_name::<R>());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_lite::future::block_on;
+    use std::sync::{Arc, Mutex};
+
+    /// Records what it is given, failing every write while `down` is set.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        down: Arc<Mutex<bool>>,
+        written: Arc<Mutex<Vec<u32>>>,
+    }
+
+    impl Recorder {
+        fn set_down(&self, down: bool) {
+            *self.down.lock().unwrap() = down;
+        }
+
+        fn written(&self) -> Vec<u32> {
+            self.written.lock().unwrap().clone()
+        }
+    }
+
+    impl IoWriter<u32> for Recorder {
+        async fn write(&mut self, data: u32) -> SinkResult {
+            if *self.down.lock().unwrap() {
+                return Err(IoSinkError::Other("down".into()));
+            }
+            self.written.lock().unwrap().push(data);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn writes_full_batches_in_order() {
+        let inner = Recorder::default();
+        let mut sink = BufferedSink::new(inner.clone()).with_capacity(2);
+        block_on(sink.write(1)).unwrap();
+        assert!(inner.written().is_empty());
+        block_on(sink.write(2)).unwrap();
+        assert_eq!(inner.written(), [1, 2]);
+        assert_eq!(sink.pending(), 0);
+    }
+
+    #[test]
+    fn failed_batches_stay_queued_until_flushed() {
+        let inner = Recorder::default();
+        let mut sink = BufferedSink::new(inner.clone()).with_capacity(2);
+        inner.set_down(true);
+        block_on(sink.write(1)).unwrap();
+        block_on(sink.write(2)).unwrap();
+        assert!(block_on(sink.flush()).is_err());
+        assert_eq!(sink.pending(), 2);
+
+        inner.set_down(false);
+        block_on(sink.flush()).unwrap();
+        assert_eq!(inner.written(), [1, 2]);
+    }
+
+    #[test]
+    fn rejects_messages_instead_of_dropping_queued_ones() {
+        let inner = Recorder::default();
+        let mut sink = BufferedSink::new(inner.clone()).with_capacity(2);
+        inner.set_down(true);
+        block_on(sink.write(1)).unwrap();
+        block_on(sink.write(2)).unwrap();
+        assert!(matches!(
+            block_on(sink.write(3)),
+            Err(IoSinkError::BufferFull { capacity: 2 })
+        ));
+        assert_eq!(sink.pending(), 2);
+
+        inner.set_down(false);
+        block_on(sink.write(3)).unwrap();
+        block_on(sink.flush()).unwrap();
+        assert_eq!(inner.written(), [1, 2, 3]);
+    }
+}
@@ -0,0 +1,287 @@
+use crate::{IoSinkError, IoSinks, SinkErrorLog};
+use async_channel::{unbounded, Receiver, Sender};
+use async_std::path::PathBuf;
+use bevy::{prelude::*, tasks::IoTaskPool};
+use serde::Serialize;
+use serde_json::Value;
+use std::{
+    io::{Cursor, Write},
+    sync::Arc,
+};
+use zip::{result::ZipError, write::SimpleFileOptions, ZipWriter};
+
+const REDACTED: &str = "<redacted>";
+const DEFAULT_ERROR_CAPACITY: usize = 32;
+
+/// Trigger to write a bug report zip to `path`:
+/// `commands.trigger(BugReportRequest::new("bug-report.zip"))`.
+#[derive(Event, Debug, Clone)]
+pub struct BugReportRequest {
+    pub path: PathBuf,
+}
+
+impl BugReportRequest {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+/// Emitted once the zip requested by a [`BugReportRequest`] is on disk.
+#[derive(Event, Debug)]
+pub struct BugReportWritten {
+    pub path: PathBuf,
+}
+
+/// Emitted when the zip requested by a [`BugReportRequest`] could not be written.
+#[derive(Event, Debug)]
+pub struct BugReportFailed {
+    pub path: PathBuf,
+    pub error: IoSinkError,
+}
+
+/// Packages every file-backed save and journal, the recent [`SinkErrorLog`] and version
+/// info into a zip on [`BugReportRequest`]. Values under the redacted keys are replaced in
+/// every JSON document, and files are stored by name only so absolute paths don't leak.
+pub struct BugReportPlugin {
+    redacted_keys: Vec<String>,
+    error_capacity: usize,
+    app_version: Option<String>,
+}
+
+impl Default for BugReportPlugin {
+    fn default() -> Self {
+        Self {
+            redacted_keys: Vec::new(),
+            error_capacity: DEFAULT_ERROR_CAPACITY,
+            app_version: None,
+        }
+    }
+}
+
+impl BugReportPlugin {
+    /// Replace the value of every JSON object field named like one of `keys`, at any depth.
+    pub fn with_redacted_keys(mut self, keys: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.redacted_keys = keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// How many recent sink errors are kept for the report.
+    pub fn with_error_capacity(mut self, capacity: usize) -> Self {
+        self.error_capacity = capacity;
+        self
+    }
+
+    /// Game version recorded in the report manifest.
+    pub fn with_app_version(mut self, version: impl Into<String>) -> Self {
+        self.app_version = Some(version.into());
+        self
+    }
+}
+
+impl Plugin for BugReportPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world().contains_resource::<SinkErrorLog>() {
+            app.insert_resource(SinkErrorLog::new(self.error_capacity));
+        }
+        let (tx, rx) = unbounded();
+        app.insert_resource(BugReportConfig {
+            redacted_keys: self.redacted_keys.clone().into(),
+            app_version: self.app_version.clone(),
+            tx,
+        });
+        app.insert_resource(BugReportReceiver(rx));
+        app.add_event::<BugReportWritten>();
+        app.add_event::<BugReportFailed>();
+        app.add_observer(on_bug_report_request);
+        app.add_systems(PreUpdate, forward_bug_reports);
+    }
+}
+
+type BugReportResult = (PathBuf, Result<(), IoSinkError>);
+
+#[derive(Resource)]
+struct BugReportConfig {
+    redacted_keys: Arc<[String]>,
+    app_version: Option<String>,
+    tx: Sender<BugReportResult>,
+}
+
+#[derive(Resource)]
+struct BugReportReceiver(Receiver<BugReportResult>);
+
+#[derive(Serialize)]
+struct Manifest {
+    crate_version: &'static str,
+    app_version: Option<String>,
+    os: &'static str,
+    arch: &'static str,
+    files: Vec<ManifestFile>,
+    errors: Vec<ManifestError>,
+}
+
+#[derive(Serialize)]
+struct ManifestFile {
+    sink: &'static str,
+    /// Name inside the zip, absent when the file could not be read.
+    entry: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ManifestError {
+    sink: &'static str,
+    phase: String,
+    error: String,
+}
+
+fn on_bug_report_request(
+    trigger: Trigger<BugReportRequest>,
+    sinks: Res<IoSinks>,
+    error_log: Res<SinkErrorLog>,
+    config: Res<BugReportConfig>,
+) {
+    let path = trigger.event().path.clone();
+    let files: Vec<_> = sinks
+        .files()
+        .map(|(sink, path)| (sink, path.to_path_buf()))
+        .collect();
+    let manifest = Manifest {
+        crate_version: env!("CARGO_PKG_VERSION"),
+        app_version: config.app_version.clone(),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        files: Vec::new(),
+        errors: error_log
+            .iter()
+            .map(|logged| ManifestError {
+                sink: logged.sink,
+                phase: format!("{:?}", logged.phase),
+                error: logged.error.clone(),
+            })
+            .collect(),
+    };
+    let redacted_keys = config.redacted_keys.clone();
+    let tx = config.tx.clone();
+
+    IoTaskPool::get()
+        .spawn(async move {
+            let written = async {
+                let zip = build_report(files, manifest, &redacted_keys).await?;
+                if let Some(parent) = path.parent() {
+                    async_fs::create_dir_all(parent).await?;
+                }
+                async_fs::write(&path, zip).await?;
+                Ok(())
+            }
+            .await;
+            let _ = tx.send((path, written)).await;
+        })
+        .detach();
+}
+
+async fn build_report(
+    files: Vec<(&'static str, PathBuf)>,
+    mut manifest: Manifest,
+    redacted_keys: &[String],
+) -> Result<Vec<u8>, IoSinkError> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default();
+
+    for (index, (sink, path)) in files.into_iter().enumerate() {
+        let contents = match async_fs::read_to_string(&path).await {
+            Ok(contents) => contents,
+            Err(e) => {
+                manifest.files.push(ManifestFile {
+                    sink,
+                    entry: None,
+                    error: Some(e.to_string()),
+                });
+                continue;
+            }
+        };
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        // Prefixed with the index, different directories may hold files of the same name.
+        let entry = format!("saves/{index}-{file_name}");
+        zip.start_file(entry.as_str(), options).map_err(zip_error)?;
+        zip.write_all(redact(&contents, redacted_keys).as_bytes())?;
+        manifest.files.push(ManifestFile {
+            sink,
+            entry: Some(entry),
+            error: None,
+        });
+    }
+
+    let manifest = serde_json::to_vec_pretty(&manifest).map_err(IoSinkError::serialization)?;
+    zip.start_file("manifest.json", options)
+        .map_err(zip_error)?;
+    zip.write_all(&manifest)?;
+    Ok(zip.finish().map_err(zip_error)?.into_inner())
+}
+
+fn zip_error(err: ZipError) -> IoSinkError {
+    IoSinkError::Other(err.to_string())
+}
+
+/// Redact a JSON document, or a JSON-lines journal line by line. Anything that isn't JSON
+/// can't be redacted and is left out.
+fn redact(contents: &str, keys: &[String]) -> String {
+    if keys.is_empty() {
+        return contents.to_owned();
+    }
+    if let Ok(mut value) = serde_json::from_str::<Value>(contents) {
+        redact_value(&mut value, keys);
+        return serde_json::to_string_pretty(&value).unwrap_or_default();
+    }
+    contents
+        .lines()
+        .map(|line| match serde_json::from_str::<Value>(line) {
+            Ok(mut value) => {
+                redact_value(&mut value, keys);
+                value.to_string()
+            }
+            Err(_) => "<unparseable line removed>".to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn redact_value(value: &mut Value, keys: &[String]) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if keys.iter().any(|redacted| redacted == key) {
+                    *value = Value::String(REDACTED.to_owned());
+                } else {
+                    redact_value(value, keys);
+                }
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                redact_value(value, keys);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn forward_bug_reports(
+    receiver: Res<BugReportReceiver>,
+    mut written: EventWriter<BugReportWritten>,
+    mut failed: EventWriter<BugReportFailed>,
+) {
+    while let Ok((path, result)) = receiver.0.try_recv() {
+        match result {
+            Ok(()) => {
+                written.write(BugReportWritten { path });
+            }
+            Err(error) => {
+                error!("{error}");
+                failed.write(BugReportFailed { path, error });
+            }
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use crate::stats::SinkShared;
+use async_channel::{bounded, unbounded, Receiver, Sender, TrySendError};
+use bevy::prelude::*;
+use std::{fmt, sync::Arc, time::Duration};
+
+/// How often a [`OverflowPolicy::Block`]ed sender checks for room.
+#[cfg(not(target_arch = "wasm32"))]
+const BLOCK_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// What [`IoSender::enqueue`] does when a bounded sink channel is full.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Block the calling thread for up to `timeout` until the sink task makes room, then
+    /// discard the new message like [`OverflowPolicy::DropNewest`]. Gives up early if the
+    /// task exits. Never blocks on `wasm32`, where the caller is the only thread.
+    Block { timeout: Duration },
+    /// Discard the oldest queued message to make room for the new one.
+    #[default]
+    DropOldest,
+    /// Discard the new message, [`IoSender::enqueue`] returns [`EnqueueError::Full`].
+    DropNewest,
+    /// Discard everything queued, only the latest value is kept.
+    KeepLatest,
+}
+
+/// Capacity and overflow behaviour of the channel feeding a sink task.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ChannelConfig {
+    /// `None` means unbounded.
+    pub capacity: Option<usize>,
+    pub policy: OverflowPolicy,
+}
+
+impl ChannelConfig {
+    pub fn bounded(capacity: usize, policy: OverflowPolicy) -> Self {
+        Self {
+            capacity: Some(capacity),
+            policy,
+        }
+    }
+
+    pub(crate) fn channel<R>(&self) -> (Sender<R>, Receiver<R>) {
+        match self.capacity {
+            Some(capacity) => bounded(capacity.max(1)),
+            None => unbounded(),
+        }
+    }
+}
+
+/// Why [`IoSender::enqueue`] rejected a message, the message is handed back.
+#[derive(PartialEq, Eq)]
+pub enum EnqueueError<R> {
+    /// The channel is full and the policy is [`OverflowPolicy::DropNewest`], or
+    /// [`OverflowPolicy::Block`] timed out.
+    Full(R),
+    Closed(R),
+    /// The sink task has exited (init failure or panic), nothing would ever write `R`.
+    SinkDead(R),
+}
+
+impl<R> EnqueueError<R> {
+    pub fn into_inner(self) -> R {
+        match self {
+            Self::Full(msg) | Self::Closed(msg) | Self::SinkDead(msg) => msg,
+        }
+    }
+}
+
+impl<R> From<TrySendError<R>> for EnqueueError<R> {
+    fn from(err: TrySendError<R>) -> Self {
+        match err {
+            TrySendError::Full(msg) => Self::Full(msg),
+            TrySendError::Closed(msg) => Self::Closed(msg),
+        }
+    }
+}
+
+impl<R> fmt::Debug for EnqueueError<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full(_) => f.write_str("Full(..)"),
+            Self::Closed(_) => f.write_str("Closed(..)"),
+            Self::SinkDead(_) => f.write_str("SinkDead(..)"),
+        }
+    }
+}
+
+impl<R> fmt::Display for EnqueueError<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Full(_) => f.write_str("sending into a full sink channel"),
+            Self::Closed(_) => f.write_str("sending into a closed sink channel"),
+            Self::SinkDead(_) => f.write_str("sending to a sink whose task has exited"),
+        }
+    }
+}
+
+impl<R> std::error::Error for EnqueueError<R> {}
+
+/// Queues messages for the sink task of `R`. Only [`IoSender::enqueue`] sends, so every
+/// message goes through the [`OverflowPolicy`] and the dead-task check.
+#[derive(Resource)]
+pub struct IoSender<R> {
+    tx: Sender<R>,
+    /// Kept so overflow policies can evict queued messages.
+    rx: Receiver<R>,
+    policy: OverflowPolicy,
+    shared: Arc<SinkShared>,
+}
+
+impl<R> Clone for IoSender<R> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            rx: self.rx.clone(),
+            policy: self.policy,
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<R> IoSender<R> {
+    pub(crate) fn new(
+        tx: Sender<R>,
+        rx: Receiver<R>,
+        policy: OverflowPolicy,
+        shared: Arc<SinkShared>,
+    ) -> Self {
+        Self {
+            tx,
+            rx,
+            policy,
+            shared,
+        }
+    }
+
+    pub(crate) fn shared(&self) -> &SinkShared {
+        &self.shared
+    }
+
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    /// Whether the sink task has exited.
+    pub fn is_dead(&self) -> bool {
+        self.shared.is_dead()
+    }
+
+    /// Messages queued and not yet taken by the sink task.
+    pub fn len(&self) -> usize {
+        self.tx.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tx.is_empty()
+    }
+
+    /// Queue `msg` for the sink, applying the configured [`OverflowPolicy`] when the
+    /// channel is full.
+    pub fn enqueue(&self, msg: R) -> Result<(), EnqueueError<R>> {
+        if self.shared.is_dead() {
+            return Err(EnqueueError::SinkDead(msg));
+        }
+
+        let mut msg = match self.tx.try_send(msg) {
+            Ok(()) => return Ok(()),
+            Err(TrySendError::Full(msg)) => msg,
+            Err(err) => return Err(err.into()),
+        };
+
+        match self.policy {
+            OverflowPolicy::Block { timeout } => self.send_timeout(msg, timeout),
+            OverflowPolicy::DropNewest => {
+                self.shared.record_dropped(1);
+                Err(EnqueueError::Full(msg))
+            }
+            OverflowPolicy::DropOldest | OverflowPolicy::KeepLatest => loop {
+                if self.policy == OverflowPolicy::KeepLatest {
+                    while self.rx.try_recv().is_ok() {
+                        self.shared.record_dropped(1);
+                    }
+                } else if self.rx.try_recv().is_ok() {
+                    self.shared.record_dropped(1);
+                }
+                match self.tx.try_send(msg) {
+                    Ok(()) => return Ok(()),
+                    Err(TrySendError::Full(m)) => msg = m,
+                    Err(err) => return Err(err.into()),
+                }
+            },
+        }
+    }
+
+    /// Poll for room until `timeout`: a paused task only reads its controls and a dead one
+    /// never reads again, and neither would wake a sender waiting on the channel.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn send_timeout(&self, mut msg: R, timeout: Duration) -> Result<(), EnqueueError<R>> {
+        let deadline = bevy::platform::time::Instant::now() + timeout;
+        loop {
+            std::thread::sleep(BLOCK_POLL_INTERVAL);
+            if self.shared.is_dead() {
+                return Err(EnqueueError::SinkDead(msg));
+            }
+            match self.tx.try_send(msg) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Full(m)) => msg = m,
+                Err(err) => return Err(err.into()),
+            }
+            if bevy::platform::time::Instant::now() >= deadline {
+                self.shared.record_dropped(1);
+                return Err(EnqueueError::Full(msg));
+            }
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn send_timeout(&self, msg: R, _timeout: Duration) -> Result<(), EnqueueError<R>> {
+        self.shared.record_dropped(1);
+        Err(EnqueueError::Full(msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HeartbeatConfig;
+
+    fn sender(capacity: usize, policy: OverflowPolicy) -> IoSender<u32> {
+        let (tx, rx) = ChannelConfig::bounded(capacity, policy).channel();
+        let shared = Arc::new(SinkShared::new(HeartbeatConfig::default()));
+        IoSender::new(tx, rx, policy, shared)
+    }
+
+    fn queued(sender: &IoSender<u32>) -> Vec<u32> {
+        std::iter::from_fn(|| sender.rx.try_recv().ok()).collect()
+    }
+
+    #[test]
+    fn drop_oldest_evicts_the_front() {
+        let sender = sender(2, OverflowPolicy::DropOldest);
+        for msg in 1..=3 {
+            sender.enqueue(msg).unwrap();
+        }
+        assert_eq!(queued(&sender), [2, 3]);
+    }
+
+    #[test]
+    fn drop_newest_rejects_the_message() {
+        let sender = sender(2, OverflowPolicy::DropNewest);
+        sender.enqueue(1).unwrap();
+        sender.enqueue(2).unwrap();
+        assert_eq!(sender.enqueue(3), Err(EnqueueError::Full(3)));
+        assert_eq!(queued(&sender), [1, 2]);
+    }
+
+    #[test]
+    fn keep_latest_discards_everything_queued() {
+        let sender = sender(3, OverflowPolicy::KeepLatest);
+        for msg in 1..=4 {
+            sender.enqueue(msg).unwrap();
+        }
+        assert_eq!(queued(&sender), [4]);
+    }
+
+    #[test]
+    fn block_gives_up_after_its_timeout() {
+        let timeout = Duration::from_millis(20);
+        let sender = sender(1, OverflowPolicy::Block { timeout });
+        sender.enqueue(1).unwrap();
+        assert_eq!(sender.enqueue(2), Err(EnqueueError::Full(2)));
+        assert_eq!(queued(&sender), [1]);
+    }
+
+    #[test]
+    fn dead_sink_rejects_messages() {
+        let sender = sender(1, OverflowPolicy::DropOldest);
+        drop(crate::stats::DeadOnDrop(sender.shared.clone()));
+        assert_eq!(sender.enqueue(1), Err(EnqueueError::SinkDead(1)));
+    }
+}
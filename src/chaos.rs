@@ -0,0 +1,125 @@
+use crate::{rng::Rng, IoSinkError, IoWriter, SinkResult, WriterCapabilities};
+use async_std::{io, task::sleep};
+use std::time::Duration;
+
+/// Makes any writer slow and flaky on purpose, to see how the game behaves on bad storage
+/// or a bad network before players do: saves that take seconds, writes that fail now and
+/// then, and writes that land but are reported as failed.
+///
+/// ```ignore
+/// let sink = ChaosSink::new(FileSink::new("saves/world.json"))
+///     .with_latency(Duration::from_millis(200), Duration::from_secs(2))
+///     .with_error_rate(0.1)
+///     .with_partial_write_rate(0.05);
+/// app.add_plugins(IoSinkPlugin::<World, _>::new(sink).with_retry(RetryPolicy::new(3)));
+/// ```
+pub struct ChaosSink<W> {
+    inner: W,
+    /// Delay range before every `init`, `write` and `flush`.
+    latency: Option<(Duration, Duration)>,
+    error_rate: f64,
+    partial_write_rate: f64,
+    rng: Rng,
+}
+
+impl<W> ChaosSink<W> {
+    /// Passes everything through untouched until configured.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            latency: None,
+            error_rate: 0.0,
+            partial_write_rate: 0.0,
+            rng: Rng::from_time(),
+        }
+    }
+
+    /// Wait a uniformly random time between `min` and `max` before every `init`, `write`
+    /// and `flush`.
+    pub fn with_latency(mut self, min: Duration, max: Duration) -> Self {
+        self.latency = Some((min, max.max(min)));
+        self
+    }
+
+    /// Fail this fraction of writes without passing them on.
+    pub fn with_error_rate(mut self, rate: f64) -> Self {
+        self.error_rate = rate;
+        self
+    }
+
+    /// Fail this fraction of writes after the inner writer handled them, like a connection
+    /// lost before the acknowledgement or a file left half-written. Retries then write the
+    /// same message again.
+    pub fn with_partial_write_rate(mut self, rate: f64) -> Self {
+        self.partial_write_rate = rate;
+        self
+    }
+
+    /// Inject the same delays and failures on every run.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    async fn delay(&mut self) {
+        if let Some((min, max)) = self.latency {
+            sleep(min + (max - min).mul_f64(self.rng.next_f64())).await;
+        }
+    }
+}
+
+fn injected(what: &str) -> IoSinkError {
+    IoSinkError::Io(io::Error::other(format!("chaos: injected {what}")))
+}
+
+impl<R, W> IoWriter<R> for ChaosSink<W>
+where
+    R: Send + Sync + 'static,
+    W: IoWriter<R>,
+{
+    async fn init(&mut self) -> SinkResult {
+        self.delay().await;
+        self.inner.init().await
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        self.delay().await;
+        if self.rng.chance(self.error_rate) {
+            return Err(injected("write failure"));
+        }
+        self.inner.write(data).await?;
+        if self.rng.chance(self.partial_write_rate) {
+            return Err(injected("partial write"));
+        }
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        self.delay().await;
+        self.inner.flush().await
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        self.inner.close().await
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.inner.last_write_len()
+    }
+
+    fn last_write_changed(&self) -> Option<u64> {
+        self.inner.last_write_changed()
+    }
+
+    fn capabilities(&self) -> WriterCapabilities {
+        self.inner.capabilities()
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        self.inner.wipe().await
+    }
+}
@@ -0,0 +1,155 @@
+use bevy::{platform::time::Instant, prelude::*};
+use std::{marker::PhantomData, time::Duration};
+
+/// Stops writing to a persistently failing sink for a cool-down period instead of
+/// serializing and failing every message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreaker {
+    /// Consecutive failed writes that open the circuit.
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a trial write is allowed.
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CircuitState {
+    /// Writes go through.
+    Closed,
+    /// Writes are skipped until the cool-down elapses.
+    Open,
+    /// The cool-down elapsed, the next write decides whether the circuit closes again.
+    HalfOpen,
+}
+
+/// Emitted whenever the circuit breaker of the sink for `R` changes state.
+#[derive(Event, Debug)]
+pub struct CircuitStateChanged<R> {
+    pub state: CircuitState,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R> CircuitStateChanged<R> {
+    pub(crate) fn new(state: CircuitState) -> Self {
+        Self {
+            state,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Runtime state of a [`CircuitBreaker`] inside a sink task.
+pub(crate) struct Breaker {
+    config: CircuitBreaker,
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+impl Breaker {
+    pub(crate) fn new(config: CircuitBreaker) -> Self {
+        Self {
+            config,
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+        }
+    }
+
+    fn transition(&mut self, state: CircuitState) -> Option<CircuitState> {
+        (self.state != state).then(|| {
+            self.state = state;
+            state
+        })
+    }
+
+    /// Whether a write may be attempted, along with the state change this caused.
+    pub(crate) fn allow(&mut self) -> (bool, Option<CircuitState>) {
+        match self.state {
+            CircuitState::Closed | CircuitState::HalfOpen => (true, None),
+            CircuitState::Open => {
+                let cooled_down = self
+                    .opened_at
+                    .is_none_or(|at| at.elapsed() >= self.config.cooldown);
+                if cooled_down {
+                    (true, self.transition(CircuitState::HalfOpen))
+                } else {
+                    (false, None)
+                }
+            }
+        }
+    }
+
+    pub(crate) fn record_success(&mut self) -> Option<CircuitState> {
+        self.consecutive_failures = 0;
+        self.transition(CircuitState::Closed)
+    }
+
+    pub(crate) fn record_failure(&mut self) -> Option<CircuitState> {
+        self.consecutive_failures += 1;
+        let trip = self.state == CircuitState::HalfOpen
+            || self.consecutive_failures >= self.config.failure_threshold;
+        if trip {
+            self.opened_at = Some(Instant::now());
+            self.transition(CircuitState::Open)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(cooldown: Duration) -> Breaker {
+        Breaker::new(CircuitBreaker {
+            failure_threshold: 2,
+            cooldown,
+        })
+    }
+
+    #[test]
+    fn opens_after_the_failure_threshold() {
+        let mut breaker = breaker(Duration::from_secs(60));
+        assert_eq!(breaker.record_failure(), None);
+        assert_eq!(breaker.record_failure(), Some(CircuitState::Open));
+        assert_eq!(breaker.allow(), (false, None));
+    }
+
+    #[test]
+    fn success_resets_the_failure_count() {
+        let mut breaker = breaker(Duration::from_secs(60));
+        breaker.record_failure();
+        assert_eq!(breaker.record_success(), None);
+        assert_eq!(breaker.record_failure(), None);
+        assert_eq!(breaker.allow(), (true, None));
+    }
+
+    #[test]
+    fn half_open_after_the_cooldown_then_closes_on_success() {
+        let mut breaker = breaker(Duration::ZERO);
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_eq!(breaker.allow(), (true, Some(CircuitState::HalfOpen)));
+        assert_eq!(breaker.allow(), (true, None));
+        assert_eq!(breaker.record_success(), Some(CircuitState::Closed));
+    }
+
+    #[test]
+    fn a_failed_trial_write_opens_it_again() {
+        let mut breaker = breaker(Duration::ZERO);
+        breaker.record_failure();
+        breaker.record_failure();
+        breaker.allow();
+        assert_eq!(breaker.record_failure(), Some(CircuitState::Open));
+    }
+}
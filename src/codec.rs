@@ -0,0 +1,255 @@
+//! Pure decoding steps of the load path, free of file and ECS concerns so they can be
+//! fuzzed directly against malformed saves.
+
+use crate::IoSinkError;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{fmt, sync::Arc};
+
+/// Encoding used when writing saves. Every format is read back by [`decode`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SaveFormat {
+    #[default]
+    Json,
+    /// Indented JSON, easier to read and diff by hand.
+    JsonPretty,
+}
+
+impl SaveFormat {
+    pub fn encode<T: Serialize + ?Sized>(self, value: &T) -> Result<Vec<u8>, IoSinkError> {
+        match self {
+            Self::Json => serde_json::to_vec(value),
+            Self::JsonPretty => serde_json::to_vec_pretty(value),
+        }
+        .map_err(IoSinkError::serialization)
+    }
+}
+
+/// Envelope fields of a decoded record, see [`Envelope`](crate::Envelope).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnvelopeMeta {
+    pub seq: u64,
+    pub timestamp_ms: u64,
+    pub frame: u32,
+}
+
+/// A record split into its envelope and still-untyped payload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded {
+    pub envelope: Option<EnvelopeMeta>,
+    /// Schema version of the payload, `0` for records written without an envelope.
+    pub version: u32,
+    pub payload: Value,
+}
+
+/// Tag field [`Envelope`](crate::Envelope)s are marked with, and its value.
+const ENVELOPE_TAG: (&str, &str) = ("$record", "envelope");
+
+/// Fields of an envelope written before it was tagged, besides the optional `version`.
+const UNTAGGED_ENVELOPE_FIELDS: [&str; 4] = ["seq", "timestamp_ms", "frame", "payload"];
+
+/// Whether `object` is an envelope: tagged as one, or written before envelopes were tagged
+/// and made of exactly their fields.
+fn is_envelope(object: &serde_json::Map<String, Value>) -> bool {
+    let (tag, name) = ENVELOPE_TAG;
+    if let Some(value) = object.get(tag) {
+        return value == name;
+    }
+    UNTAGGED_ENVELOPE_FIELDS
+        .iter()
+        .all(|key| object.contains_key(*key))
+        && object
+            .keys()
+            .all(|key| key == "version" || UNTAGGED_ENVELOPE_FIELDS.contains(&key.as_str()))
+}
+
+/// Parse `bytes` as JSON and unwrap the envelope if there is one. Save metadata around the
+/// record is dropped, see [`SaveMetadata`](crate::SaveMetadata).
+pub fn decode_envelope(bytes: &[u8]) -> Result<Decoded, IoSinkError> {
+    let mut value: Value = serde_json::from_slice(bytes).map_err(IoSinkError::deserialization)?;
+
+    let has_metadata = value.as_object().is_some_and(|object| {
+        object.len() == 2 && object.contains_key("metadata") && object.contains_key("payload")
+    });
+    if has_metadata {
+        value = value["payload"].take();
+    }
+
+    if !value.as_object().is_some_and(is_envelope) {
+        return Ok(Decoded {
+            envelope: None,
+            version: 0,
+            payload: value,
+        });
+    }
+
+    let Value::Object(mut object) = value else {
+        unreachable!()
+    };
+    let field = |object: &serde_json::Map<String, Value>, key: &str| {
+        object.get(key).and_then(Value::as_u64).ok_or_else(|| {
+            IoSinkError::deserialization(format!("envelope field `{key}` is not an integer"))
+        })
+    };
+    let envelope = EnvelopeMeta {
+        seq: field(&object, "seq")?,
+        timestamp_ms: field(&object, "timestamp_ms")?,
+        frame: u32::try_from(field(&object, "frame")?).map_err(IoSinkError::deserialization)?,
+    };
+    let version = match object.get("version") {
+        Some(_) => {
+            u32::try_from(field(&object, "version")?).map_err(IoSinkError::deserialization)?
+        }
+        None => 0,
+    };
+
+    Ok(Decoded {
+        envelope: Some(envelope),
+        version,
+        payload: object.remove("payload").unwrap_or_default(),
+    })
+}
+
+type MigrationStep = Arc<dyn Fn(Value) -> Result<Value, IoSinkError> + Send + Sync>;
+
+/// Ordered schema migrations, step `n` upgrades a payload from version `n` to `n + 1`.
+#[derive(Clone, Default)]
+pub struct Migrations {
+    steps: Vec<MigrationStep>,
+}
+
+impl fmt::Debug for Migrations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Migrations")
+            .field("current_version", &self.current_version())
+            .finish()
+    }
+}
+
+impl Migrations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append the step upgrading the current version to the next one.
+    pub fn then(
+        mut self,
+        step: impl Fn(Value) -> Result<Value, IoSinkError> + Send + Sync + 'static,
+    ) -> Self {
+        self.steps.push(Arc::new(step));
+        self
+    }
+
+    /// The version written by this build.
+    pub fn current_version(&self) -> u32 {
+        self.steps.len() as u32
+    }
+}
+
+/// Upgrade `payload` from `from_version` to [`Migrations::current_version`].
+pub fn apply_migrations(
+    payload: Value,
+    from_version: u32,
+    migrations: &Migrations,
+) -> Result<Value, IoSinkError> {
+    let current = migrations.current_version();
+    if from_version > current {
+        return Err(IoSinkError::deserialization(format!(
+            "save version {from_version} is newer than supported version {current}"
+        )));
+    }
+    migrations.steps[from_version as usize..]
+        .iter()
+        .try_fold(payload, |payload, step| step(payload))
+}
+
+pub fn deserialize<R>(payload: Value) -> Result<R, IoSinkError>
+where
+    R: DeserializeOwned,
+{
+    serde_json::from_value(payload).map_err(IoSinkError::deserialization)
+}
+
+/// The whole pipeline: [`decode_envelope`], [`apply_migrations`] then [`deserialize`].
+pub fn decode<R>(bytes: &[u8], migrations: &Migrations) -> Result<R, IoSinkError>
+where
+    R: DeserializeOwned,
+{
+    let decoded = decode_envelope(bytes)?;
+    let payload = apply_migrations(decoded.payload, decoded.version, migrations)?;
+    deserialize(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Envelope;
+    use serde_json::json;
+
+    fn decode_json(value: Value) -> Decoded {
+        decode_envelope(&serde_json::to_vec(&value).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn written_envelopes_are_unwrapped() {
+        let envelope = Envelope {
+            seq: 4,
+            timestamp_ms: 1_000,
+            frame: 12,
+            version: 2,
+            payload: json!({"hp": 10}),
+        };
+        let decoded = decode_envelope(&serde_json::to_vec(&envelope).unwrap()).unwrap();
+        assert_eq!(
+            decoded.envelope,
+            Some(EnvelopeMeta {
+                seq: 4,
+                timestamp_ms: 1_000,
+                frame: 12,
+            })
+        );
+        assert_eq!(decoded.version, 2);
+        assert_eq!(decoded.payload, json!({"hp": 10}));
+    }
+
+    #[test]
+    fn untagged_envelopes_are_still_read() {
+        let decoded = decode_json(json!({
+            "seq": 1, "timestamp_ms": 2, "frame": 3, "payload": [1, 2],
+        }));
+        assert!(decoded.envelope.is_some());
+        assert_eq!(decoded.version, 0);
+        assert_eq!(decoded.payload, json!([1, 2]));
+    }
+
+    #[test]
+    fn payloads_shaped_like_envelopes_are_kept_whole() {
+        let payload = json!({
+            "seq": 1, "timestamp_ms": 2, "frame": 3, "payload": "x", "name": "replay",
+        });
+        let decoded = decode_json(payload.clone());
+        assert_eq!(decoded.envelope, None);
+        assert_eq!(decoded.payload, payload);
+
+        let payload =
+            json!({"$record": "other", "seq": 1, "timestamp_ms": 2, "frame": 3, "payload": 4});
+        assert_eq!(decode_json(payload.clone()).payload, payload);
+    }
+
+    #[test]
+    fn metadata_is_dropped() {
+        let decoded = decode_json(json!({
+            "metadata": {"playtime_ms": 5},
+            "payload": {"$record": "envelope", "seq": 0, "timestamp_ms": 0, "frame": 0, "payload": true},
+        }));
+        assert!(decoded.envelope.is_some());
+        assert_eq!(decoded.payload, json!(true));
+    }
+
+    #[test]
+    fn malformed_envelope_fields_are_errors() {
+        let bytes = br#"{"$record": "envelope", "seq": "one", "timestamp_ms": 0, "frame": 0, "payload": 1}"#;
+        assert!(decode_envelope(bytes).is_err());
+        assert!(decode_envelope(b"{").is_err());
+    }
+}
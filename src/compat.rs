@@ -0,0 +1,98 @@
+use crate::{codec, Migrations};
+use serde::de::DeserializeOwned;
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Outcome of decoding one historical save.
+#[derive(Debug, Clone)]
+pub struct SaveCheck {
+    pub path: PathBuf,
+    /// `None` when the save decoded into the current `R`.
+    pub error: Option<String>,
+}
+
+/// Result of [`check_saves`], meant to be asserted on in a downstream test so old saves
+/// can't silently stop loading.
+#[derive(Debug, Clone, Default)]
+pub struct CompatibilityReport {
+    pub checked: Vec<SaveCheck>,
+}
+
+impl CompatibilityReport {
+    pub fn failures(&self) -> impl Iterator<Item = &SaveCheck> {
+        self.checked.iter().filter(|check| check.error.is_some())
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// Panic with every failing file listed.
+    #[track_caller]
+    pub fn assert_ok(&self) {
+        if !self.is_ok() {
+            panic!("{self}");
+        }
+    }
+}
+
+impl fmt::Display for CompatibilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failures: Vec<_> = self.failures().collect();
+        writeln!(
+            f,
+            "{} of {} saves failed to load",
+            failures.len(),
+            self.checked.len()
+        )?;
+        for check in failures {
+            writeln!(
+                f,
+                "  {}: {}",
+                check.path.display(),
+                check.error.as_deref().unwrap_or_default()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Decode every file in `dir` as `R` through the same pipeline as the startup load.
+pub fn check_saves<R>(dir: impl AsRef<Path>) -> io::Result<CompatibilityReport>
+where
+    R: DeserializeOwned,
+{
+    check_saves_with_migrations::<R>(dir, &Migrations::default())
+}
+
+/// Like [`check_saves`], running every save through the current migration chain first.
+pub fn check_saves_with_migrations<R>(
+    dir: impl AsRef<Path>,
+    migrations: &Migrations,
+) -> io::Result<CompatibilityReport>
+where
+    R: DeserializeOwned,
+{
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    paths.retain(|path| path.is_file());
+    paths.sort();
+
+    let checked = paths
+        .into_iter()
+        .map(|path| {
+            let error = match fs::read_to_string(&path) {
+                Ok(buf) => codec::decode::<R>(buf.as_bytes(), migrations)
+                    .err()
+                    .map(|e| e.to_string()),
+                Err(e) => Some(e.to_string()),
+            };
+            SaveCheck { path, error }
+        })
+        .collect();
+
+    Ok(CompatibilityReport { checked })
+}
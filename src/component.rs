@@ -0,0 +1,225 @@
+use crate::{
+    codec, ready::LoadTrackerPlugin, FileSink, IoSender, IoSinkError, IoSinkPlugin, IoSinks,
+    LoadSet, LoadTracker, Migrations,
+};
+use async_channel::{unbounded, Receiver};
+use async_std::path::{Path, PathBuf};
+use bevy::{
+    ecs::entity::{EntityHashMap, MapEntities},
+    prelude::*,
+    tasks::IoTaskPool,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{collections::HashMap, io::ErrorKind, marker::PhantomData, time::Duration};
+
+const DEFAULT_SNAPSHOT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Stable identity of a persisted entity, entity ids themselves change between runs.
+#[derive(
+    Component, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "scene", derive(Reflect), reflect(Component))]
+pub struct PersistId(pub u64);
+
+/// One entity in a [`ComponentSnapshot`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistedComponent<C> {
+    pub id: PersistId,
+    /// The entity at save time, lets [`Entity`] references inside other saved components be
+    /// remapped on load, see [`ComponentSinkPlugin::with_entity_mapping`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entity: Option<Entity>,
+    pub component: C,
+}
+
+/// Every `C` on an entity with a [`PersistId`], ordered by id.
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentSnapshot<C: Component> {
+    pub entities: Vec<PersistedComponent<C>>,
+}
+
+/// Periodically snapshots every entity with both `C` and a [`PersistId`] into a file. At
+/// startup the file is read back: entities whose [`PersistId`] already exists get their `C`
+/// replaced, the others are spawned. [`Entity`] references inside `C` can be remapped to the
+/// restored entities with [`ComponentSinkPlugin::with_entity_mapping`].
+pub struct ComponentSinkPlugin<C> {
+    path: PathBuf,
+    interval: Duration,
+    map_entities: Option<RemapFn<C>>,
+    _marker: PhantomData<fn() -> C>,
+}
+
+type RemapFn<C> = fn(&mut C, &mut EntityHashMap<Entity>);
+
+impl<C> ComponentSinkPlugin<C> {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            interval: DEFAULT_SNAPSHOT_INTERVAL,
+            map_entities: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// How often to snapshot, 5 seconds by default.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+impl<C: MapEntities> ComponentSinkPlugin<C> {
+    /// Remap the [`Entity`] references inside each loaded `C` from the entities of the saving
+    /// session to the ones they were restored as. References to entities that weren't part of
+    /// the snapshot are left as they are.
+    pub fn with_entity_mapping(mut self) -> Self {
+        self.map_entities = Some(|component, mapper| component.map_entities(mapper));
+        self
+    }
+}
+
+#[derive(Resource)]
+struct SnapshotTimer<C> {
+    timer: Timer,
+    _marker: PhantomData<fn() -> C>,
+}
+
+#[derive(Resource)]
+struct SnapshotReceiver<C: Component> {
+    rx: Receiver<ComponentSnapshot<C>>,
+    map_entities: Option<RemapFn<C>>,
+}
+
+impl<C> Plugin for ComponentSinkPlugin<C>
+where
+    C: Component + Clone + Serialize + DeserializeOwned,
+{
+    fn build(&self, app: &mut App) {
+        let sink = FileSink::<ComponentSnapshot<C>>::new(self.path.clone());
+        app.add_plugins(IoSinkPlugin::<ComponentSnapshot<C>, _>::new(sink));
+        app.world_mut()
+            .resource_mut::<IoSinks>()
+            .set_path::<ComponentSnapshot<C>>(self.path.clone());
+
+        if !app.is_plugin_added::<LoadTrackerPlugin>() {
+            app.add_plugins(LoadTrackerPlugin);
+        }
+        app.world_mut()
+            .resource_mut::<LoadTracker>()
+            .register::<ComponentSnapshot<C>>();
+
+        let (tx, rx) = unbounded();
+        app.insert_resource(SnapshotReceiver::<C> {
+            rx,
+            map_entities: self.map_entities,
+        });
+        app.insert_resource(SnapshotTimer::<C> {
+            timer: Timer::new(self.interval, TimerMode::Repeating),
+            _marker: PhantomData,
+        });
+        app.add_systems(PreUpdate, restore_components::<C>.in_set(LoadSet));
+        // Never before the load, an empty snapshot would overwrite the save.
+        app.add_systems(
+            Update,
+            snapshot_components::<C>
+                .run_if(|tracker: Res<LoadTracker>| tracker.is_loaded::<ComponentSnapshot<C>>()),
+        );
+
+        let path = self.path.clone();
+        app.add_systems(Startup, move || {
+            let path = path.clone();
+            let tx = tx.clone();
+            IoTaskPool::get()
+                .spawn(async move {
+                    let snapshot = read_snapshot::<C>(&path).await.unwrap_or_else(|e| {
+                        error!("component save {} is unreadable: {e}", path.display());
+                        ComponentSnapshot {
+                            entities: Vec::new(),
+                        }
+                    });
+                    let _ = tx.send(snapshot).await;
+                })
+                .detach();
+        });
+    }
+}
+
+async fn read_snapshot<C>(path: &Path) -> Result<ComponentSnapshot<C>, IoSinkError>
+where
+    C: Component + DeserializeOwned,
+{
+    let bytes = match async_fs::read(path).await {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e.into()),
+    };
+    if bytes.is_empty() {
+        return Ok(ComponentSnapshot {
+            entities: Vec::new(),
+        });
+    }
+    codec::decode(&bytes, &Migrations::default())
+}
+
+fn restore_components<C>(
+    mut commands: Commands,
+    receiver: Res<SnapshotReceiver<C>>,
+    existing: Query<(Entity, &PersistId)>,
+    mut tracker: ResMut<LoadTracker>,
+) where
+    C: Component,
+{
+    let Ok(snapshot) = receiver.rx.try_recv() else {
+        return;
+    };
+    let existing: HashMap<PersistId, Entity> =
+        existing.iter().map(|(entity, id)| (*id, entity)).collect();
+    // Resolve every target first, a component may refer to an entity restored after it.
+    let targets: Vec<Entity> = snapshot
+        .entities
+        .iter()
+        .map(|persisted| match existing.get(&persisted.id) {
+            Some(&entity) => entity,
+            None => commands.spawn(persisted.id).id(),
+        })
+        .collect();
+    let mut mapper: EntityHashMap<Entity> = snapshot
+        .entities
+        .iter()
+        .zip(&targets)
+        .filter_map(|(persisted, &target)| Some((persisted.entity?, target)))
+        .collect();
+    for (persisted, target) in snapshot.entities.into_iter().zip(targets) {
+        let mut component = persisted.component;
+        if let Some(map_entities) = receiver.map_entities {
+            map_entities(&mut component, &mut mapper);
+        }
+        commands.entity(target).insert(component);
+    }
+    tracker.mark_loaded::<ComponentSnapshot<C>>();
+}
+
+fn snapshot_components<C>(
+    sender: Res<IoSender<ComponentSnapshot<C>>>,
+    mut timer: ResMut<SnapshotTimer<C>>,
+    time: Res<Time>,
+    query: Query<(Entity, &PersistId, &C)>,
+) where
+    C: Component + Clone,
+{
+    if !timer.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let mut entities: Vec<_> = query
+        .iter()
+        .map(|(entity, id, component)| PersistedComponent {
+            id: *id,
+            entity: Some(entity),
+            component: component.clone(),
+        })
+        .collect();
+    entities.sort_by_key(|persisted| persisted.id);
+    if let Err(err) = sender.enqueue(ComponentSnapshot { entities }) {
+        error!("{err}");
+    }
+}
@@ -0,0 +1,81 @@
+use crate::{IoSender, IoSinkError};
+use bevy::prelude::*;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+/// A message whose write failed after all retries.
+#[derive(Debug, Clone)]
+pub struct DeadLetter<R> {
+    pub message: R,
+    pub error: String,
+}
+
+/// Bounded buffer of messages the sink of `R` failed to write, oldest first. When full the
+/// oldest letter is discarded.
+#[derive(Resource)]
+pub struct DeadLetters<R> {
+    letters: Arc<Mutex<VecDeque<DeadLetter<R>>>>,
+    capacity: usize,
+}
+
+impl<R> Clone for DeadLetters<R> {
+    fn clone(&self) -> Self {
+        Self {
+            letters: self.letters.clone(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+impl<R> DeadLetters<R> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            letters: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub(crate) fn push(&self, message: R, error: &IoSinkError) {
+        let mut letters = self.letters.lock().unwrap();
+        if letters.len() == self.capacity {
+            letters.pop_front();
+        }
+        letters.push_back(DeadLetter {
+            message,
+            error: error.to_string(),
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.letters.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Take every letter out of the buffer.
+    pub fn drain(&self) -> Vec<DeadLetter<R>> {
+        self.letters.lock().unwrap().drain(..).collect()
+    }
+
+    /// Queue every letter for another write attempt, returns how many were re-sent.
+    pub fn resend(&self, sender: &IoSender<R>) -> usize {
+        let mut sent = 0;
+        for letter in self.drain() {
+            match sender.enqueue(letter.message) {
+                Ok(()) => sent += 1,
+                Err(err) => error!("{err}"),
+            }
+        }
+        sent
+    }
+}
+
+impl<R: Clone> DeadLetters<R> {
+    pub fn snapshot(&self) -> Vec<DeadLetter<R>> {
+        self.letters.lock().unwrap().iter().cloned().collect()
+    }
+}
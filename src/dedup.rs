@@ -0,0 +1,72 @@
+use crate::{Filter, FilterSink, SinkResult};
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    marker::PhantomData,
+};
+
+/// Skips a message equal to the last one written, however it got sent, e.g. when change
+/// detection fires for a `ResMut` access that didn't change anything, see [`Dedup`]. Built
+/// with [`IoWriterExt::dedup`](crate::IoWriterExt::dedup),
+/// [`IoWriterExt::dedup_by_hash`](crate::IoWriterExt::dedup_by_hash) or
+/// [`IoWriterExt::dedup_by_key`](crate::IoWriterExt::dedup_by_key).
+///
+/// ```ignore
+/// let sink = FileSink::new("saves/world.json").dedup_by_key(|world: &World| world.revision);
+/// app.add_plugins(IoSinkPlugin::<World, _>::new(sink));
+/// ```
+pub type DedupSink<R, W, K, F> = FilterSink<W, Dedup<R, K, F>>;
+
+/// Rejects messages with the same key, extracted by `key`, as the last one written.
+pub struct Dedup<R, K, F> {
+    key: F,
+    /// Key of the last message written, forgotten when the writer starts over.
+    last: Option<K>,
+    /// Key of the message being written.
+    pending: Option<K>,
+    _phantom: PhantomData<fn(&R)>,
+}
+
+impl<R, K, F> Dedup<R, K, F> {
+    pub fn new(key: F) -> Self {
+        Self {
+            key,
+            last: None,
+            pending: None,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// Key of [`IoWriterExt::dedup_by_hash`](crate::IoWriterExt::dedup_by_hash), cheaper to keep
+/// than a copy of a large value.
+pub(crate) fn hash_of<R: Hash>(value: &R) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<R, K, F> Filter<R> for Dedup<R, K, F>
+where
+    R: 'static,
+    K: PartialEq + Send + Sync + 'static,
+    F: Fn(&R) -> K + Send + Sync + 'static,
+{
+    fn accept(&mut self, data: &R) -> bool {
+        let key = (self.key)(data);
+        if self.last.as_ref() == Some(&key) {
+            return false;
+        }
+        self.pending = Some(key);
+        true
+    }
+
+    /// A failed write mustn't hold back the same message sent again.
+    fn written(&mut self, result: &SinkResult) {
+        let pending = self.pending.take();
+        self.last = if result.is_ok() { pending } else { None };
+    }
+
+    fn reset(&mut self) {
+        self.last = None;
+    }
+}
@@ -0,0 +1,236 @@
+use crate::{
+    load::{self, LoadOutcome, LoadResult, LoadSource},
+    ready::LoadTrackerPlugin,
+    FileSink, IoSender, IoSinkError, IoSinkPlugin, IoSinks, LoadFailed, LoadSet, LoadTracker,
+    StorageFs, UnreadableSavePolicy,
+};
+use async_channel::{unbounded, Receiver};
+use async_std::path::{Path, PathBuf};
+use bevy::{prelude::*, tasks::IoTaskPool};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::io::ErrorKind;
+
+/// Every member of a [`DocumentPlugin`] file, serialized and keyed by name. Keys without a
+/// registered resource are preserved.
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SaveDocument {
+    pub members: Map<String, Value>,
+}
+
+type InsertMember = fn(&mut Commands, &mut LoadTracker, Option<&Map<String, Value>>, &str);
+type RegisterMember = fn(&mut App, String);
+
+struct Member {
+    key: String,
+    insert: InsertMember,
+    register: RegisterMember,
+}
+
+/// Persists several resources into one file, e.g. `{"player": {...}, "settings": {...}}`.
+/// Changes made during a frame are written together as a single document.
+pub struct DocumentPlugin {
+    path: PathBuf,
+    members: Vec<Member>,
+    unreadable: UnreadableSavePolicy,
+}
+
+impl DocumentPlugin {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            members: Vec::new(),
+            unreadable: UnreadableSavePolicy::default(),
+        }
+    }
+
+    /// What to do when the document exists but can't be read or decoded as a whole, a
+    /// [`LoadFailed<SaveDocument>`] is emitted in every case. Members that fail to decode on
+    /// their own are always loaded as `R::default()`.
+    pub fn with_unreadable_save(mut self, policy: UnreadableSavePolicy) -> Self {
+        self.unreadable = policy;
+        self
+    }
+
+    /// Store `R` under `key`. A missing or undecodable member is loaded as `R::default()`.
+    pub fn with_resource<R>(mut self, key: impl Into<String>) -> Self
+    where
+        R: Resource + Serialize + DeserializeOwned + Default,
+    {
+        self.members.push(Member {
+            key: key.into(),
+            insert: insert_member::<R>,
+            register: register_member::<R>,
+        });
+        self
+    }
+}
+
+#[derive(Resource)]
+struct DocumentMembers {
+    members: Vec<(String, InsertMember)>,
+    unreadable: UnreadableSavePolicy,
+}
+
+#[derive(Resource)]
+struct DocumentReceiver(Receiver<LoadResult<Map<String, Value>>>);
+
+/// A member changed this frame and the document must be written.
+#[derive(Resource, Default)]
+struct DocumentDirty(bool);
+
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+struct SyncMembers;
+
+impl Plugin for DocumentPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(IoSinkPlugin::<SaveDocument, _>::new(
+            FileSink::<SaveDocument>::new(self.path.clone()),
+        ));
+        app.world_mut()
+            .resource_mut::<IoSinks>()
+            .set_path::<SaveDocument>(self.path.clone());
+
+        if !app.is_plugin_added::<LoadTrackerPlugin>() {
+            app.add_plugins(LoadTrackerPlugin);
+        }
+        for member in &self.members {
+            (member.register)(app, member.key.clone());
+        }
+        app.insert_resource(DocumentMembers {
+            members: self
+                .members
+                .iter()
+                .map(|member| (member.key.clone(), member.insert))
+                .collect(),
+            unreadable: self.unreadable,
+        });
+        app.init_resource::<SaveDocument>();
+        app.init_resource::<DocumentDirty>();
+        app.add_event::<LoadFailed<SaveDocument>>();
+
+        let (tx, rx) = unbounded();
+        app.insert_resource(DocumentReceiver(rx));
+        app.add_systems(PreUpdate, receive_document.in_set(LoadSet));
+        app.add_systems(Update, write_document.after(SyncMembers));
+
+        let (path, unreadable) = (self.path.clone(), self.unreadable);
+        app.add_systems(Startup, move || {
+            let path = path.clone();
+            let tx = tx.clone();
+            IoTaskPool::get()
+                .spawn(async move {
+                    let loaded = match read_document(&path).await {
+                        Ok(members) => LoadResult {
+                            value: Some(members),
+                            outcome: LoadOutcome::Loaded(LoadSource::File),
+                        },
+                        Err(e) => load::load_unreadable(&StorageFs, &path, e, unreadable).await,
+                    };
+                    let _ = tx.send(loaded).await;
+                })
+                .detach();
+        });
+    }
+}
+
+async fn read_document(path: &Path) -> Result<Map<String, Value>, IoSinkError> {
+    let bytes = match async_fs::read(path).await {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Map::new()),
+        Err(e) => return Err(e.into()),
+    };
+    if bytes.is_empty() {
+        return Ok(Map::new());
+    }
+    serde_json::from_slice(&bytes).map_err(IoSinkError::deserialization)
+}
+
+fn register_member<R>(app: &mut App, key: String)
+where
+    R: Resource + Serialize + DeserializeOwned + Default,
+{
+    app.world_mut()
+        .resource_mut::<LoadTracker>()
+        .register::<R>();
+    app.add_systems(
+        Update,
+        (move |res: Res<R>,
+               mut document: ResMut<SaveDocument>,
+               mut dirty: ResMut<DocumentDirty>| {
+            match serde_json::to_value(&*res) {
+                Ok(value) => {
+                    document.members.insert(key.clone(), value);
+                    dirty.0 = true;
+                }
+                Err(e) => error!("{}", IoSinkError::serialization(e)),
+            }
+        })
+        .run_if(resource_exists_and_changed::<R>)
+        .in_set(SyncMembers),
+    );
+}
+
+/// Insert the member of `R` stored under `key`, or leave it out when there is no `document`.
+fn insert_member<R>(
+    commands: &mut Commands,
+    tracker: &mut LoadTracker,
+    document: Option<&Map<String, Value>>,
+    key: &str,
+) where
+    R: Resource + DeserializeOwned + Default,
+{
+    if let Some(document) = document {
+        let value = match document.get(key).cloned().map(serde_json::from_value::<R>) {
+            Some(Ok(value)) => value,
+            Some(Err(e)) => {
+                error!("{}", IoSinkError::deserialization(e));
+                R::default()
+            }
+            None => R::default(),
+        };
+        commands.insert_resource(value);
+    }
+    tracker.mark_loaded::<R>();
+}
+
+fn receive_document(
+    mut commands: Commands,
+    receiver: Res<DocumentReceiver>,
+    members: Res<DocumentMembers>,
+    mut document: ResMut<SaveDocument>,
+    mut tracker: ResMut<LoadTracker>,
+    mut failed: EventWriter<LoadFailed<SaveDocument>>,
+) {
+    let Ok(LoadResult { value, outcome }) = receiver.0.try_recv() else {
+        return;
+    };
+    if let LoadOutcome::Failed { error, backup } = outcome {
+        if members.unreadable == UnreadableSavePolicy::Panic {
+            panic!("save document is unreadable: {error}");
+        }
+        failed.write(LoadFailed::new(error, backup));
+    }
+    for (key, insert) in &members.members {
+        insert(&mut commands, &mut tracker, value.as_ref(), key);
+    }
+    // Keep unknown keys, e.g. written by a newer version of the game.
+    if let Some(mut loaded) = value {
+        loaded.extend(std::mem::take(&mut document.members));
+        document.members = loaded;
+    }
+}
+
+fn write_document(
+    sender: Res<IoSender<SaveDocument>>,
+    document: Res<SaveDocument>,
+    mut dirty: ResMut<DocumentDirty>,
+) {
+    if !std::mem::take(&mut dirty.0) {
+        return;
+    }
+    if let Err(err) = sender.enqueue(document.clone()) {
+        error!("{err}");
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{IoSinkError, IoWriter, Migrations, SaveFormat, SinkResult, WriterCapabilities};
+use bevy::{diagnostic::FrameCount, prelude::*};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+        Arc,
+    },
+};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+/// Metadata wrapped around a written record so journals and telemetry can be ordered and
+/// gap-detected downstream. Serialized with a `"$record": "envelope"` tag so
+/// [`decode_envelope`](crate::codec::decode_envelope) never mistakes a payload with the same
+/// fields for one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "$record", rename = "envelope")]
+pub struct Envelope<T> {
+    /// Increases by one for every record written by the sink during a session.
+    pub seq: u64,
+    /// Milliseconds according to the app's [`EnvelopeClock`], wall-clock milliseconds since
+    /// the unix epoch by default.
+    pub timestamp_ms: u64,
+    /// [`FrameCount`] when the record was written.
+    pub frame: u32,
+    /// Schema version of `payload`, see [`Migrations`](crate::Migrations).
+    #[serde(default)]
+    pub version: u32,
+    pub payload: T,
+}
+
+/// Where envelope timestamps come from.
+#[derive(Clone, Default)]
+pub enum ClockSource {
+    /// [`SystemTime`], sampled when the record is written.
+    #[default]
+    WallClock,
+    /// Elapsed [`Time<Real>`], sampled once per frame.
+    Real,
+    /// Elapsed [`Time<Virtual>`], sampled once per frame. Stable across replays of a
+    /// deterministic simulation.
+    Virtual,
+    /// Milliseconds from a user clock, sampled once per frame.
+    Custom(Arc<dyn Fn() -> u64 + Send + Sync>),
+}
+
+impl fmt::Debug for ClockSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::WallClock => f.write_str("WallClock"),
+            Self::Real => f.write_str("Real"),
+            Self::Virtual => f.write_str("Virtual"),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// The clock used for every envelope timestamp in the app.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct EnvelopeClock(pub ClockSource);
+
+/// The app's [`FrameCount`] and [`EnvelopeClock`], mirrored for sink tasks.
+#[derive(Resource, Clone)]
+pub(crate) struct ClockMirror {
+    frame: Arc<AtomicU32>,
+    time_ms: Arc<AtomicU64>,
+    wall_clock: Arc<AtomicBool>,
+}
+
+impl Default for ClockMirror {
+    fn default() -> Self {
+        Self {
+            frame: Arc::default(),
+            time_ms: Arc::default(),
+            wall_clock: Arc::new(AtomicBool::new(true)),
+        }
+    }
+}
+
+impl ClockMirror {
+    pub(crate) fn frame(&self) -> u32 {
+        self.frame.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn timestamp_ms(&self) -> u64 {
+        if self.wall_clock.load(Ordering::Relaxed) {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis() as u64)
+        } else {
+            self.time_ms.load(Ordering::Relaxed)
+        }
+    }
+}
+
+pub(crate) struct EnvelopePlugin;
+
+impl Plugin for EnvelopePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ClockMirror>();
+        app.init_resource::<EnvelopeClock>();
+        app.add_systems(First, mirror_clock);
+    }
+}
+
+fn mirror_clock(
+    frame_count: Option<Res<FrameCount>>,
+    clock: Res<EnvelopeClock>,
+    real: Option<Res<Time<Real>>>,
+    virt: Option<Res<Time<Virtual>>>,
+    mirror: Res<ClockMirror>,
+) {
+    if let Some(frame_count) = frame_count {
+        mirror.frame.store(frame_count.0, Ordering::Relaxed);
+    }
+
+    let time_ms = match &clock.0 {
+        ClockSource::WallClock => None,
+        ClockSource::Real => real.map(|t| t.elapsed().as_millis() as u64),
+        ClockSource::Virtual => virt.map(|t| t.elapsed().as_millis() as u64),
+        ClockSource::Custom(clock) => Some(clock()),
+    };
+    mirror
+        .wall_clock
+        .store(time_ms.is_none(), Ordering::Relaxed);
+    if let Some(time_ms) = time_ms {
+        mirror.time_ms.store(time_ms, Ordering::Relaxed);
+    }
+}
+
+/// Encodes saves in an [`Envelope`] carrying the schema version, for writers that dedup on
+/// the encoded payload and so can't sit behind an [`EnvelopeSink`]. Loaders then only run
+/// the migrations newer than the save.
+#[derive(Clone)]
+pub(crate) struct VersionStamp {
+    version: u32,
+    clock: ClockMirror,
+}
+
+impl VersionStamp {
+    pub(crate) fn new(app: &mut App, migrations: &Migrations) -> Self {
+        if !app.is_plugin_added::<EnvelopePlugin>() {
+            app.add_plugins(EnvelopePlugin);
+        }
+        Self {
+            version: migrations.current_version(),
+            clock: app.world().resource::<ClockMirror>().clone(),
+        }
+    }
+
+    pub(crate) fn encode<T: Serialize>(
+        &self,
+        format: SaveFormat,
+        payload: &T,
+    ) -> Result<Vec<u8>, IoSinkError> {
+        format.encode(&Envelope {
+            seq: 0,
+            timestamp_ms: self.clock.timestamp_ms(),
+            frame: self.clock.frame(),
+            version: self.version,
+            payload,
+        })
+    }
+}
+
+/// Wraps every record in an [`Envelope`] before handing it to the inner writer.
+pub struct EnvelopeSink<W> {
+    inner: W,
+    next_seq: u64,
+    version: u32,
+    clock: ClockMirror,
+}
+
+impl<W> EnvelopeSink<W> {
+    pub(crate) fn new(inner: W, clock: &ClockMirror) -> Self {
+        Self {
+            inner,
+            next_seq: 0,
+            version: 0,
+            clock: clock.clone(),
+        }
+    }
+
+    /// Continue numbering from `seq`, e.g. after the last record of a previous session.
+    pub fn starting_at(mut self, seq: u64) -> Self {
+        self.next_seq = seq;
+        self
+    }
+
+    /// Schema version stamped on every record.
+    pub fn with_version(mut self, version: u32) -> Self {
+        self.version = version;
+        self
+    }
+}
+
+impl<R, W> IoWriter<R> for EnvelopeSink<W>
+where
+    R: Send + Sync + 'static,
+    W: IoWriter<Envelope<R>>,
+{
+    async fn init(&mut self) -> SinkResult {
+        self.inner.init().await
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let envelope = Envelope {
+            seq: self.next_seq,
+            timestamp_ms: self.clock.timestamp_ms(),
+            frame: self.clock.frame(),
+            version: self.version,
+            payload: data,
+        };
+        self.next_seq += 1;
+        self.inner.write(envelope).await
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        self.inner.flush().await
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        self.inner.close().await
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.inner.last_write_len()
+    }
+
+    fn last_write_changed(&self) -> Option<u64> {
+        self.inner.last_write_changed()
+    }
+
+    fn capabilities(&self) -> WriterCapabilities {
+        self.inner.capabilities()
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        self.inner.wipe().await
+    }
+}
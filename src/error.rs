@@ -0,0 +1,152 @@
+use async_std::io;
+use std::{error::Error, fmt};
+
+pub type BoxedError = Box<dyn Error + Send + Sync + 'static>;
+
+/// Errors produced by sinks and the IO pipeline.
+#[derive(Debug)]
+pub enum IoSinkError {
+    Io(io::Error),
+    /// The value could not be encoded.
+    Serialization(BoxedError),
+    /// Stored bytes could not be decoded.
+    Deserialization(BoxedError),
+    /// A channel between the main world and a sink task was closed.
+    ChannelClosed,
+    /// A writer was used before its `init` succeeded.
+    NotInitialized,
+    /// The serialized message exceeds the sink's configured maximum size.
+    MessageTooLarge {
+        size: u64,
+        max: u64,
+    },
+    /// A [`BufferedSink`](crate::BufferedSink) holds `capacity` messages it can't write yet,
+    /// the message was not queued.
+    BufferFull {
+        capacity: usize,
+    },
+    /// Some of the writers combined by a sink failed, e.g. the targets of a
+    /// [`FanoutSink`](crate::FanoutSink). Never empty.
+    Targets(Vec<TargetError>),
+    Other(String),
+}
+
+/// Failure of one named writer within a sink combining several.
+#[derive(Debug)]
+pub struct TargetError {
+    pub target: String,
+    pub error: IoSinkError,
+}
+
+impl IoSinkError {
+    pub fn serialization(err: impl Into<BoxedError>) -> Self {
+        Self::Serialization(err.into())
+    }
+
+    pub fn deserialization(err: impl Into<BoxedError>) -> Self {
+        Self::Deserialization(err.into())
+    }
+
+    pub fn kind(&self) -> SinkErrorKind {
+        match self {
+            Self::Io(err) => SinkErrorKind::Io(err.kind()),
+            Self::Serialization(_) => SinkErrorKind::Serialization,
+            Self::Deserialization(_) => SinkErrorKind::Deserialization,
+            Self::ChannelClosed => SinkErrorKind::ChannelClosed,
+            Self::NotInitialized => SinkErrorKind::NotInitialized,
+            Self::MessageTooLarge { .. } => SinkErrorKind::MessageTooLarge,
+            Self::BufferFull { .. } => SinkErrorKind::BufferFull,
+            Self::Targets(errors) => errors
+                .first()
+                .map_or(SinkErrorKind::Other, |failed| failed.error.kind()),
+            Self::Other(_) => SinkErrorKind::Other,
+        }
+    }
+
+    /// Whether writing the same message again could succeed. Messages that can't be encoded
+    /// or are too large fail the same way every time.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Serialization(_) | Self::MessageTooLarge { .. } => false,
+            Self::Targets(errors) => errors.iter().any(|failed| failed.error.is_retryable()),
+            _ => true,
+        }
+    }
+
+    /// Fail with [`IoSinkError::MessageTooLarge`] if `size` exceeds `max`.
+    pub fn check_size(size: usize, max: Option<u64>) -> SinkResult {
+        match max {
+            Some(max) if size as u64 > max => Err(Self::MessageTooLarge {
+                size: size as u64,
+                max,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// The underlying [`io::ErrorKind`], if this is an IO error.
+    pub fn io_kind(&self) -> Option<io::ErrorKind> {
+        match self {
+            Self::Io(err) => Some(err.kind()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for IoSinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "io error: {err}"),
+            Self::Serialization(err) => write!(f, "serialization failed: {err}"),
+            Self::Deserialization(err) => write!(f, "deserialization failed: {err}"),
+            Self::ChannelClosed => f.write_str("sink channel closed"),
+            Self::NotInitialized => f.write_str("writer used before init"),
+            Self::MessageTooLarge { size, max } => {
+                write!(f, "message of {size} bytes exceeds the {max} byte limit")
+            }
+            Self::BufferFull { capacity } => {
+                write!(f, "buffer is full with {capacity} unwritten messages")
+            }
+            Self::Targets(errors) => {
+                for (i, failed) in errors.iter().enumerate() {
+                    let separator = if i == 0 { "" } else { "; " };
+                    write!(f, "{separator}{}: {}", failed.target, failed.error)?;
+                }
+                Ok(())
+            }
+            Self::Other(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl Error for IoSinkError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Serialization(err) | Self::Deserialization(err) => Some(err.as_ref()),
+            Self::Targets(errors) => errors.first().map(|failed| &failed.error as _),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for IoSinkError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+pub type SinkResult<T = ()> = Result<T, IoSinkError>;
+
+/// Category of an [`IoSinkError`], cheap to copy into status resources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SinkErrorKind {
+    Io(io::ErrorKind),
+    Serialization,
+    Deserialization,
+    ChannelClosed,
+    NotInitialized,
+    MessageTooLarge,
+    BufferFull,
+    Other,
+}
@@ -0,0 +1,219 @@
+use crate::{CircuitState, CircuitStateChanged, IoSinkError, SinkState, SinkStatus};
+use async_channel::{Receiver, Sender};
+use bevy::prelude::*;
+use std::{collections::VecDeque, marker::PhantomData, time::Duration};
+
+/// Which [`IoWriter`](crate::IoWriter) call failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SinkPhase {
+    Init,
+    Write,
+    Flush,
+    Close,
+    Wipe,
+}
+
+/// Emitted when the sink of `R` fails, so game code can surface a "save failed" message
+/// or retry.
+#[derive(Event, Debug)]
+pub struct SinkFailed<R> {
+    pub phase: SinkPhase,
+    pub error: IoSinkError,
+    _marker: PhantomData<fn() -> R>,
+}
+
+/// Emitted once a write of `R` has actually finished, as opposed to merely being queued.
+#[derive(Event, Debug)]
+pub struct SaveCompleted<R> {
+    /// Bytes written, when the writer reports it.
+    pub bytes: Option<u64>,
+    /// Time spent in the writer, retries included.
+    pub duration: Duration,
+    _marker: PhantomData<fn() -> R>,
+}
+
+/// Emitted once the sink of `R` has deleted everything it persisted, see
+/// [`CommandsSaveExt::wipe_persisted`](crate::CommandsSaveExt::wipe_persisted).
+#[derive(Event, Debug)]
+pub struct PersistedWiped<R>(PhantomData<fn() -> R>);
+
+/// Emitted when a writer panics inside the sink task of `R`.
+#[derive(Event, Debug)]
+pub struct SinkPanicked<R> {
+    /// The panic payload, when it was a string.
+    pub message: String,
+    /// Whether the task was restarted according to its [`PanicPolicy`](crate::PanicPolicy).
+    pub restarted: bool,
+    _marker: PhantomData<fn() -> R>,
+}
+
+/// A failure recorded in the [`SinkErrorLog`].
+#[derive(Debug, Clone)]
+pub struct LoggedSinkError {
+    /// Type name of the sink's resource.
+    pub sink: &'static str,
+    pub phase: SinkPhase,
+    pub error: String,
+}
+
+/// The most recent [`SinkFailed`] errors of every sink, oldest first. Errors are only
+/// recorded while this resource exists.
+#[derive(Resource, Debug)]
+pub struct SinkErrorLog {
+    entries: VecDeque<LoggedSinkError>,
+    capacity: usize,
+}
+
+impl SinkErrorLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    fn push(&mut self, entry: LoggedSinkError) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &LoggedSinkError> {
+        self.entries.iter()
+    }
+}
+
+/// Sent from a sink task back to the main world.
+pub(crate) enum TaskReport {
+    Completed {
+        bytes: Option<u64>,
+        duration: Duration,
+    },
+    Failed {
+        phase: SinkPhase,
+        error: IoSinkError,
+    },
+    Panicked {
+        message: String,
+        restarted: bool,
+        /// Re-raise on the main thread.
+        fatal: bool,
+    },
+    Circuit(CircuitState),
+    Status(SinkState),
+    Wiped,
+}
+
+/// Task side of the report channel.
+#[derive(Clone)]
+pub(crate) struct TaskReporter(pub(crate) Sender<TaskReport>);
+
+impl TaskReporter {
+    pub(crate) fn status(&self, state: SinkState) {
+        let _ = self.0.try_send(TaskReport::Status(state));
+    }
+
+    pub(crate) fn failed(&self, phase: SinkPhase, error: IoSinkError) {
+        error!("{}", error);
+        self.status(SinkState::Errored(error.kind()));
+        let _ = self.0.try_send(TaskReport::Failed { phase, error });
+    }
+
+    pub(crate) fn completed(&self, bytes: Option<u64>, duration: Duration) {
+        self.status(SinkState::Idle);
+        let _ = self.0.try_send(TaskReport::Completed { bytes, duration });
+    }
+
+    pub(crate) fn wiped(&self) {
+        self.status(SinkState::Idle);
+        let _ = self.0.try_send(TaskReport::Wiped);
+    }
+
+    pub(crate) fn circuit(&self, state: CircuitState) {
+        warn!("circuit breaker {state:?}");
+        let _ = self.0.try_send(TaskReport::Circuit(state));
+    }
+
+    pub(crate) fn panicked(&self, message: String, restarted: bool, fatal: bool) {
+        error!("sink task panicked: {message}");
+        let _ = self.0.try_send(TaskReport::Panicked {
+            message,
+            restarted,
+            fatal,
+        });
+    }
+}
+
+#[derive(Resource)]
+pub(crate) struct TaskReportReceiver<R> {
+    pub(crate) rx: Receiver<TaskReport>,
+    pub(crate) _marker: PhantomData<fn() -> R>,
+}
+
+pub(crate) fn forward_task_reports<R>(
+    receiver: Res<TaskReportReceiver<R>>,
+    mut status: ResMut<SinkStatus<R>>,
+    mut completed: EventWriter<SaveCompleted<R>>,
+    mut errors: EventWriter<SinkFailed<R>>,
+    mut panics: EventWriter<SinkPanicked<R>>,
+    mut circuit: EventWriter<CircuitStateChanged<R>>,
+    mut wiped: EventWriter<PersistedWiped<R>>,
+    mut error_log: Option<ResMut<SinkErrorLog>>,
+) where
+    R: Send + Sync + 'static,
+{
+    while let Ok(report) = receiver.rx.try_recv() {
+        match report {
+            TaskReport::Completed { bytes, duration } => {
+                completed.write(SaveCompleted {
+                    bytes,
+                    duration,
+                    _marker: PhantomData,
+                });
+            }
+            TaskReport::Failed { phase, error } => {
+                if let Some(log) = error_log.as_mut() {
+                    log.push(LoggedSinkError {
+                        sink: std::any::type_name::<R>(),
+                        phase,
+                        error: error.to_string(),
+                    });
+                }
+                errors.write(SinkFailed {
+                    phase,
+                    error,
+                    _marker: PhantomData,
+                });
+            }
+            TaskReport::Panicked {
+                message,
+                restarted,
+                fatal,
+            } => {
+                if fatal {
+                    panic!(
+                        "sink task for {} panicked: {message}",
+                        std::any::type_name::<R>()
+                    );
+                }
+                panics.write(SinkPanicked {
+                    message,
+                    restarted,
+                    _marker: PhantomData,
+                });
+            }
+            TaskReport::Circuit(state) => {
+                circuit.write(CircuitStateChanged::new(state));
+            }
+            TaskReport::Wiped => {
+                wiped.write(PersistedWiped(PhantomData));
+            }
+            TaskReport::Status(state) => {
+                if status.state != state {
+                    status.state = state;
+                }
+            }
+        }
+    }
+}
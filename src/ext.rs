@@ -0,0 +1,336 @@
+#[cfg(feature = "file")]
+use crate::{codec, envelope::ClockMirror, load::FileLoader, Envelope, IoSinkError, LoadTracker};
+use crate::{
+    dedup::hash_of, groups::reset_to_default, Dedup, DedupSink, EnqueueError, FilterSink, IoSender,
+    IoSinks, IoWriter, Labeled, LabeledSender, Layer, Sample, SampleSink, SinkControl,
+    TransformSink,
+};
+#[cfg(feature = "debug")]
+use crate::{InspectSink, PayloadInspector};
+#[cfg(feature = "file")]
+use async_std::path::PathBuf;
+#[cfg(feature = "file")]
+use bevy::diagnostic::FrameCount;
+use bevy::prelude::*;
+#[cfg(feature = "file")]
+use serde::{de::DeserializeOwned, Serialize};
+use std::{any::type_name, hash::Hash, time::Duration};
+
+/// Direct persistence from exclusive systems and tests.
+pub trait WorldSaveExt {
+    /// Clone the current `R` into its [`IoSender<R>`].
+    ///
+    /// # Panics
+    ///
+    /// If no sink is registered for `R`, or `R` is not in the world.
+    fn save_resource<R: Resource + Clone>(&self) -> Result<(), EnqueueError<R>>;
+
+    /// Clone the current `R` into the sender of its sink labeled `L`, see [`Labeled`].
+    ///
+    /// # Panics
+    ///
+    /// If no sink labeled `L` is registered for `R`, or `R` is not in the world.
+    fn save_resource_to<R: Resource + Clone, L: 'static>(
+        &self,
+    ) -> Result<(), EnqueueError<Labeled<R, L>>>;
+
+    /// Encode the current `R` like a save file, e.g. for share codes or bug reports. The
+    /// bytes are stamped with the schema version of `R`'s
+    /// [`FileSinkPlugin`](crate::FileSinkPlugin) so [`import_resource`](Self::import_resource)
+    /// migrates them in later builds.
+    ///
+    /// # Panics
+    ///
+    /// If `R` is not in the world.
+    #[cfg(feature = "file")]
+    fn export_resource<R: Resource + Serialize>(&self) -> Result<Vec<u8>, IoSinkError>;
+
+    /// Decode `bytes` exported by [`export_resource`](Self::export_resource), or read from a
+    /// save file, and replace `R` with the result. The value goes through the migrations of
+    /// `R`'s [`FileSinkPlugin`](crate::FileSinkPlugin) and is saved like any other change.
+    /// `R` is left untouched if `bytes` can't be decoded.
+    #[cfg(feature = "file")]
+    fn import_resource<R>(&mut self, bytes: &[u8]) -> Result<(), IoSinkError>
+    where
+        R: Resource + DeserializeOwned;
+}
+
+impl WorldSaveExt for World {
+    fn save_resource<R: Resource + Clone>(&self) -> Result<(), EnqueueError<R>> {
+        let Some(sender) = self.get_resource::<IoSender<R>>() else {
+            panic!(
+                "no sink is registered for {}, add a FileSinkPlugin or IoSinkPlugin for it",
+                type_name::<R>()
+            );
+        };
+        let Some(res) = self.get_resource::<R>() else {
+            panic!("cannot save {}, it is not in the world", type_name::<R>());
+        };
+        sender.enqueue(res.clone())
+    }
+
+    fn save_resource_to<R: Resource + Clone, L: 'static>(
+        &self,
+    ) -> Result<(), EnqueueError<Labeled<R, L>>> {
+        let Some(sender) = self.get_resource::<LabeledSender<R, L>>() else {
+            panic!(
+                "no sink labeled {} is registered for {}, add an IoSinkPlugin::labeled for it",
+                type_name::<L>(),
+                type_name::<R>()
+            );
+        };
+        let Some(res) = self.get_resource::<R>() else {
+            panic!("cannot save {}, it is not in the world", type_name::<R>());
+        };
+        sender.enqueue_labeled(res.clone())
+    }
+
+    #[cfg(feature = "file")]
+    fn export_resource<R: Resource + Serialize>(&self) -> Result<Vec<u8>, IoSinkError> {
+        let Some(res) = self.get_resource::<R>() else {
+            panic!("cannot export {}, it is not in the world", type_name::<R>());
+        };
+        let version = self
+            .get_resource::<FileLoader<R>>()
+            .map_or(0, |loader| loader.migrations.current_version());
+        let envelope = Envelope {
+            seq: 0,
+            timestamp_ms: self
+                .get_resource::<ClockMirror>()
+                .cloned()
+                .unwrap_or_default()
+                .timestamp_ms(),
+            frame: self.get_resource::<FrameCount>().map_or(0, |frame| frame.0),
+            version,
+            payload: res,
+        };
+        serde_json::to_vec(&envelope).map_err(IoSinkError::serialization)
+    }
+
+    #[cfg(feature = "file")]
+    fn import_resource<R>(&mut self, bytes: &[u8]) -> Result<(), IoSinkError>
+    where
+        R: Resource + DeserializeOwned,
+    {
+        let value = match self.get_resource::<FileLoader<R>>() {
+            Some(loader) => codec::decode::<R>(bytes, &loader.migrations)?,
+            None => codec::decode::<R>(bytes, &Default::default())?,
+        };
+        self.insert_resource(value);
+        Ok(())
+    }
+}
+
+/// Persistence requests from regular systems, without injecting `Res<IoSender<R>>` and `Res<R>`.
+pub trait CommandsSaveExt {
+    /// Queue the current `R` for its sink once commands are applied. Panics like
+    /// [`WorldSaveExt::save_resource`], enqueue failures are logged.
+    fn save_resource<R: Resource + Clone>(&mut self);
+
+    /// [`save_resource`](Self::save_resource) to the sink labeled `L`, see [`Labeled`].
+    fn save_resource_to<R: Resource + Clone, L: 'static>(&mut self);
+
+    /// Read the save of `R` again. The value replaces the resource and a
+    /// [`LoadCompleted<R>`](crate::LoadCompleted) or [`LoadFailed<R>`](crate::LoadFailed)
+    /// is emitted, exactly like the startup load.
+    ///
+    /// Panics when applied if `R` is not loaded by a [`FileSinkPlugin`](crate::FileSinkPlugin).
+    #[cfg(feature = "file")]
+    fn reload_resource<R>(&mut self)
+    where
+        R: Resource + DeserializeOwned + Serialize + Default;
+
+    /// Persist `R` to `path` from now on, e.g. to switch from `slot1.json` to `slot2.json`.
+    /// Saves queued before are still written to the previous file, then the writer is
+    /// reopened on `path` and `R` is loaded from it. `R` is out of the world until the load
+    /// completes, like at startup.
+    ///
+    /// Panics when applied if `R` is not persisted by a
+    /// [`FileSinkPlugin`](crate::FileSinkPlugin).
+    #[cfg(feature = "file")]
+    fn switch_save_path<R>(&mut self, path: impl Into<PathBuf>)
+    where
+        R: Resource + DeserializeOwned + Serialize + Default;
+
+    /// Delete everything persisted for `R` on its sink task, e.g. for a data deletion
+    /// request, and reset `R` to its default without saving it. Saves still queued are
+    /// dropped. [`PersistedWiped<R>`](crate::PersistedWiped) is emitted once the data is gone,
+    /// or [`SinkFailed<R>`](crate::SinkFailed) if the writer can't delete it.
+    fn wipe_persisted<R: Resource + Default>(&mut self);
+
+    /// [`WorldSaveExt::import_resource`] once commands are applied, decoding failures are
+    /// logged.
+    #[cfg(feature = "file")]
+    fn import_resource<R>(&mut self, bytes: Vec<u8>)
+    where
+        R: Resource + DeserializeOwned;
+
+    /// [`wipe_persisted`](Self::wipe_persisted) for every sink of the app. Resources persisted
+    /// by a [`FileSinkPlugin`](crate::FileSinkPlugin) are reset to their default.
+    fn wipe_all_persisted(&mut self);
+}
+
+impl CommandsSaveExt for Commands<'_, '_> {
+    fn save_resource<R: Resource + Clone>(&mut self) {
+        self.queue(|world: &mut World| {
+            if let Err(err) = world.save_resource::<R>() {
+                error!("{err}");
+            }
+        });
+    }
+
+    fn save_resource_to<R: Resource + Clone, L: 'static>(&mut self) {
+        self.queue(|world: &mut World| {
+            if let Err(err) = world.save_resource_to::<R, L>() {
+                error!("{err}");
+            }
+        });
+    }
+
+    #[cfg(feature = "file")]
+    fn reload_resource<R>(&mut self)
+    where
+        R: Resource + DeserializeOwned + Serialize + Default,
+    {
+        self.queue(|world: &mut World| {
+            let Some(loader) = world.get_resource::<FileLoader<R>>() else {
+                panic!(
+                    "cannot reload {}, it is not loaded by a FileSinkPlugin",
+                    type_name::<R>()
+                );
+            };
+            loader.spawn();
+        });
+    }
+
+    #[cfg(feature = "file")]
+    fn switch_save_path<R>(&mut self, path: impl Into<PathBuf>)
+    where
+        R: Resource + DeserializeOwned + Serialize + Default,
+    {
+        let path = path.into();
+        self.queue(move |world: &mut World| {
+            let Some(loader) = world.get_resource::<FileLoader<R>>() else {
+                panic!(
+                    "cannot switch the save of {}, it is not persisted by a FileSinkPlugin",
+                    type_name::<R>()
+                );
+            };
+            loader.path.request(path.clone());
+            loader.spawn();
+            // Nothing may be saved to the new file before it has been read.
+            world.remove_resource::<R>();
+            world.resource_mut::<LoadTracker>().mark_unloaded::<R>();
+            let mut sinks = world.resource_mut::<IoSinks>();
+            sinks.set_path::<R>(path);
+            sinks.send_to::<R>(SinkControl::Reopen);
+        });
+    }
+
+    #[cfg(feature = "file")]
+    fn import_resource<R>(&mut self, bytes: Vec<u8>)
+    where
+        R: Resource + DeserializeOwned,
+    {
+        self.queue(move |world: &mut World| {
+            if let Err(e) = world.import_resource::<R>(&bytes) {
+                error!("cannot import {}: {e}", type_name::<R>());
+            }
+        });
+    }
+
+    fn wipe_persisted<R: Resource + Default>(&mut self) {
+        self.queue(|world: &mut World| {
+            world.resource::<IoSinks>().send_to::<R>(SinkControl::Wipe);
+            reset_to_default::<R>(world);
+        });
+    }
+
+    fn wipe_all_persisted(&mut self) {
+        self.queue(|world: &mut World| {
+            let Some(sinks) = world.get_resource::<IoSinks>() else {
+                return;
+            };
+            sinks.wipe_all();
+            let resets: Vec<_> = sinks.resets().collect();
+            for reset in resets {
+                reset(world);
+            }
+        });
+    }
+}
+
+/// Combinators available on every [`IoWriter`].
+pub trait IoWriterExt<R>: IoWriter<R> + Sized {
+    /// Wrap the writer with `layer`, or with every layer of a tuple, see [`Layer`].
+    fn layer<L: Layer<R, Self>>(self, layer: L) -> L::Writer {
+        layer.layer(self)
+    }
+
+    /// Write `transform(value)` for every `T` sent, see [`TransformSink`].
+    fn map<T, F>(self, transform: F) -> TransformSink<Self, F>
+    where
+        F: Fn(T) -> R + Send + Sync + 'static,
+    {
+        TransformSink::new(self, transform)
+    }
+
+    /// Write every `T` sent converted with `R::from`.
+    fn map_from<T>(self) -> TransformSink<Self, fn(T) -> R>
+    where
+        R: From<T>,
+    {
+        TransformSink::new(self, R::from)
+    }
+
+    /// Drop every message `predicate` rejects, see [`FilterSink`].
+    fn filter<F>(self, predicate: F) -> FilterSink<Self, F>
+    where
+        F: Fn(&R) -> bool + Send + Sync + 'static,
+    {
+        FilterSink::new(self, predicate)
+    }
+
+    /// Skip messages equal to the last one written, see [`DedupSink`]. Keeps a copy of it.
+    fn dedup(self) -> DedupSink<R, Self, R, fn(&R) -> R>
+    where
+        R: Clone + PartialEq + Send + Sync + 'static,
+    {
+        FilterSink::new(self, Dedup::new(R::clone))
+    }
+
+    /// Skip messages hashing like the last one written, see [`DedupSink`].
+    fn dedup_by_hash(self) -> DedupSink<R, Self, u64, fn(&R) -> u64>
+    where
+        R: Hash,
+    {
+        FilterSink::new(self, Dedup::new(hash_of::<R>))
+    }
+
+    /// Skip messages with the same `key` as the last one written, see [`DedupSink`].
+    fn dedup_by_key<K, F>(self, key: F) -> DedupSink<R, Self, K, F>
+    where
+        K: PartialEq + Send + Sync + 'static,
+        F: Fn(&R) -> K + Send + Sync + 'static,
+    {
+        FilterSink::new(self, Dedup::new(key))
+    }
+
+    /// Write one message out of every `n`, see [`SampleSink`].
+    fn sample_every(self, n: u64) -> SampleSink<Self> {
+        FilterSink::new(self, Sample::every(n))
+    }
+
+    /// Write at most one message per `interval`, see [`SampleSink`].
+    fn sample_interval(self, interval: Duration) -> SampleSink<Self> {
+        FilterSink::new(self, Sample::interval(interval))
+    }
+
+    /// Record the most recent writes into `inspector`, see [`InspectSink`].
+    #[cfg(feature = "debug")]
+    fn inspect(self, inspector: &PayloadInspector<R>) -> InspectSink<R, Self> {
+        InspectSink::new(self, inspector.clone())
+    }
+}
+
+impl<R, W: IoWriter<R>> IoWriterExt<R> for W {}
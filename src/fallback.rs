@@ -0,0 +1,326 @@
+use crate::{IoSinkError, IoWriter, SinkResult, TargetError, WriterCapabilities};
+use async_channel::{unbounded, Receiver, Sender};
+use bevy::{platform::time::Instant, prelude::*};
+use std::{
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+const DEFAULT_RECHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Emitted when the sink of `R` switched to its secondary writer because the primary failed.
+#[derive(Event, Debug)]
+pub struct SinkDegraded<R> {
+    /// Why the primary was given up on.
+    pub error: IoSinkError,
+    _marker: PhantomData<fn() -> R>,
+}
+
+/// Emitted when the sink of `R` wrote to its primary writer again after being degraded.
+#[derive(Event, Debug)]
+pub struct SinkRecovered<R>(PhantomData<fn() -> R>);
+
+enum FallbackChange {
+    Degraded(IoSinkError),
+    Recovered,
+}
+
+/// Whether the [`FallbackSink`] of `R` currently writes to its secondary, shared with the
+/// sink task.
+#[derive(Resource)]
+pub struct FallbackStatus<R> {
+    degraded: Arc<AtomicBool>,
+    changes: Receiver<FallbackChange>,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R> Clone for FallbackStatus<R> {
+    fn clone(&self) -> Self {
+        Self {
+            degraded: self.degraded.clone(),
+            changes: self.changes.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R> FallbackStatus<R> {
+    pub fn is_degraded(&self) -> bool {
+        self.degraded.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Active {
+    Primary,
+    Secondary,
+}
+
+/// Writes to `primary`, and to `secondary` while the primary fails, e.g. to keep saving to
+/// a temp dir when the save dir turned read-only.
+///
+/// Once degraded, the primary is tried again every
+/// [`with_recheck_interval`](Self::with_recheck_interval), and takes over again as soon as
+/// a write to it succeeds. Add a [`FallbackPlugin`] with the sink's
+/// [`status`](Self::status) for [`SinkDegraded<R>`] and [`SinkRecovered<R>`] events.
+///
+/// ```ignore
+/// let sink = FallbackSink::new(
+///     FileSink::new(save_dir.join("world.json")),
+///     FileSink::new(std::env::temp_dir().join("world.json")),
+/// );
+/// app.add_plugins(FallbackPlugin::new(sink.status()))
+///     .add_plugins(IoSinkPlugin::<World, _>::new(sink));
+/// ```
+pub struct FallbackSink<R, P, S> {
+    primary: P,
+    secondary: S,
+    primary_ready: bool,
+    secondary_ready: bool,
+    active: Active,
+    /// When the primary last failed, while degraded.
+    degraded_at: Option<Instant>,
+    recheck_interval: Duration,
+    degraded: Arc<AtomicBool>,
+    changes: (Sender<FallbackChange>, Receiver<FallbackChange>),
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R, P, S> FallbackSink<R, P, S> {
+    pub fn new(primary: P, secondary: S) -> Self {
+        Self {
+            primary,
+            secondary,
+            primary_ready: false,
+            secondary_ready: false,
+            active: Active::Primary,
+            degraded_at: None,
+            recheck_interval: DEFAULT_RECHECK_INTERVAL,
+            degraded: Arc::new(AtomicBool::new(false)),
+            changes: unbounded(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// How long to keep writing to the secondary before trying the primary again, 30
+    /// seconds by default. Zero tries the primary first on every write.
+    pub fn with_recheck_interval(mut self, interval: Duration) -> Self {
+        self.recheck_interval = interval;
+        self
+    }
+
+    pub fn status(&self) -> FallbackStatus<R> {
+        FallbackStatus {
+            degraded: self.degraded.clone(),
+            changes: self.changes.1.clone(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn primary(&self) -> &P {
+        &self.primary
+    }
+
+    pub fn secondary(&self) -> &S {
+        &self.secondary
+    }
+
+    fn degrade(&mut self, error: &IoSinkError) {
+        self.degraded_at = Some(Instant::now());
+        if !self.degraded.swap(true, Ordering::Relaxed) {
+            warn!("primary writer failed, falling back to the secondary: {error}");
+            let error = IoSinkError::Other(error.to_string());
+            let _ = self.changes.0.try_send(FallbackChange::Degraded(error));
+        }
+    }
+
+    fn recover(&mut self) {
+        self.degraded_at = None;
+        if self.degraded.swap(false, Ordering::Relaxed) {
+            let _ = self.changes.0.try_send(FallbackChange::Recovered);
+        }
+    }
+
+    fn primary_due(&self) -> bool {
+        self.degraded_at
+            .is_none_or(|at| at.elapsed() >= self.recheck_interval)
+    }
+}
+
+fn targets(primary: IoSinkError, secondary: IoSinkError) -> IoSinkError {
+    IoSinkError::Targets(vec![
+        TargetError {
+            target: "primary".into(),
+            error: primary,
+        },
+        TargetError {
+            target: "secondary".into(),
+            error: secondary,
+        },
+    ])
+}
+
+impl<R, P, S> FallbackSink<R, P, S>
+where
+    R: Send + Sync + 'static,
+    P: IoWriter<R>,
+    S: IoWriter<R>,
+{
+    async fn write_primary(&mut self, data: R) -> SinkResult {
+        if !self.primary_ready {
+            self.primary.init().await?;
+            self.primary_ready = true;
+        }
+        self.primary.write(data).await
+    }
+
+    async fn write_secondary(&mut self, data: R) -> SinkResult {
+        if !self.secondary_ready {
+            self.secondary.init().await?;
+            self.secondary_ready = true;
+        }
+        self.secondary.write(data).await?;
+        self.active = Active::Secondary;
+        Ok(())
+    }
+}
+
+impl<R, P, S> IoWriter<R> for FallbackSink<R, P, S>
+where
+    R: Clone + Send + Sync + 'static,
+    P: IoWriter<R>,
+    S: IoWriter<R>,
+{
+    /// The secondary is only initialized once it's needed.
+    async fn init(&mut self) -> SinkResult {
+        match self.primary.init().await {
+            Ok(()) => self.primary_ready = true,
+            Err(e) => {
+                self.degrade(&e);
+                self.secondary.init().await.map_err(|s| targets(e, s))?;
+                self.secondary_ready = true;
+            }
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        if !self.primary_due() {
+            return self.write_secondary(data).await;
+        }
+        let error = match self.write_primary(data.clone()).await {
+            Ok(()) => {
+                self.active = Active::Primary;
+                self.recover();
+                return Ok(());
+            }
+            // The secondary couldn't take this message either.
+            Err(e) if !e.is_retryable() => return Err(e),
+            Err(e) => e,
+        };
+        self.degrade(&error);
+        self.write_secondary(data)
+            .await
+            .map_err(|e| targets(error, e))
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        match self.active {
+            Active::Primary => self.primary.flush().await,
+            Active::Secondary => self.secondary.flush().await,
+        }
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        let primary = if self.primary_ready {
+            self.primary.close().await
+        } else {
+            Ok(())
+        };
+        let secondary = if self.secondary_ready {
+            self.secondary.close().await
+        } else {
+            Ok(())
+        };
+        match (primary, secondary) {
+            (Err(p), Err(s)) => Err(targets(p, s)),
+            (Err(e), _) | (_, Err(e)) => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        match self.active {
+            Active::Primary => self.primary.last_write_len(),
+            Active::Secondary => self.secondary.last_write_len(),
+        }
+    }
+
+    fn last_write_changed(&self) -> Option<u64> {
+        match self.active {
+            Active::Primary => self.primary.last_write_changed(),
+            Active::Secondary => self.secondary.last_write_changed(),
+        }
+    }
+
+    /// Only what both support, either may be written to.
+    fn capabilities(&self) -> WriterCapabilities {
+        self.primary
+            .capabilities()
+            .intersection(self.secondary.capabilities())
+    }
+
+    /// Wipes both, a save may have landed on either.
+    async fn wipe(&mut self) -> SinkResult {
+        match (self.primary.wipe().await, self.secondary.wipe().await) {
+            (Err(p), Err(s)) => Err(targets(p, s)),
+            (Err(e), _) | (_, Err(e)) => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Turns the changes of a [`FallbackStatus<R>`] into [`SinkDegraded<R>`] and
+/// [`SinkRecovered<R>`] events, and inserts the status as a resource.
+pub struct FallbackPlugin<R> {
+    status: FallbackStatus<R>,
+}
+
+impl<R> FallbackPlugin<R> {
+    pub fn new(status: FallbackStatus<R>) -> Self {
+        Self { status }
+    }
+}
+
+impl<R: Send + Sync + 'static> Plugin for FallbackPlugin<R> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.status.clone())
+            .add_event::<SinkDegraded<R>>()
+            .add_event::<SinkRecovered<R>>()
+            .add_systems(PreUpdate, forward_fallback_changes::<R>);
+    }
+}
+
+fn forward_fallback_changes<R: Send + Sync + 'static>(
+    status: Res<FallbackStatus<R>>,
+    mut degraded: EventWriter<SinkDegraded<R>>,
+    mut recovered: EventWriter<SinkRecovered<R>>,
+) {
+    while let Ok(change) = status.changes.try_recv() {
+        match change {
+            FallbackChange::Degraded(error) => {
+                degraded.write(SinkDegraded {
+                    error,
+                    _marker: PhantomData,
+                });
+            }
+            FallbackChange::Recovered => {
+                recovered.write(SinkRecovered(PhantomData));
+            }
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{boxed::DynWriter, IoSinkError, IoWriter, SinkResult, TargetError, WriterCapabilities};
+use bevy::log::warn;
+
+struct Target<R> {
+    name: String,
+    writer: Box<dyn DynWriter<R>>,
+    /// Whether `init` succeeded since the sink started or was closed.
+    ready: bool,
+}
+
+impl<R> Target<R> {
+    /// Init the writer unless it already is, so a target that failed to start is retried.
+    async fn ready(&mut self) -> SinkResult {
+        if !self.ready {
+            self.writer.init().await?;
+            self.ready = true;
+        }
+        Ok(())
+    }
+}
+
+/// Delivers every message to several writers, so one [`IoSender<R>`](crate::IoSender) can
+/// feed e.g. a local save and a remote backup.
+///
+/// Targets are written one after the other, in the order they were added. A failing target
+/// doesn't keep the message from the others; the call then fails with
+/// [`IoSinkError::Targets`], naming every target that failed. Retries write the message to
+/// all targets again.
+///
+/// The sink starts as long as one target does, those that failed to are logged and started
+/// again before each write until they succeed, failing the write meanwhile.
+///
+/// ```ignore
+/// let sink = FanoutSink::new()
+///     .with_target("local", FileSink::new("saves/world.json"))
+///     .with_target("backup", HttpSink::new("https://example.com/saves/world"));
+/// app.add_plugins(IoSinkPlugin::<World, _>::new(sink));
+/// ```
+pub struct FanoutSink<R> {
+    targets: Vec<Target<R>>,
+}
+
+impl<R> Default for FanoutSink<R> {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+        }
+    }
+}
+
+impl<R: Send + Sync + 'static> FanoutSink<R> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a writer, `name` identifies it in errors.
+    pub fn with_target(mut self, name: impl Into<String>, writer: impl IoWriter<R>) -> Self {
+        self.targets.push(Target {
+            name: name.into(),
+            writer: Box::new(writer),
+            ready: false,
+        });
+        self
+    }
+
+    /// Names of the targets, in write order.
+    pub fn targets(&self) -> impl Iterator<Item = &str> {
+        self.targets.iter().map(|target| target.name.as_str())
+    }
+}
+
+/// Collect the error of every failed target.
+fn report(errors: Vec<TargetError>) -> SinkResult {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(IoSinkError::Targets(errors))
+    }
+}
+
+macro_rules! each_target {
+    ($self:ident, $target:ident => $call:expr) => {{
+        let mut errors = Vec::new();
+        for $target in &mut $self.targets {
+            if let Err(error) = $call.await {
+                errors.push(TargetError {
+                    target: $target.name.clone(),
+                    error,
+                });
+            }
+        }
+        report(errors)
+    }};
+}
+
+impl<R> IoWriter<R> for FanoutSink<R>
+where
+    R: Clone + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        for target in &mut self.targets {
+            target.ready = false;
+        }
+        match each_target!(self, target => target.ready()) {
+            Err(IoSinkError::Targets(errors)) if errors.len() < self.targets.len() => {
+                for TargetError { target, error } in errors {
+                    warn!("fanout target {target} failed to start, retrying on write: {error}");
+                }
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        each_target!(self, target => async {
+            target.ready().await?;
+            target.writer.write(data.clone()).await
+        })
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        each_target!(self, target => async {
+            if !target.ready {
+                return Ok(());
+            }
+            target.writer.flush().await
+        })
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        each_target!(self, target => async {
+            if !std::mem::take(&mut target.ready) {
+                return Ok(());
+            }
+            target.writer.close().await
+        })
+    }
+
+    /// Bytes written to all targets together.
+    fn last_write_len(&self) -> Option<u64> {
+        let lens = self
+            .targets
+            .iter()
+            .filter_map(|t| t.writer.last_write_len());
+        lens.reduce(|a, b| a + b)
+    }
+
+    fn last_write_changed(&self) -> Option<u64> {
+        let changed = self
+            .targets
+            .iter()
+            .filter_map(|t| t.writer.last_write_changed());
+        changed.reduce(|a, b| a + b)
+    }
+
+    /// Only what every target supports.
+    fn capabilities(&self) -> WriterCapabilities {
+        self.targets
+            .iter()
+            .map(|t| t.writer.capabilities())
+            .reduce(WriterCapabilities::intersection)
+            .unwrap_or_default()
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        each_target!(self, target => async {
+            target.ready().await?;
+            target.writer.wipe().await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_lite::future::block_on;
+    use std::sync::{Arc, Mutex};
+
+    /// Records what it is given, failing to start `init_failures` times.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        init_failures: Arc<Mutex<u32>>,
+        written: Arc<Mutex<Vec<u32>>>,
+    }
+
+    impl Recorder {
+        fn failing_init(times: u32) -> Self {
+            let recorder = Self::default();
+            *recorder.init_failures.lock().unwrap() = times;
+            recorder
+        }
+
+        fn written(&self) -> Vec<u32> {
+            self.written.lock().unwrap().clone()
+        }
+    }
+
+    impl IoWriter<u32> for Recorder {
+        async fn init(&mut self) -> SinkResult {
+            let mut failures = self.init_failures.lock().unwrap();
+            if *failures > 0 {
+                *failures -= 1;
+                return Err(IoSinkError::Other("unreachable".into()));
+            }
+            Ok(())
+        }
+
+        async fn write(&mut self, data: u32) -> SinkResult {
+            self.written.lock().unwrap().push(data);
+            Ok(())
+        }
+    }
+
+    fn failed_targets(result: SinkResult) -> Vec<String> {
+        match result {
+            Err(IoSinkError::Targets(errors)) => errors.into_iter().map(|e| e.target).collect(),
+            other => panic!("expected target errors, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn starts_with_the_healthy_targets() {
+        let (local, remote) = (Recorder::default(), Recorder::failing_init(1));
+        let mut sink = FanoutSink::new()
+            .with_target("local", local.clone())
+            .with_target("remote", remote.clone());
+
+        block_on(sink.init()).unwrap();
+        block_on(sink.write(1)).unwrap();
+        assert_eq!(local.written(), [1]);
+        assert_eq!(remote.written(), [1]);
+    }
+
+    #[test]
+    fn failing_starts_fail_writes_until_they_succeed() {
+        let (local, remote) = (Recorder::default(), Recorder::failing_init(2));
+        let mut sink = FanoutSink::new()
+            .with_target("local", local.clone())
+            .with_target("remote", remote.clone());
+
+        block_on(sink.init()).unwrap();
+        assert_eq!(failed_targets(block_on(sink.write(1))), ["remote"]);
+        block_on(sink.write(2)).unwrap();
+        assert_eq!(local.written(), [1, 2]);
+        assert_eq!(remote.written(), [2]);
+    }
+
+    #[test]
+    fn fails_to_start_when_every_target_does() {
+        let mut sink = FanoutSink::new()
+            .with_target("a", Recorder::failing_init(1))
+            .with_target("b", Recorder::failing_init(1));
+        assert_eq!(failed_targets(block_on(sink.init())), ["a", "b"]);
+    }
+}
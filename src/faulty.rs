@@ -0,0 +1,155 @@
+use crate::{rng::Rng, IoSinkError, IoWriter, SinkResult, WriterCapabilities};
+use async_std::io;
+
+/// When a [`FaultySink`] operation fails. Calls are counted from 1, failed calls included.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Fault {
+    #[default]
+    Never,
+    Always,
+    /// Only the `n`th call fails.
+    OnCall(u64),
+    /// The `n`th call and every later one fail, like storage that went away for good.
+    FromCall(u64),
+    /// Every call fails with this probability, reproducible with
+    /// [`FaultySink::with_seed`].
+    Probability(f64),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Init,
+    Write,
+    Flush,
+    Close,
+}
+
+/// Wraps a writer and fails its `init`, `write`, `flush` or `close` calls on purpose, to test
+/// how the app handles [`SinkFailed<R>`](crate::SinkFailed) events and whether its
+/// [`RetryPolicy`](crate::RetryPolicy) gets messages through. A failed call doesn't reach the
+/// inner writer.
+///
+/// ```ignore
+/// let written = MemorySinkHandle::<Settings>::default();
+/// let sink = FaultySink::new(MemorySink::new(written.clone()))
+///     .fail_write(Fault::OnCall(1))
+///     .fail_flush(Fault::Probability(0.2))
+///     .with_seed(7);
+/// app.add_plugins(IoSinkPlugin::<Settings, _>::new(sink).with_retry(RetryPolicy::new(1)));
+/// ```
+pub struct FaultySink<W> {
+    inner: W,
+    faults: [Fault; 4],
+    calls: [u64; 4],
+    error_kind: io::ErrorKind,
+    rng: Rng,
+}
+
+impl<W> FaultySink<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            faults: [Fault::Never; 4],
+            calls: [0; 4],
+            error_kind: io::ErrorKind::Other,
+            rng: Rng::from_time(),
+        }
+    }
+
+    pub fn fail_init(mut self, fault: Fault) -> Self {
+        self.faults[Op::Init as usize] = fault;
+        self
+    }
+
+    pub fn fail_write(mut self, fault: Fault) -> Self {
+        self.faults[Op::Write as usize] = fault;
+        self
+    }
+
+    pub fn fail_flush(mut self, fault: Fault) -> Self {
+        self.faults[Op::Flush as usize] = fault;
+        self
+    }
+
+    pub fn fail_close(mut self, fault: Fault) -> Self {
+        self.faults[Op::Close as usize] = fault;
+        self
+    }
+
+    /// Kind of the [`IoSinkError::Io`] injected failures report, [`io::ErrorKind::Other`]
+    /// by default.
+    pub fn with_error_kind(mut self, kind: io::ErrorKind) -> Self {
+        self.error_kind = kind;
+        self
+    }
+
+    /// Make [`Fault::Probability`] fail the same calls on every run.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    /// Count the call and fail it if its fault says so.
+    fn inject(&mut self, op: Op) -> SinkResult {
+        let call = &mut self.calls[op as usize];
+        *call += 1;
+        let fails = match self.faults[op as usize] {
+            Fault::Never => false,
+            Fault::Always => true,
+            Fault::OnCall(n) => *call == n,
+            Fault::FromCall(n) => *call >= n,
+            Fault::Probability(p) => self.rng.chance(p),
+        };
+        if fails {
+            let message = format!("injected {op:?} fault on call {call}").to_lowercase();
+            return Err(IoSinkError::Io(io::Error::new(self.error_kind, message)));
+        }
+        Ok(())
+    }
+}
+
+impl<R, W> IoWriter<R> for FaultySink<W>
+where
+    R: Send + Sync + 'static,
+    W: IoWriter<R>,
+{
+    async fn init(&mut self) -> SinkResult {
+        self.inject(Op::Init)?;
+        self.inner.init().await
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        self.inject(Op::Write)?;
+        self.inner.write(data).await
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        self.inject(Op::Flush)?;
+        self.inner.flush().await
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        self.inject(Op::Close)?;
+        self.inner.close().await
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.inner.last_write_len()
+    }
+
+    fn last_write_changed(&self) -> Option<u64> {
+        self.inner.last_write_changed()
+    }
+
+    fn capabilities(&self) -> WriterCapabilities {
+        self.inner.capabilities()
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        self.inner.wipe().await
+    }
+}
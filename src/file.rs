@@ -0,0 +1,728 @@
+#[cfg(all(target_arch = "wasm32", feature = "wasm", not(feature = "opfs")))]
+use crate::LocalStorageSink;
+#[cfg(all(target_arch = "wasm32", feature = "opfs"))]
+use crate::OpfsSink;
+use crate::{
+    envelope::{ClockMirror, EnvelopePlugin},
+    load::{self, FileLoader, LoadFileReceiver},
+    metadata::{MetadataPlugin, MetadataRecord, MetadataSink, SaveInfoMirror},
+    path::{is_template, resolve_at_startup, SharedPath},
+    ready::LoadTrackerPlugin,
+    requests,
+    vfs::{self, StorageFs, Vfs, VfsFile},
+    AutoSave, ChannelConfig, CircuitBreaker, Envelope, EnvelopeSink, IoSender, IoSinkError,
+    IoSinkPlugin, IoSinks, IoWriter, LoadCompleted, LoadFailed, LoadSet, LoadTracker, Migrations,
+    MissingSavePolicy, OverflowPolicy, PanicPolicy, RetryPolicy, SaveFormat, SinkResult, SinkTag,
+    UnreadableSavePolicy, WriterCapabilities,
+};
+#[cfg(feature = "debug")]
+use crate::{InspectSink, PayloadInspector};
+use async_channel::unbounded;
+use async_std::path::{Path, PathBuf};
+use bevy::{
+    ecs::schedule::{InternedScheduleLabel, ScheduleLabel},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+use std::{any::TypeId, io::ErrorKind, marker::PhantomData, sync::Arc, time::Duration};
+
+pub struct FileSink<R> {
+    path: SharedPath,
+    format: SaveFormat,
+    /// Previous sessions' saves to keep, see [`FileSink::with_backups`].
+    backups: usize,
+    max_message_size: Option<u64>,
+    vfs: Arc<dyn Vfs>,
+    writer: Option<Box<dyn VfsFile>>,
+    /// Last bytes written, used to skip writes that wouldn't change the file and to measure
+    /// how much of each write actually changed.
+    last_contents: Option<Vec<u8>>,
+    last_write_len: Option<u64>,
+    last_write_changed: Option<u64>,
+    /// The save was deleted by [`IoWriter::wipe`], the next write recreates it.
+    wiped: bool,
+    _marker: PhantomData<R>,
+}
+
+impl<R> FileSink<R> {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: SharedPath::new(path.into()),
+            format: SaveFormat::default(),
+            backups: 0,
+            max_message_size: None,
+            vfs: Arc::new(StorageFs),
+            writer: None,
+            last_contents: None,
+            last_write_len: None,
+            last_write_changed: None,
+            wiped: false,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Reject encoded messages larger than `bytes` with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+
+    /// Write where `path` points to when the sink starts, set by a templated
+    /// [`FileSinkPlugin`].
+    pub(crate) fn with_shared_path(mut self, path: SharedPath) -> Self {
+        self.path = path;
+        self
+    }
+
+    /// Read and write through `vfs` instead of the platform's storage, e.g. a
+    /// [`MemoryFs`](crate::MemoryFs) in tests.
+    pub fn with_vfs(mut self, vfs: impl Vfs) -> Self {
+        self.vfs = Arc::new(vfs);
+        self
+    }
+
+    pub(crate) fn with_shared_vfs(mut self, vfs: Arc<dyn Vfs>) -> Self {
+        self.vfs = vfs;
+        self
+    }
+
+    /// Before the first write of a session, keep a copy of the existing save as `<path>.1`,
+    /// shifting older copies up to `<path>.<count>`.
+    pub fn with_backups(mut self, count: usize) -> Self {
+        self.backups = count;
+        self
+    }
+}
+
+fn backup_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{index}"));
+    name.into()
+}
+
+async fn rotate_backups(vfs: &dyn Vfs, path: &Path, count: usize) -> SinkResult {
+    if count == 0 || !vfs.exists(path).await? {
+        return Ok(());
+    }
+    for index in (1..count).rev() {
+        match vfs
+            .rename(&backup_path(path, index), &backup_path(path, index + 1))
+            .await
+        {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+    vfs.copy(path, &backup_path(path, 1)).await?;
+    Ok(())
+}
+
+/// Bytes that differ between two versions of the file, a grown or shrunk tail included.
+fn changed_bytes(previous: Option<&[u8]>, current: &[u8]) -> u64 {
+    let Some(previous) = previous else {
+        return current.len() as u64;
+    };
+    let differing = previous
+        .iter()
+        .zip(current)
+        .filter(|(old, new)| old != new)
+        .count();
+    (differing + previous.len().abs_diff(current.len())) as u64
+}
+
+impl<R> IoWriter<R> for FileSink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        let path = self.path.open();
+        // Reopened on another path, the previous contents say nothing about this file.
+        self.last_contents = None;
+        rotate_backups(&*self.vfs, &path, self.backups).await?;
+        self.writer = Some(self.vfs.open(&path).await?);
+        self.wiped = false;
+        Ok(())
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let json = self.format.encode(&data)?;
+        IoSinkError::check_size(json.len(), self.max_message_size)?;
+        if self.wiped {
+            IoWriter::<R>::init(self).await?;
+        }
+
+        // Change detection fires on any `ResMut` deref, so identical payloads are common.
+        if self.last_contents.as_deref() == Some(json.as_slice()) {
+            self.last_write_len = Some(0);
+            self.last_write_changed = Some(0);
+            return Ok(());
+        }
+
+        let writer = self.writer.as_mut().ok_or(IoSinkError::NotInitialized)?;
+        writer.replace(&json).await?;
+
+        self.last_write_changed = Some(changed_bytes(self.last_contents.as_deref(), &json));
+        self.last_write_len = Some(json.len() as u64);
+        self.last_contents = Some(json);
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+
+    fn last_write_changed(&self) -> Option<u64> {
+        self.last_write_changed
+    }
+
+    /// Saves are rewritten in place, not staged and renamed.
+    fn capabilities(&self) -> WriterCapabilities {
+        WriterCapabilities {
+            seek: true,
+            ..default()
+        }
+    }
+
+    /// Deletes the save, its backups, the copy of an unreadable save and its thumbnail. The
+    /// file isn't recreated until the next write.
+    async fn wipe(&mut self) -> SinkResult {
+        self.writer = None;
+        self.last_contents = None;
+        self.wiped = true;
+        let path = self.path.get();
+        let mut unreadable = path.as_os_str().to_owned();
+        unreadable.push(".unreadable");
+        let backups = (1..=self.backups).map(|index| backup_path(&path, index));
+        vfs::remove_all(
+            &*self.vfs,
+            [path.clone(), unreadable.into()]
+                .into_iter()
+                .chain(backups)
+                .chain(sidecars(&path)),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "thumbnail")]
+fn sidecars(path: &Path) -> Option<PathBuf> {
+    Some(crate::thumbnail_path(path))
+}
+
+#[cfg(not(feature = "thumbnail"))]
+fn sidecars(_: &Path) -> Option<PathBuf> {
+    None
+}
+pub struct FileSinkPlugin<R> {
+    /// If true, the resource will be synced to disk on every change.
+    sync_res: bool,
+    /// Upper bound on change-driven writes per second, excess changes are coalesced.
+    max_writes_per_second: Option<f32>,
+    autosave: Option<AutoSave>,
+    /// Schedule the sync and autosave systems run in.
+    schedule: InternedScheduleLabel,
+    format: SaveFormat,
+    backups: usize,
+    max_message_size: Option<u64>,
+    /// Resources whose load must be inserted before this one's.
+    load_after: Vec<TypeId>,
+    missing: MissingSavePolicy,
+    unreadable: UnreadableSavePolicy,
+    tag: SinkTag,
+    channel: ChannelConfig,
+    panic_policy: PanicPolicy,
+    retry: Option<RetryPolicy>,
+    dead_letter_capacity: Option<usize>,
+    /// Wrap every record in an [`Envelope`].
+    envelope: bool,
+    /// Store a [`SaveMetadata`](crate::SaveMetadata) next to every record.
+    metadata: bool,
+    circuit_breaker: Option<CircuitBreaker>,
+    migrations: Migrations,
+    vfs: Arc<dyn Vfs>,
+    path: PathBuf,
+    _phantom: PhantomData<R>,
+}
+
+impl<R> FileSinkPlugin<R> {
+    /// `path` may contain `{name}` placeholders, resolved from [`PathVariables`] at
+    /// `PreStartup`.
+    ///
+    /// [`PathVariables`]: crate::PathVariables
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            _phantom: PhantomData,
+            sync_res: false,
+            max_writes_per_second: None,
+            autosave: None,
+            schedule: Update.intern(),
+            format: SaveFormat::default(),
+            backups: 0,
+            max_message_size: None,
+            load_after: Vec::new(),
+            missing: MissingSavePolicy::default(),
+            unreadable: UnreadableSavePolicy::default(),
+            tag: SinkTag::Save,
+            channel: ChannelConfig::default(),
+            panic_policy: PanicPolicy::default(),
+            retry: None,
+            dead_letter_capacity: None,
+            envelope: false,
+            metadata: false,
+            circuit_breaker: None,
+            migrations: Migrations::default(),
+            vfs: Arc::new(StorageFs),
+        }
+    }
+
+    pub fn with_tag(mut self, tag: SinkTag) -> Self {
+        self.tag = tag;
+        self
+    }
+
+    /// Bound the write queue, see [`OverflowPolicy`] for what happens when it fills up.
+    pub fn with_channel_capacity(mut self, capacity: usize, policy: OverflowPolicy) -> Self {
+        self.channel = ChannelConfig::bounded(capacity, policy);
+        self
+    }
+
+    pub fn with_panic_policy(mut self, panic_policy: PanicPolicy) -> Self {
+        self.panic_policy = panic_policy;
+        self
+    }
+
+    /// Retry failed writes, e.g. while a backup tool briefly holds a lock on the file.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Keep failed saves in a [`DeadLetters<R>`] resource so they can be re-sent.
+    pub fn with_dead_letters(mut self, capacity: usize) -> Self {
+        self.dead_letter_capacity = Some(capacity);
+        self
+    }
+
+    /// Store records wrapped in an [`Envelope`] with a timestamp, frame and sequence number.
+    pub fn with_envelope(mut self, enabled: bool) -> Self {
+        self.envelope = enabled;
+        self
+    }
+
+    /// Store a [`SaveMetadata`](crate::SaveMetadata) with every save for the load menu, see
+    /// [`read_save_metadata`](crate::read_save_metadata).
+    pub fn with_metadata(mut self, enabled: bool) -> Self {
+        self.metadata = enabled;
+        self
+    }
+
+    /// Upgrade older saves on load. The schema version lives in the [`Envelope`], so this
+    /// also enables envelopes.
+    pub fn with_migrations(mut self, migrations: Migrations) -> Self {
+        self.migrations = migrations;
+        self.envelope = true;
+        self
+    }
+
+    /// Stop attempting writes for a while after repeated failures (disk full, permission
+    /// denied).
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Sync the resource to disk whenever it changes.
+    pub fn with_sync_on_change(mut self, enabled: bool) -> Self {
+        self.sync_res = enabled;
+        self
+    }
+
+    /// Cap change-driven syncs to `max` writes per second. Changes that happen in
+    /// between collapse into a single pending write of the latest value.
+    pub fn with_max_writes_per_second(mut self, max: f32) -> Self {
+        self.max_writes_per_second = Some(max);
+        self
+    }
+
+    /// Also save periodically, independently of change detection.
+    pub fn with_autosave(mut self, autosave: AutoSave) -> Self {
+        self.autosave = Some(autosave);
+        self
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Keep the saves of the last `count` sessions, see [`FileSink::with_backups`].
+    pub fn with_backups(mut self, count: usize) -> Self {
+        self.backups = count;
+        self
+    }
+
+    /// Reject saves whose encoded size exceeds `bytes`. Oversized saves are not retried and
+    /// surface as a [`SinkFailed`](crate::SinkFailed) with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+
+    /// Insert the loaded `R` only once `D` has been loaded, e.g. settings before the world.
+    /// [`AllLoaded`](crate::AllLoaded) is emitted once the last stage has been inserted.
+    /// Panics when the plugin is added if `D` already waits on `R`, directly or not.
+    pub fn with_load_after<D: Resource>(mut self) -> Self {
+        self.load_after.push(TypeId::of::<D>());
+        self
+    }
+
+    /// What to do on a fresh install, when there is no save yet.
+    pub fn with_missing_save(mut self, policy: MissingSavePolicy) -> Self {
+        self.missing = policy;
+        self
+    }
+
+    /// What to do when the save exists but can't be read or decoded.
+    pub fn with_unreadable_save(mut self, policy: UnreadableSavePolicy) -> Self {
+        self.unreadable = policy;
+        self
+    }
+
+    /// Save and load through `vfs` instead of the platform's storage, e.g. a
+    /// [`MemoryFs`](crate::MemoryFs) to run the plugin in tests without touching the disk.
+    /// Browser builds save through their `localStorage` or OPFS sink regardless and only
+    /// load through `vfs`.
+    pub fn with_vfs(mut self, vfs: impl Vfs) -> Self {
+        self.vfs = Arc::new(vfs);
+        self
+    }
+
+    /// Run the sync and autosave systems in `schedule` instead of `Update`.
+    pub fn with_schedule(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.schedule = schedule.intern();
+        self
+    }
+
+    /// Only write when asked to: no syncing, no autosave and no default for a missing save.
+    pub(crate) fn read_only(mut self) -> Self {
+        self.sync_res = false;
+        self.autosave = None;
+        self.missing = MissingSavePolicy::Skip;
+        self
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub(crate) fn migrations(&self) -> &Migrations {
+        &self.migrations
+    }
+}
+
+/// Number of payloads kept per sink by the `debug` feature.
+#[cfg(feature = "debug")]
+const INSPECTED_PAYLOADS: usize = 8;
+
+impl<R> FileSinkPlugin<R>
+where
+    R: for<'de> Deserialize<'de> + Clone + Serialize + Resource + Default + Send + Sync + 'static,
+{
+    #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+    fn writer<T>(&self, path: &SharedPath) -> FileSink<T> {
+        let sink = FileSink::new(self.path.clone())
+            .with_shared_path(path.clone())
+            .with_shared_vfs(self.vfs.clone())
+            .with_format(self.format)
+            .with_backups(self.backups);
+        match self.max_message_size {
+            Some(max) => sink.with_max_message_size(max),
+            None => sink,
+        }
+    }
+
+    /// Browsers have no file system, the path becomes a `localStorage` key.
+    #[cfg(all(target_arch = "wasm32", feature = "wasm", not(feature = "opfs")))]
+    fn writer<T>(&self, path: &SharedPath) -> LocalStorageSink<T> {
+        let sink = LocalStorageSink::new(self.path.to_string_lossy())
+            .with_shared_path(path.clone())
+            .with_format(self.format);
+        match self.max_message_size {
+            Some(max) => sink.with_max_message_size(max),
+            None => sink,
+        }
+    }
+
+    /// The same path, in the origin private file system.
+    #[cfg(all(target_arch = "wasm32", feature = "opfs"))]
+    fn writer<T>(&self, path: &SharedPath) -> OpfsSink<T> {
+        let sink = OpfsSink::new(self.path.clone())
+            .with_shared_path(path.clone())
+            .with_format(self.format);
+        match self.max_message_size {
+            Some(max) => sink.with_max_message_size(max),
+            None => sink,
+        }
+    }
+
+    fn metadata_sink<W>(&self, app: &mut App, inner: W, path: &SharedPath) -> MetadataSink<W> {
+        if !app.is_plugin_added::<MetadataPlugin>() {
+            app.add_plugins(MetadataPlugin);
+        }
+        let mirror = app.world().resource::<SaveInfoMirror>();
+        MetadataSink::new(inner, path, mirror)
+    }
+
+    fn add_sink<W: IoWriter<R>>(&self, app: &mut App, writer: W) {
+        #[cfg(feature = "debug")]
+        let writer = {
+            let inspector = PayloadInspector::<R>::new(INSPECTED_PAYLOADS);
+            app.insert_resource(inspector.clone());
+            InspectSink::new(writer, inspector)
+        };
+        let mut sink_plugin = IoSinkPlugin::<R, _>::new(writer)
+            .with_tag(self.tag)
+            .with_channel(self.channel)
+            .with_panic_policy(self.panic_policy);
+        if let Some(retry) = self.retry {
+            sink_plugin = sink_plugin.with_retry(retry);
+        }
+        if let Some(capacity) = self.dead_letter_capacity {
+            sink_plugin = sink_plugin.with_dead_letters(capacity);
+        }
+        if let Some(circuit_breaker) = self.circuit_breaker {
+            sink_plugin = sink_plugin.with_circuit_breaker(circuit_breaker);
+        }
+        app.add_plugins(sink_plugin);
+    }
+}
+
+impl<R> Plugin for FileSinkPlugin<R>
+where
+    R: for<'de> Deserialize<'de> + Clone + Serialize + Resource + Default + Send + Sync + 'static,
+{
+    fn build(&self, app: &mut App) {
+        let (tx, rx) = unbounded();
+        let path = SharedPath::new(self.path.clone());
+
+        if self.envelope {
+            if !app.is_plugin_added::<EnvelopePlugin>() {
+                app.add_plugins(EnvelopePlugin);
+            }
+            let clock = app.world().resource::<ClockMirror>().clone();
+            let version = self.migrations.current_version();
+            if self.metadata {
+                let writer = self.writer::<MetadataRecord<Envelope<R>>>(&path);
+                let writer = self.metadata_sink(app, writer, &path);
+                self.add_sink(app, EnvelopeSink::new(writer, &clock).with_version(version));
+            } else {
+                let writer = self.writer::<Envelope<R>>(&path);
+                self.add_sink(app, EnvelopeSink::new(writer, &clock).with_version(version));
+            }
+        } else if self.metadata {
+            let writer = self.metadata_sink(app, self.writer::<MetadataRecord<R>>(&path), &path);
+            self.add_sink(app, writer);
+        } else {
+            self.add_sink(app, self.writer::<R>(&path));
+        }
+
+        let mut sinks = app.world_mut().resource_mut::<IoSinks>();
+        sinks.set_path::<R>(self.path.clone());
+        sinks.set_reset::<R>();
+        app.insert_resource(LoadFileReceiver::<R>(rx));
+        if is_template(&self.path) {
+            resolve_at_startup::<R>(app, self.path.clone(), path.clone());
+        }
+        app.insert_resource(FileLoader::<R> {
+            path,
+            migrations: self.migrations.clone(),
+            vfs: self.vfs.clone(),
+            missing: self.missing,
+            unreadable: self.unreadable,
+            sync_on_change: self.sync_res,
+            tx,
+        });
+        if !app.is_plugin_added::<LoadTrackerPlugin>() {
+            app.add_plugins(LoadTrackerPlugin);
+        }
+        let mut tracker = app.world_mut().resource_mut::<LoadTracker>();
+        tracker.register::<R>();
+        for &dependency in &self.load_after {
+            tracker.add_dependency::<R>(dependency);
+        }
+        app.add_event::<LoadCompleted<R>>();
+        app.add_event::<LoadFailed<R>>();
+        app.add_observer(requests::on_save_request::<R>);
+        app.add_observer(requests::on_load_request::<R>);
+
+        // Not in `FixedUpdate`, which doesn't run while virtual time is paused.
+        app.add_systems(PreUpdate, load::receive_loaded::<R>.in_set(LoadSet));
+        if self.sync_res {
+            match self.max_writes_per_second {
+                Some(max) if max > 0.0 => {
+                    app.insert_resource(SyncRateLimit::<R>::new(max));
+                    app.add_systems(
+                        self.schedule,
+                        sync_file_rate_limited::<R>.run_if(resource_exists::<R>),
+                    );
+                }
+                _ => {
+                    app.add_systems(
+                        self.schedule,
+                        sync_file::<R>.run_if(resource_exists_and_changed::<R>),
+                    );
+                }
+            }
+        }
+        if let Some(autosave) = &self.autosave {
+            app.insert_resource(AutoSaveTimer::<R> {
+                autosave: autosave.clone(),
+                changes: 0,
+                _marker: PhantomData,
+            });
+            app.add_systems(
+                self.schedule,
+                autosave_file::<R>.run_if(resource_exists::<R>),
+            );
+        }
+        app.add_systems(Startup, |loader: Res<FileLoader<R>>| loader.spawn());
+    }
+}
+
+fn sync_file<R>(sender: Res<IoSender<R>>, res: Res<R>)
+where
+    R: for<'de> Deserialize<'de> + Clone + Serialize + Resource + Default + Send + Sync + 'static,
+{
+    if let Err(err) = sender.enqueue(res.clone()) {
+        error!("{err}");
+    }
+}
+
+#[derive(Resource)]
+struct SyncRateLimit<R> {
+    min_interval: Duration,
+    last_sync: Option<Duration>,
+    pending: bool,
+    _marker: PhantomData<R>,
+}
+
+impl<R> SyncRateLimit<R> {
+    fn new(max_writes_per_second: f32) -> Self {
+        // A tiny rate, e.g. a subnormal, makes an interval too long for a `Duration`.
+        let min_interval =
+            Duration::try_from_secs_f32(1.0 / max_writes_per_second).unwrap_or(Duration::MAX);
+        Self {
+            min_interval,
+            last_sync: None,
+            pending: false,
+            _marker: PhantomData,
+        }
+    }
+}
+
+fn sync_file_rate_limited<R>(
+    sender: Res<IoSender<R>>,
+    res: Res<R>,
+    mut limit: ResMut<SyncRateLimit<R>>,
+    time: Res<Time<Real>>,
+) where
+    R: for<'de> Deserialize<'de> + Clone + Serialize + Resource + Default + Send + Sync + 'static,
+{
+    if res.is_changed() {
+        limit.pending = true;
+    }
+    if !limit.pending {
+        return;
+    }
+
+    let now = time.elapsed();
+    if let Some(last) = limit.last_sync {
+        if now.saturating_sub(last) < limit.min_interval {
+            return;
+        }
+    }
+
+    limit.pending = false;
+    limit.last_sync = Some(now);
+    if let Err(err) = sender.enqueue(res.clone()) {
+        error!("{err}");
+    }
+}
+
+#[derive(Resource)]
+struct AutoSaveTimer<R> {
+    autosave: AutoSave,
+    /// Frames on which `R` changed during the current interval.
+    changes: u32,
+    _marker: PhantomData<R>,
+}
+
+fn autosave_file<R>(
+    sender: Res<IoSender<R>>,
+    res: Res<R>,
+    mut timer: ResMut<AutoSaveTimer<R>>,
+    time: Res<Time>,
+) where
+    R: for<'de> Deserialize<'de> + Clone + Serialize + Resource + Default + Send + Sync + 'static,
+{
+    let AutoSaveTimer {
+        autosave, changes, ..
+    } = &mut *timer;
+    if !autosave.enabled {
+        return;
+    }
+    if res.is_changed() {
+        *changes = changes.saturating_add(1);
+    }
+    if !autosave.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    if let Some(bounds) = autosave.adaptive {
+        let current = autosave.timer.duration();
+        let next = match *changes {
+            0 => current * 2,
+            1 => current,
+            _ => current / 2,
+        };
+        autosave
+            .timer
+            .set_duration(next.clamp(bounds.min, bounds.max));
+        if std::mem::take(changes) == 0 {
+            return;
+        }
+    }
+    if let Err(err) = sender.enqueue(res.clone()) {
+        error!("{err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Resource)]
+    struct Settings;
+
+    #[test]
+    fn tiny_rates_limit_syncs_to_the_longest_interval() {
+        for max in [1e-39, f32::MIN_POSITIVE, 1e-30] {
+            let limit = SyncRateLimit::<Settings>::new(max);
+            assert!(
+                limit.min_interval >= Duration::from_secs(1_000_000),
+                "{max}"
+            );
+        }
+        assert_eq!(
+            SyncRateLimit::<Settings>::new(4.0).min_interval,
+            Duration::from_millis(250)
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{IoWriter, SinkResult, WriterCapabilities};
+
+/// Decides which messages a [`FilterSink`] passes on. Implemented for predicates
+/// `Fn(&R) -> bool`, and by [`Sample`](crate::Sample) and [`Dedup`](crate::Dedup).
+pub trait Filter<R>: Send + Sync + 'static {
+    /// Whether `data` should be written.
+    fn accept(&mut self, data: &R) -> bool;
+
+    /// The message accepted last was written, or failed to be.
+    fn written(&mut self, _result: &SinkResult) {}
+
+    /// The writer starts over, on `init` and `wipe`.
+    fn reset(&mut self) {}
+}
+
+impl<R, F> Filter<R> for F
+where
+    F: Fn(&R) -> bool + Send + Sync + 'static,
+{
+    fn accept(&mut self, data: &R) -> bool {
+        self(data)
+    }
+}
+
+/// Passes on only the messages `filter` accepts, e.g. to persist a state only while it's
+/// dirty. The check runs in the sink task, so game systems can send every change. Built
+/// with [`IoWriterExt::filter`](crate::IoWriterExt::filter), and the base of
+/// [`SampleSink`](crate::SampleSink) and [`DedupSink`](crate::DedupSink).
+///
+/// ```ignore
+/// let sink = FileSink::new("saves/world.json").filter(|world: &World| world.dirty);
+/// app.add_plugins(IoSinkPlugin::<World, _>::new(sink));
+/// ```
+pub struct FilterSink<W, F> {
+    inner: W,
+    filter: F,
+    /// Whether the most recent message was dropped.
+    skipped: bool,
+}
+
+impl<W, F> FilterSink<W, F> {
+    pub fn new(inner: W, filter: F) -> Self {
+        Self {
+            inner,
+            filter,
+            skipped: false,
+        }
+    }
+
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn filter(&self) -> &F {
+        &self.filter
+    }
+}
+
+impl<R, W, F> IoWriter<R> for FilterSink<W, F>
+where
+    R: Send + Sync + 'static,
+    W: IoWriter<R>,
+    F: Filter<R>,
+{
+    async fn init(&mut self) -> SinkResult {
+        self.filter.reset();
+        self.inner.init().await
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        self.skipped = !self.filter.accept(&data);
+        if self.skipped {
+            return Ok(());
+        }
+        let result = self.inner.write(data).await;
+        self.filter.written(&result);
+        result
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        self.inner.flush().await
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        self.inner.close().await
+    }
+
+    /// `0` when the most recent message was dropped.
+    fn last_write_len(&self) -> Option<u64> {
+        if self.skipped {
+            Some(0)
+        } else {
+            self.inner.last_write_len()
+        }
+    }
+
+    fn last_write_changed(&self) -> Option<u64> {
+        if self.skipped {
+            Some(0)
+        } else {
+            self.inner.last_write_changed()
+        }
+    }
+
+    fn capabilities(&self) -> WriterCapabilities {
+        self.inner.capabilities()
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        self.filter.reset();
+        self.inner.wipe().await
+    }
+}
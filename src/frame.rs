@@ -0,0 +1,15 @@
+//! Framing shared by the stream sinks, so one reader handles every transport.
+
+use crate::{IoSinkError, SinkResult};
+
+/// `payload` prefixed with its length as a big-endian `u32`.
+pub(crate) fn length_prefixed(payload: &[u8]) -> SinkResult<Vec<u8>> {
+    let len = u32::try_from(payload.len()).map_err(|_| IoSinkError::MessageTooLarge {
+        size: payload.len() as u64,
+        max: u32::MAX.into(),
+    })?;
+    let mut frame = Vec::with_capacity(4 + payload.len());
+    frame.extend_from_slice(&len.to_be_bytes());
+    frame.extend_from_slice(payload);
+    Ok(frame)
+}
@@ -0,0 +1,169 @@
+use crate::{SinkTag, WriterCapabilities};
+use async_channel::Sender;
+use async_std::path::{Path, PathBuf};
+use bevy::prelude::*;
+
+/// Command delivered to a running sink task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinkControl {
+    /// Call [`IoWriter::flush`](crate::IoWriter::flush) on the writer.
+    Flush,
+    /// Stop consuming messages, they stay queued until [`SinkControl::Resume`].
+    Pause,
+    Resume,
+    /// Drop every message currently queued for the sink.
+    Purge,
+    /// Write the queued messages, close the writer and `init` it again, e.g. to open
+    /// another file.
+    Reopen,
+    /// Drop the queued messages and delete everything the writer persisted.
+    Wipe,
+}
+
+struct SinkHandle {
+    name: &'static str,
+    tag: SinkTag,
+    control: Sender<SinkControl>,
+    /// File the sink writes to, for file-backed sinks.
+    path: Option<PathBuf>,
+    capabilities: WriterCapabilities,
+    /// Puts the persisted resource back to its default after a wipe, for resource sinks.
+    reset: Option<fn(&mut World)>,
+}
+
+/// Registry of every sink added to the app, used to address sinks by [`SinkTag`]
+/// instead of by resource type.
+#[derive(Resource, Default)]
+pub struct IoSinks {
+    sinks: Vec<SinkHandle>,
+}
+
+impl IoSinks {
+    pub(crate) fn register<R>(
+        &mut self,
+        tag: SinkTag,
+        control: Sender<SinkControl>,
+        capabilities: WriterCapabilities,
+    ) {
+        self.sinks.push(SinkHandle {
+            name: std::any::type_name::<R>(),
+            tag,
+            control,
+            path: None,
+            capabilities,
+            reset: None,
+        });
+    }
+
+    /// Reset `R` to its default when [`CommandsSaveExt::wipe_all_persisted`] wipes the most
+    /// recently registered sink of `R`.
+    ///
+    /// [`CommandsSaveExt::wipe_all_persisted`]: crate::CommandsSaveExt::wipe_all_persisted
+    pub(crate) fn set_reset<R: Resource + Default>(&mut self) {
+        let name = std::any::type_name::<R>();
+        if let Some(sink) = self.sinks.iter_mut().rev().find(|sink| sink.name == name) {
+            sink.reset = Some(reset_to_default::<R>);
+        }
+    }
+
+    pub(crate) fn resets(&self) -> impl Iterator<Item = fn(&mut World)> + '_ {
+        self.sinks.iter().filter_map(|sink| sink.reset)
+    }
+
+    /// Record the file written by the most recently registered sink of `R`.
+    pub(crate) fn set_path<R>(&mut self, path: PathBuf) {
+        let name = std::any::type_name::<R>();
+        if let Some(sink) = self.sinks.iter_mut().rev().find(|sink| sink.name == name) {
+            sink.path = Some(path);
+        }
+    }
+
+    /// Type name and file of every file-backed sink.
+    pub fn files(&self) -> impl Iterator<Item = (&'static str, &Path)> + '_ {
+        self.sinks
+            .iter()
+            .filter_map(|sink| Some((sink.name, sink.path.as_deref()?)))
+    }
+
+    /// File of the most recently registered sink of `R`, if it is file-backed.
+    pub fn path<R>(&self) -> Option<&Path> {
+        let name = std::any::type_name::<R>();
+        self.sinks
+            .iter()
+            .rev()
+            .find(|sink| sink.name == name)
+            .and_then(|sink| sink.path.as_deref())
+    }
+
+    /// Capabilities of the writer of the most recently registered sink of `R`.
+    pub fn capabilities<R>(&self) -> Option<WriterCapabilities> {
+        let name = std::any::type_name::<R>();
+        self.sinks
+            .iter()
+            .rev()
+            .find(|sink| sink.name == name)
+            .map(|sink| sink.capabilities)
+    }
+
+    /// Type names of the sinks registered under `tag`.
+    pub fn names(&self, tag: SinkTag) -> impl Iterator<Item = &'static str> + '_ {
+        self.sinks
+            .iter()
+            .filter(move |sink| sink.tag == tag)
+            .map(|sink| sink.name)
+    }
+
+    /// Send `control` to every sink registered under `tag`.
+    pub fn send(&self, tag: SinkTag, control: SinkControl) {
+        for sink in self.sinks.iter().filter(|sink| sink.tag == tag) {
+            if let Err(err) = sink.control.try_send(control) {
+                error!("{}: {err}", sink.name);
+            }
+        }
+    }
+
+    /// Send `control` to the most recently registered sink of `R`.
+    pub fn send_to<R>(&self, control: SinkControl) {
+        let name = std::any::type_name::<R>();
+        let Some(sink) = self.sinks.iter().rev().find(|sink| sink.name == name) else {
+            error!("no sink is registered for {name}");
+            return;
+        };
+        if let Err(err) = sink.control.try_send(control) {
+            error!("{name}: {err}");
+        }
+    }
+
+    pub fn flush(&self, tag: SinkTag) {
+        self.send(tag, SinkControl::Flush);
+    }
+
+    pub fn pause(&self, tag: SinkTag) {
+        self.send(tag, SinkControl::Pause);
+    }
+
+    pub fn resume(&self, tag: SinkTag) {
+        self.send(tag, SinkControl::Resume);
+    }
+
+    pub fn purge(&self, tag: SinkTag) {
+        self.send(tag, SinkControl::Purge);
+    }
+
+    /// Send [`SinkControl::Wipe`] to every sink, whatever its tag.
+    pub(crate) fn wipe_all(&self) {
+        for sink in &self.sinks {
+            if let Err(err) = sink.control.try_send(SinkControl::Wipe) {
+                error!("{}: {err}", sink.name);
+            }
+        }
+    }
+}
+
+/// Replace `R` with its default without marking it changed, so the default isn't saved
+/// right back. Missing resources are left alone.
+pub(crate) fn reset_to_default<R: Resource + Default>(world: &mut World) {
+    if let Some(mut res) = world.get_resource_mut::<R>() {
+        *res.bypass_change_detection() = R::default();
+    }
+}
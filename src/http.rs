@@ -0,0 +1,120 @@
+use crate::{IoSinkError, IoWriter, SaveFormat, SinkResult};
+use async_std::task::spawn_blocking;
+use serde::Serialize;
+use std::{marker::PhantomData, time::Duration};
+use ureq::Agent;
+
+/// POSTs every message to a URL, e.g. for telemetry or remote backups of saves. Failed
+/// requests and non-2xx responses are errors, so they are retried according to the sink's
+/// [`RetryPolicy`](crate::RetryPolicy) and then reported like any other write failure.
+///
+/// ```ignore
+/// let sink = HttpSink::new("https://telemetry.example.com/events")
+///     .with_bearer_auth(env!("TELEMETRY_TOKEN"));
+/// app.add_plugins(IoSinkPlugin::<Event, _>::new(sink).with_retry(RetryPolicy::new(5)));
+/// ```
+pub struct HttpSink<R> {
+    url: String,
+    headers: Vec<(String, String)>,
+    format: SaveFormat,
+    timeout: Duration,
+    max_message_size: Option<u64>,
+    agent: Option<Agent>,
+    last_write_len: Option<u64>,
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R> HttpSink<R> {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            headers: Vec::new(),
+            format: SaveFormat::default(),
+            timeout: Duration::from_secs(10),
+            max_message_size: None,
+            agent: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Send `name: value` with every request.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Send `Authorization: Bearer <token>` with every request.
+    pub fn with_bearer_auth(self, token: impl AsRef<str>) -> Self {
+        let value = format!("Bearer {}", token.as_ref());
+        self.with_header("Authorization", value)
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Give up on a request after `timeout`, 10 seconds by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Reject encoded messages larger than `bytes` with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+}
+
+fn http_error(url: &str, error: ureq::Error) -> IoSinkError {
+    match error {
+        ureq::Error::Io(e) => e.into(),
+        ureq::Error::StatusCode(status) => {
+            IoSinkError::Other(format!("POST {url} responded with status {status}"))
+        }
+        e => IoSinkError::Other(format!("POST {url} failed: {e}")),
+    }
+}
+
+impl<R> IoWriter<R> for HttpSink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        let agent = Agent::config_builder()
+            .timeout_global(Some(self.timeout))
+            .build()
+            .into();
+        self.agent = Some(agent);
+        Ok(())
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let body = self.format.encode(&data)?;
+        IoSinkError::check_size(body.len(), self.max_message_size)?;
+        let agent = self.agent.clone().ok_or(IoSinkError::NotInitialized)?;
+        let (url, headers) = (self.url.clone(), self.headers.clone());
+        let len = body.len() as u64;
+
+        // ureq blocks, keep it off the sink task's executor thread.
+        spawn_blocking(move || {
+            let mut request = agent.post(&url).header("Content-Type", "application/json");
+            for (name, value) in &headers {
+                request = request.header(name, value);
+            }
+            request
+                .send(&body[..])
+                .map(|_| ())
+                .map_err(|e| http_error(&url, e))
+        })
+        .await?;
+        self.last_write_len = Some(len);
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+}
@@ -0,0 +1,172 @@
+use crate::{
+    codec,
+    web::{js_error, run_local},
+    IoSinkError, IoWriter, Migrations, SaveFormat, SinkResult,
+};
+use js_sys::Promise;
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbRequest, IdbTransactionMode};
+
+const OBJECT_STORE: &str = "saves";
+
+/// Wait for `request` to succeed or fail.
+async fn complete(request: &IdbRequest) -> Result<JsValue, IoSinkError> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    JsFuture::from(promise).await.map_err(js_error)?;
+    request.result().map_err(js_error)
+}
+
+async fn open(database: &str) -> Result<IdbDatabase, IoSinkError> {
+    let factory = web_sys::window()
+        .ok_or_else(|| IoSinkError::Other("no window".into()))?
+        .indexed_db()
+        .map_err(js_error)?
+        .ok_or_else(|| IoSinkError::Other("IndexedDB is unavailable".into()))?;
+    let request = factory.open_with_u32(database, 1).map_err(js_error)?;
+    let upgrade = Closure::once(|event: web_sys::Event| {
+        let Some(db) = event
+            .target()
+            .and_then(|target| target.dyn_into::<IdbRequest>().ok())
+            .and_then(|request| request.result().ok())
+        else {
+            return;
+        };
+        let _ = db
+            .unchecked_into::<IdbDatabase>()
+            .create_object_store(OBJECT_STORE);
+    });
+    request.set_onupgradeneeded(Some(upgrade.as_ref().unchecked_ref()));
+    let db = complete(&request).await?;
+    request.set_onupgradeneeded(None);
+    Ok(db.unchecked_into())
+}
+
+async fn put(database: &str, key: &str, value: &str) -> SinkResult {
+    let db = open(database).await?;
+    let store = db
+        .transaction_with_str_and_mode(OBJECT_STORE, IdbTransactionMode::Readwrite)
+        .and_then(|transaction| transaction.object_store(OBJECT_STORE))
+        .map_err(js_error)?;
+    let request = store
+        .put_with_key(&JsValue::from_str(value), &JsValue::from_str(key))
+        .map_err(js_error)?;
+    complete(&request).await?;
+    db.close();
+    Ok(())
+}
+
+async fn delete(database: &str, key: &str) -> SinkResult {
+    let db = open(database).await?;
+    let store = db
+        .transaction_with_str_and_mode(OBJECT_STORE, IdbTransactionMode::Readwrite)
+        .and_then(|transaction| transaction.object_store(OBJECT_STORE))
+        .map_err(js_error)?;
+    let request = store.delete(&JsValue::from_str(key)).map_err(js_error)?;
+    complete(&request).await?;
+    db.close();
+    Ok(())
+}
+
+async fn get(database: &str, key: &str) -> Result<Option<String>, IoSinkError> {
+    let db = open(database).await?;
+    let store = db
+        .transaction_with_str(OBJECT_STORE)
+        .and_then(|transaction| transaction.object_store(OBJECT_STORE))
+        .map_err(js_error)?;
+    let request = store.get(&JsValue::from_str(key)).map_err(js_error)?;
+    let value = complete(&request).await?;
+    db.close();
+    Ok(value.as_string())
+}
+
+/// Read back what an [`IndexedDbSink`] stored under `key`, `None` if nothing was.
+pub async fn load_indexed_db<R>(
+    database: impl Into<String>,
+    key: impl Into<String>,
+    migrations: &Migrations,
+) -> Result<Option<R>, IoSinkError>
+where
+    R: DeserializeOwned,
+{
+    let (database, key) = (database.into(), key.into());
+    let Some(json) = run_local(move || async move { get(&database, &key).await }).await? else {
+        return Ok(None);
+    };
+    codec::decode(json.as_bytes(), migrations).map(Some)
+}
+
+/// Stores each `R` under `key` in an IndexedDB database, for browser saves too large for
+/// `localStorage`.
+///
+/// ```ignore
+/// app.add_plugins(IoSinkPlugin::<World, _>::new(IndexedDbSink::new("my_game", "world")));
+/// ```
+pub struct IndexedDbSink<R> {
+    database: String,
+    key: String,
+    format: SaveFormat,
+    max_message_size: Option<u64>,
+    last_write_len: Option<u64>,
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R> IndexedDbSink<R> {
+    pub fn new(database: impl Into<String>, key: impl Into<String>) -> Self {
+        Self {
+            database: database.into(),
+            key: key.into(),
+            format: SaveFormat::default(),
+            max_message_size: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Reject encoded messages larger than `bytes` with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+}
+
+impl<R> IoWriter<R> for IndexedDbSink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        let database = self.database.clone();
+        run_local(move || async move { open(&database).await.map(|db| db.close()) }).await
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let json = self.format.encode(&data)?;
+        IoSinkError::check_size(json.len(), self.max_message_size)?;
+        let json = String::from_utf8(json).map_err(IoSinkError::serialization)?;
+        let len = json.len() as u64;
+
+        let (database, key) = (self.database.clone(), self.key.clone());
+        run_local(move || async move { put(&database, &key, &json).await }).await?;
+        self.last_write_len = Some(len);
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        let (database, key) = (self.database.clone(), self.key.clone());
+        run_local(move || async move { delete(&database, &key).await }).await
+    }
+}
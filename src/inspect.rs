@@ -0,0 +1,148 @@
+use crate::{IoWriter, SinkResult, WriterCapabilities};
+use bevy::prelude::*;
+use serde::Serialize;
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadState {
+    /// Handed to the writer, write not finished yet.
+    Pending,
+    Written,
+    Failed,
+}
+
+#[derive(Debug, Clone)]
+pub struct InspectedPayload {
+    pub json: String,
+    pub state: PayloadState,
+}
+
+/// The most recent writes of the sink for `R`, as JSON strings. Only messages the sink task
+/// took off the channel show up: messages still queued, or dropped by the
+/// [`OverflowPolicy`](crate::OverflowPolicy), don't. See [`IoSinkStats`](crate::IoSinkStats) for
+/// the queue itself.
+///
+/// [`FileSinkPlugin`](crate::FileSinkPlugin) inserts one with the `debug` feature, other
+/// sinks wrap their writer in an [`InspectSink`]:
+///
+/// ```ignore
+/// let inspector = PayloadInspector::<World>::new(8);
+/// app.insert_resource(inspector.clone()).add_plugins(IoSinkPlugin::<World, _>::new(
+///     HttpSink::new("https://example.com/saves/world").inspect(&inspector),
+/// ));
+/// ```
+#[derive(Resource)]
+pub struct PayloadInspector<R> {
+    payloads: Arc<Mutex<VecDeque<InspectedPayload>>>,
+    capacity: usize,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R> Clone for PayloadInspector<R> {
+    fn clone(&self) -> Self {
+        Self {
+            payloads: self.payloads.clone(),
+            capacity: self.capacity,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R> PayloadInspector<R> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            payloads: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity: capacity.max(1),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Oldest first.
+    pub fn snapshot(&self) -> Vec<InspectedPayload> {
+        self.payloads.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn latest(&self) -> Option<InspectedPayload> {
+        self.payloads.lock().unwrap().back().cloned()
+    }
+
+    fn push(&self, json: String) {
+        let mut payloads = self.payloads.lock().unwrap();
+        if payloads.len() == self.capacity {
+            payloads.pop_front();
+        }
+        payloads.push_back(InspectedPayload {
+            json,
+            state: PayloadState::Pending,
+        });
+    }
+
+    fn finish(&self, state: PayloadState) {
+        if let Some(last) = self.payloads.lock().unwrap().back_mut() {
+            last.state = state;
+        }
+    }
+}
+
+/// Wraps a writer and records every write, and whether it succeeded, into a
+/// [`PayloadInspector`].
+pub struct InspectSink<R, W> {
+    inner: W,
+    inspector: PayloadInspector<R>,
+}
+
+impl<R, W> InspectSink<R, W> {
+    pub fn new(inner: W, inspector: PayloadInspector<R>) -> Self {
+        Self { inner, inspector }
+    }
+}
+
+impl<R, W> IoWriter<R> for InspectSink<R, W>
+where
+    R: Serialize + Send + Sync + 'static,
+    W: IoWriter<R>,
+{
+    async fn init(&mut self) -> SinkResult {
+        self.inner.init().await
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let json = serde_json::to_string(&data).unwrap_or_else(|e| format!("<{e}>"));
+        self.inspector.push(json);
+
+        let result = self.inner.write(data).await;
+        self.inspector.finish(match result {
+            Ok(()) => PayloadState::Written,
+            Err(_) => PayloadState::Failed,
+        });
+        result
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        self.inner.flush().await
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        self.inner.close().await
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.inner.last_write_len()
+    }
+
+    fn last_write_changed(&self) -> Option<u64> {
+        self.inner.last_write_changed()
+    }
+
+    fn capabilities(&self) -> WriterCapabilities {
+        self.inner.capabilities()
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        self.inner.wipe().await
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{frame::length_prefixed, IoSinkError, IoWriter, SaveFormat, SinkResult};
+use async_std::{
+    io::{self, WriteExt},
+    path::{Path, PathBuf},
+};
+use bevy::prelude::*;
+use serde::Serialize;
+use std::marker::PhantomData;
+
+#[cfg(unix)]
+type Stream = async_std::os::unix::net::UnixStream;
+#[cfg(windows)]
+type Stream = async_fs::File;
+
+#[cfg(unix)]
+async fn connect(path: &Path) -> io::Result<Stream> {
+    Stream::connect(path).await
+}
+
+/// A named pipe client is opened like a file.
+#[cfg(windows)]
+async fn connect(path: &Path) -> io::Result<Stream> {
+    async_fs::OpenOptions::new().write(true).open(path).await
+}
+
+/// Sends every message to a process on the same machine, framed like `TcpSink` messages,
+/// without going through the network stack. Connects to a Unix domain socket on Linux and
+/// macOS, see `UnixSocketSink`, and to a named pipe on Windows, see `NamedPipeSink`. The
+/// connection is opened by `init` and again on the
+/// write after a failure, which the sink's [`RetryPolicy`](crate::RetryPolicy) retries.
+pub struct LocalSocketSink<R> {
+    path: PathBuf,
+    format: SaveFormat,
+    max_message_size: Option<u64>,
+    stream: Option<Stream>,
+    last_write_len: Option<u64>,
+    _phantom: PhantomData<fn(R)>,
+}
+
+/// [`LocalSocketSink`] connecting to a Unix domain socket.
+///
+/// ```ignore
+/// app.add_plugins(IoSinkPlugin::<Overlay, _>::new(UnixSocketSink::new("/tmp/overlay.sock")));
+/// ```
+#[cfg(unix)]
+pub type UnixSocketSink<R> = LocalSocketSink<R>;
+
+/// [`LocalSocketSink`] connecting to a named pipe, which the reading process creates.
+///
+/// ```ignore
+/// app.add_plugins(IoSinkPlugin::<Overlay, _>::new(NamedPipeSink::new(r"\\.\pipe\overlay")));
+/// ```
+#[cfg(windows)]
+pub type NamedPipeSink<R> = LocalSocketSink<R>;
+
+impl<R> LocalSocketSink<R> {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            format: SaveFormat::default(),
+            max_message_size: None,
+            stream: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Reject encoded messages larger than `bytes` with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+
+    async fn connect(&mut self) -> SinkResult<&mut Stream> {
+        let stream = match self.stream.take() {
+            Some(stream) => stream,
+            None => connect(&self.path).await?,
+        };
+        Ok(self.stream.insert(stream))
+    }
+}
+
+impl<R> IoWriter<R> for LocalSocketSink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        self.connect().await?;
+        Ok(())
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let payload = self.format.encode(&data)?;
+        IoSinkError::check_size(payload.len(), self.max_message_size)?;
+        let frame = length_prefixed(&payload)?;
+
+        let stream = self.connect().await?;
+        if let Err(e) = stream.write_all(&frame).await {
+            // Half a frame may have gone out, only a new connection is in a known state.
+            warn!("lost the connection to {}: {e}", self.path.display());
+            self.stream = None;
+            return Err(e.into());
+        }
+        self.last_write_len = Some(frame.len() as u64);
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        if let Some(stream) = self.stream.as_mut() {
+            stream.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        self.stream = None;
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+}
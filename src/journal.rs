@@ -0,0 +1,315 @@
+use crate::{
+    envelope::{ClockMirror, EnvelopePlugin},
+    ready::LoadTrackerPlugin,
+    Envelope, EnvelopeSink, IoSinkError, IoSinkPlugin, IoSinks, IoWriter, LoadSet, LoadTracker,
+    SinkResult, WriterCapabilities,
+};
+use async_channel::{unbounded, Receiver};
+use async_fs::{File, OpenOptions};
+use async_std::{
+    io::{self, BufWriter, WriteExt},
+    path::PathBuf,
+};
+use bevy::{prelude::*, tasks::IoTaskPool};
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+
+/// Appends one JSON record per line instead of overwriting the file.
+pub struct JournalSink {
+    path: PathBuf,
+    writer: Option<BufWriter<File>>,
+    max_message_size: Option<u64>,
+    last_write_len: Option<u64>,
+    /// The journal was deleted by [`IoWriter::wipe`], the next write recreates it.
+    wiped: bool,
+}
+
+impl JournalSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            writer: None,
+            max_message_size: None,
+            last_write_len: None,
+            wiped: false,
+        }
+    }
+
+    /// Reject records larger than `bytes` with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+}
+
+impl<T> IoWriter<T> for JournalSink
+where
+    T: Serialize + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        if let Some(parent) = self.path.parent() {
+            async_fs::create_dir_all(parent).await?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        self.writer = Some(BufWriter::new(file));
+        self.wiped = false;
+        Ok(())
+    }
+
+    async fn write(&mut self, data: T) -> SinkResult {
+        let mut line = serde_json::to_vec(&data).map_err(IoSinkError::serialization)?;
+        line.push(b'\n');
+        IoSinkError::check_size(line.len(), self.max_message_size)?;
+        if self.wiped {
+            IoWriter::<T>::init(self).await?;
+        }
+
+        let writer = self.writer.as_mut().ok_or(IoSinkError::NotInitialized)?;
+        writer.write_all(&line).await?;
+        writer.flush().await?;
+        self.last_write_len = Some(line.len() as u64);
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        if let Some(writer) = self.writer.as_mut() {
+            writer.flush().await?;
+        }
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+
+    /// Appends never rewrite earlier bytes.
+    fn last_write_changed(&self) -> Option<u64> {
+        self.last_write_len
+    }
+
+    fn capabilities(&self) -> WriterCapabilities {
+        WriterCapabilities {
+            append: true,
+            ..default()
+        }
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        // Drop the handle first, appends would otherwise go to the deleted file.
+        self.writer = None;
+        self.wiped = true;
+        match async_fs::remove_file(&self.path).await {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Sequence numbers missing from a journal, `first..=last`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequenceGap {
+    pub first: u64,
+    pub last: u64,
+}
+
+/// Find lost records in a journal. A sequence number dropping back down marks a new session
+/// rather than a gap.
+pub fn detect_gaps<T>(records: &[Envelope<T>]) -> Vec<SequenceGap> {
+    records
+        .windows(2)
+        .filter_map(|pair| {
+            let (prev, next) = (pair[0].seq, pair[1].seq);
+            (next > prev.saturating_add(1)).then(|| SequenceGap {
+                first: prev + 1,
+                last: next - 1,
+            })
+        })
+        .collect()
+}
+
+/// Every readable record of a journal, and what is known to be missing from it.
+#[derive(Debug, Clone)]
+pub struct JournalReport<T> {
+    pub records: Vec<Envelope<T>>,
+    pub gaps: Vec<SequenceGap>,
+    /// Lines that could not be decoded, e.g. a record torn by a crash mid-write.
+    pub corrupt_lines: usize,
+}
+
+/// Read an enveloped journal written by [`JournalSink`]. A missing file is an empty journal.
+pub async fn load_journal<T>(path: impl Into<PathBuf>) -> io::Result<JournalReport<T>>
+where
+    T: DeserializeOwned,
+{
+    let path = path.into();
+    let contents = match async_fs::read_to_string(&path).await {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+
+    let mut records = Vec::new();
+    let mut corrupt_lines = 0;
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str::<Envelope<T>>(line) {
+            Ok(record) => records.push(record),
+            Err(_) => corrupt_lines += 1,
+        }
+    }
+
+    Ok(JournalReport {
+        gaps: detect_gaps(&records),
+        records,
+        corrupt_lines,
+    })
+}
+
+/// Emitted once the journal of `R` has been read at startup.
+#[derive(Event, Debug)]
+pub struct JournalLoaded<R> {
+    pub report: JournalReport<R>,
+}
+
+#[derive(Resource)]
+struct JournalReportReceiver<R>(Receiver<Option<JournalReport<R>>>);
+
+/// Appends every `R` sent through [`IoSender<R>`](crate::IoSender) to an enveloped journal,
+/// and reads the existing journal back at startup as a [`JournalLoaded<R>`] event.
+pub struct JournalSinkPlugin<R> {
+    path: PathBuf,
+    max_message_size: Option<u64>,
+    _phantom: PhantomData<R>,
+}
+
+impl<R> JournalSinkPlugin<R> {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_message_size: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Reject records whose encoded line exceeds `bytes`, reported as a
+    /// [`SinkFailed`](crate::SinkFailed) with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+}
+
+impl<R> Plugin for JournalSinkPlugin<R>
+where
+    R: DeserializeOwned + Serialize + Resource,
+{
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EnvelopePlugin>() {
+            app.add_plugins(EnvelopePlugin);
+        }
+        let clock = app.world().resource::<ClockMirror>().clone();
+        let mut journal = JournalSink::new(self.path.clone());
+        if let Some(max) = self.max_message_size {
+            journal = journal.with_max_message_size(max);
+        }
+        let sink = EnvelopeSink::new(journal, &clock);
+        app.add_plugins(IoSinkPlugin::<R, _>::new(sink));
+        app.world_mut()
+            .resource_mut::<IoSinks>()
+            .set_path::<R>(self.path.clone());
+
+        if !app.is_plugin_added::<LoadTrackerPlugin>() {
+            app.add_plugins(LoadTrackerPlugin);
+        }
+        app.world_mut()
+            .resource_mut::<LoadTracker>()
+            .register::<R>();
+
+        let (tx, rx) = unbounded();
+        app.insert_resource(JournalReportReceiver::<R>(rx));
+        app.add_event::<JournalLoaded<R>>();
+
+        let path = self.path.clone();
+        app.add_systems(Startup, move || {
+            let path = path.clone();
+            let tx = tx.clone();
+            IoTaskPool::get()
+                .spawn(async move {
+                    match load_journal::<R>(path).await {
+                        Ok(report) => {
+                            if !report.gaps.is_empty() {
+                                warn!("journal is missing records: {:?}", report.gaps);
+                            }
+                            let _ = tx.send(Some(report)).await;
+                        }
+                        Err(e) => {
+                            error!("{e}");
+                            // Still counts as loaded, there is just nothing to replay.
+                            let _ = tx.send(None).await;
+                        }
+                    }
+                })
+                .detach();
+        });
+        app.add_systems(PreUpdate, forward_journal_report::<R>.in_set(LoadSet));
+    }
+}
+
+fn forward_journal_report<R>(
+    receiver: Res<JournalReportReceiver<R>>,
+    mut loaded: EventWriter<JournalLoaded<R>>,
+    mut tracker: ResMut<LoadTracker>,
+) where
+    R: Send + Sync + 'static,
+{
+    while let Ok(report) = receiver.0.try_recv() {
+        tracker.mark_loaded::<R>();
+        if let Some(report) = report {
+            loaded.write(JournalLoaded { report });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(seqs: &[u64]) -> Vec<Envelope<()>> {
+        seqs.iter()
+            .map(|&seq| Envelope {
+                seq,
+                timestamp_ms: 0,
+                frame: 0,
+                version: 0,
+                payload: (),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn consecutive_records_have_no_gaps() {
+        assert!(detect_gaps(&records(&[])).is_empty());
+        assert!(detect_gaps(&records(&[7])).is_empty());
+        assert!(detect_gaps(&records(&[0, 1, 2, 3])).is_empty());
+    }
+
+    #[test]
+    fn missing_records_are_reported_as_ranges() {
+        assert_eq!(
+            detect_gaps(&records(&[0, 2, 3, 7])),
+            [
+                SequenceGap { first: 1, last: 1 },
+                SequenceGap { first: 4, last: 6 },
+            ]
+        );
+    }
+
+    #[test]
+    fn a_new_session_is_not_a_gap() {
+        assert!(detect_gaps(&records(&[4, 5, 0, 1])).is_empty());
+        assert!(detect_gaps(&records(&[u64::MAX, 0])).is_empty());
+    }
+}
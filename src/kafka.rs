@@ -0,0 +1,150 @@
+use crate::{IoSinkError, IoWriter, SaveFormat, SinkResult};
+use rdkafka::{
+    client::DefaultClientContext,
+    config::ClientConfig,
+    producer::{FutureProducer, FutureRecord, Producer},
+    util::{AsyncRuntime, Timeout},
+};
+use serde::Serialize;
+use std::{future::Future, marker::PhantomData, pin::Pin, time::Duration};
+
+/// Lets rdkafka time out deliveries without a tokio runtime.
+struct AsyncStdRuntime;
+
+impl AsyncRuntime for AsyncStdRuntime {
+    type Delay = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+    fn spawn<T>(task: T)
+    where
+        T: Future<Output = ()> + Send + 'static,
+    {
+        async_std::task::spawn(task);
+    }
+
+    fn delay_for(duration: Duration) -> Self::Delay {
+        Box::pin(async_std::task::sleep(duration))
+    }
+}
+
+/// Produces every message to a Kafka topic, keyed by the type path of `R` unless set with
+/// [`with_key`](Self::with_key). A write completes once the broker acknowledged the
+/// message, so delivery shows up as [`SaveCompleted<R>`](crate::SaveCompleted) and a failed
+/// delivery as [`SinkFailed<R>`](crate::SinkFailed), after the sink's
+/// [`RetryPolicy`](crate::RetryPolicy).
+///
+/// ```ignore
+/// let sink = KafkaSink::new("localhost:9092", "game-analytics")
+///     .with_config("compression.type", "lz4");
+/// app.add_plugins(IoSinkPlugin::<MatchStats, _>::new(sink));
+/// ```
+pub struct KafkaSink<R> {
+    config: ClientConfig,
+    topic: String,
+    key: String,
+    delivery_timeout: Duration,
+    format: SaveFormat,
+    max_message_size: Option<u64>,
+    producer: Option<FutureProducer<DefaultClientContext, AsyncStdRuntime>>,
+    last_write_len: Option<u64>,
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R> KafkaSink<R> {
+    /// `brokers` is the comma-separated `bootstrap.servers` list.
+    pub fn new(brokers: impl Into<String>, topic: impl Into<String>) -> Self {
+        let mut config = ClientConfig::new();
+        config.set("bootstrap.servers", brokers);
+        Self {
+            config,
+            topic: topic.into(),
+            key: std::any::type_name::<R>().into(),
+            delivery_timeout: Duration::from_secs(30),
+            format: SaveFormat::default(),
+            max_message_size: None,
+            producer: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Set a librdkafka producer property, e.g. `acks` or `sasl.mechanisms`.
+    pub fn with_config(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.set(key, value);
+        self
+    }
+
+    /// Key every message with `key`, which decides its partition.
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = key.into();
+        self
+    }
+
+    /// Fail a write whose message wasn't acknowledged within `timeout`, 30 seconds by
+    /// default.
+    pub fn with_delivery_timeout(mut self, timeout: Duration) -> Self {
+        self.delivery_timeout = timeout;
+        self
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Reject encoded messages larger than `bytes` with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+}
+
+fn kafka_error(e: rdkafka::error::KafkaError) -> IoSinkError {
+    IoSinkError::Other(format!("kafka: {e}"))
+}
+
+impl<R> IoWriter<R> for KafkaSink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        let producer = self
+            .config
+            .clone()
+            .set(
+                "message.timeout.ms",
+                self.delivery_timeout.as_millis().to_string(),
+            )
+            .create()
+            .map_err(kafka_error)?;
+        self.producer = Some(producer);
+        Ok(())
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let payload = self.format.encode(&data)?;
+        IoSinkError::check_size(payload.len(), self.max_message_size)?;
+        let producer = self.producer.as_ref().ok_or(IoSinkError::NotInitialized)?;
+        let record = FutureRecord::to(&self.topic)
+            .key(&self.key)
+            .payload(&payload);
+        producer
+            .send(record, Timeout::After(self.delivery_timeout))
+            .await
+            .map_err(|(e, _)| kafka_error(e))?;
+        self.last_write_len = Some(payload.len() as u64);
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        if let Some(producer) = self.producer.as_ref() {
+            producer
+                .flush(Timeout::After(self.delivery_timeout))
+                .map_err(kafka_error)?;
+        }
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+}
@@ -0,0 +1,370 @@
+use crate::{
+    codec, envelope::VersionStamp, ready::LoadTrackerPlugin, IoSender, IoSinkError, IoSinkPlugin,
+    IoWriter, LoadSet, LoadTracker, Migrations, SaveFormat, SinkResult,
+};
+use async_channel::{unbounded, Receiver};
+use async_std::task::spawn_blocking;
+use bevy::{prelude::*, tasks::IoTaskPool};
+use redb::{Database, ReadableTable, TableDefinition, TableError};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{marker::PhantomData, path::Path, sync::Arc};
+
+const RESOURCES: TableDefinition<&str, &[u8]> = TableDefinition::new("resources");
+
+fn redb_error(e: impl Into<redb::Error>) -> IoSinkError {
+    IoSinkError::Other(format!("redb: {}", e.into()))
+}
+
+/// An embedded key-value database shared by any number of [`KvSink`]s, one key per resource.
+/// A database file can only be opened once per process, clone the store to share it.
+///
+/// ```ignore
+/// let store = KvStore::open("saves/game.redb")?;
+/// app.add_plugins((
+///     KvPlugin::<Settings>::new(store.clone()),
+///     KvPlugin::<Progress>::new(store),
+/// ));
+/// ```
+#[derive(Clone)]
+pub struct KvStore {
+    db: Arc<Database>,
+}
+
+impl KvStore {
+    /// Open the database at `path`, creating it and its parent directories if needed.
+    pub fn open(path: impl AsRef<Path>) -> SinkResult<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let db = Database::create(path).map_err(redb_error)?;
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    /// The bytes stored under `key`, `None` if there are none.
+    pub fn get(&self, key: &str) -> SinkResult<Option<Vec<u8>>> {
+        let transaction = self.db.begin_read().map_err(redb_error)?;
+        let table = match transaction.open_table(RESOURCES) {
+            Ok(table) => table,
+            // Nothing was written yet.
+            Err(TableError::TableDoesNotExist(_)) => return Ok(None),
+            Err(e) => return Err(redb_error(e)),
+        };
+        let value = table.get(key).map_err(redb_error)?;
+        Ok(value.map(|value| value.value().to_vec()))
+    }
+
+    /// Store every `(key, bytes)` pair of `entries` in one transaction, all of them or none.
+    pub fn insert_all<'a>(
+        &self,
+        entries: impl IntoIterator<Item = (&'a str, &'a [u8])>,
+    ) -> SinkResult {
+        let transaction = self.db.begin_write().map_err(redb_error)?;
+        {
+            let mut table = transaction.open_table(RESOURCES).map_err(redb_error)?;
+            for (key, bytes) in entries {
+                table.insert(key, bytes).map_err(redb_error)?;
+            }
+        }
+        transaction.commit().map_err(redb_error)
+    }
+
+    pub fn insert(&self, key: &str, bytes: &[u8]) -> SinkResult {
+        self.insert_all([(key, bytes)])
+    }
+
+    pub fn remove(&self, key: &str) -> SinkResult {
+        let transaction = self.db.begin_write().map_err(redb_error)?;
+        {
+            let mut table = transaction.open_table(RESOURCES).map_err(redb_error)?;
+            table.remove(key).map_err(redb_error)?;
+        }
+        transaction.commit().map_err(redb_error)
+    }
+}
+
+/// Read and decode what a [`KvSink`] stored under `key`, `None` if nothing was. Only values
+/// stored by a [`KvPlugin`] carry their schema version, pass [`Migrations::default`] for
+/// those of a bare sink.
+pub async fn load_kv<R>(
+    store: &KvStore,
+    key: impl Into<String>,
+    migrations: &Migrations,
+) -> Result<Option<R>, IoSinkError>
+where
+    R: DeserializeOwned,
+{
+    let (store, key) = (store.clone(), key.into());
+    let bytes = spawn_blocking(move || store.get(&key)).await?;
+    bytes
+        .map(|bytes| codec::decode(&bytes, migrations))
+        .transpose()
+}
+
+/// Stores each `R` under a key of a [`KvStore`], the type path of `R` unless set with
+/// [`with_key`](Self::with_key). Every write is its own transaction, so the database never
+/// holds half a message.
+pub struct KvSink<R> {
+    store: KvStore,
+    key: String,
+    format: SaveFormat,
+    stamp: Option<VersionStamp>,
+    max_message_size: Option<u64>,
+    /// Payload of the last write, without the envelope.
+    last_contents: Option<Vec<u8>>,
+    last_write_len: Option<u64>,
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R> KvSink<R> {
+    pub fn new(store: KvStore) -> Self {
+        Self {
+            store,
+            key: std::any::type_name::<R>().into(),
+            format: SaveFormat::default(),
+            stamp: None,
+            max_message_size: None,
+            last_contents: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Store values in a versioned envelope, so the loader only migrates older ones.
+    pub(crate) fn stamped(mut self, stamp: VersionStamp) -> Self {
+        self.stamp = Some(stamp);
+        self
+    }
+
+    /// Key of this resource, which must be unique within the store.
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = key.into();
+        self
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Reject encoded messages larger than `bytes` with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+}
+
+impl<R> IoWriter<R> for KvSink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        self.last_contents = None;
+        Ok(())
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let payload = self.format.encode(&data)?;
+        // Change detection fires on any `ResMut` deref, so identical payloads are common.
+        if self.last_contents.as_deref() == Some(payload.as_slice()) {
+            self.last_write_len = Some(0);
+            return Ok(());
+        }
+        let bytes = match &self.stamp {
+            Some(stamp) => stamp.encode(self.format, &data)?,
+            None => payload.clone(),
+        };
+        IoSinkError::check_size(bytes.len(), self.max_message_size)?;
+
+        let (store, key, len) = (self.store.clone(), self.key.clone(), bytes.len() as u64);
+        // Commits block on fsync, keep them off the sink task's executor thread.
+        spawn_blocking(move || store.insert(&key, &bytes)).await?;
+        self.last_contents = Some(payload);
+        self.last_write_len = Some(len);
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        let (store, key) = (self.store.clone(), self.key.clone());
+        spawn_blocking(move || store.remove(&key)).await?;
+        self.last_contents = None;
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+struct KvLoadReceiver<R>(Receiver<Option<R>>);
+
+/// Persists the resource `R` under a key of a [`KvStore`]: it is loaded at startup, like a
+/// [`FileSinkPlugin`](crate::FileSinkPlugin) save, then written on every change through the
+/// usual [`IoSender<R>`]. `R::default()` is inserted when the key doesn't exist or can't be
+/// read.
+pub struct KvPlugin<R> {
+    store: KvStore,
+    key: String,
+    migrations: Migrations,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R> KvPlugin<R> {
+    pub fn new(store: KvStore) -> Self {
+        Self {
+            store,
+            key: std::any::type_name::<R>().into(),
+            migrations: Migrations::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Key of this resource, the type path of `R` by default.
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = key.into();
+        self
+    }
+
+    /// Upgrade values stored by older builds while loading. Values are stored with the
+    /// current version of `migrations`.
+    pub fn with_migrations(mut self, migrations: Migrations) -> Self {
+        self.migrations = migrations;
+        self
+    }
+}
+
+impl<R> Plugin for KvPlugin<R>
+where
+    R: Resource + Clone + Serialize + DeserializeOwned + Default,
+{
+    fn build(&self, app: &mut App) {
+        let stamp = VersionStamp::new(app, &self.migrations);
+        app.add_plugins(IoSinkPlugin::<R, _>::new(
+            KvSink::new(self.store.clone())
+                .with_key(self.key.clone())
+                .stamped(stamp),
+        ));
+        if !app.is_plugin_added::<LoadTrackerPlugin>() {
+            app.add_plugins(LoadTrackerPlugin);
+        }
+        app.world_mut()
+            .resource_mut::<LoadTracker>()
+            .register::<R>();
+
+        let (tx, rx) = unbounded();
+        app.insert_resource(KvLoadReceiver::<R>(rx));
+        let (store, key, migrations) = (
+            self.store.clone(),
+            self.key.clone(),
+            self.migrations.clone(),
+        );
+        app.add_systems(Startup, move || {
+            let (store, key, migrations, tx) =
+                (store.clone(), key.clone(), migrations.clone(), tx.clone());
+            IoTaskPool::get()
+                .spawn(async move {
+                    let loaded = load_kv::<R>(&store, key.clone(), &migrations)
+                        .await
+                        .unwrap_or_else(|e| {
+                            error!("failed to load {key}: {e}");
+                            None
+                        });
+                    let _ = tx.send(loaded).await;
+                })
+                .detach();
+        });
+        app.add_systems(PreUpdate, receive_kv_load::<R>.in_set(LoadSet));
+        app.add_systems(
+            PostUpdate,
+            sync_kv::<R>.run_if(resource_exists_and_changed::<R>),
+        );
+    }
+}
+
+fn receive_kv_load<R: Resource + Default>(
+    mut commands: Commands,
+    receiver: Res<KvLoadReceiver<R>>,
+    mut tracker: ResMut<LoadTracker>,
+) {
+    if let Ok(loaded) = receiver.0.try_recv() {
+        commands.insert_resource(loaded.unwrap_or_default());
+        tracker.mark_loaded::<R>();
+    }
+}
+
+fn sync_kv<R: Resource + Clone>(sender: Res<IoSender<R>>, res: Res<R>) {
+    if let Err(err) = sender.enqueue(res.clone()) {
+        error!("{err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_lite::future::block_on;
+    use serde::Deserialize;
+    use serde_json::Value;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Level {
+        level: u64,
+    }
+
+    /// A fresh store for `test`, in the temp directory.
+    fn store(test: &str) -> KvStore {
+        let dir = std::env::temp_dir().join(format!("bevy_io_sink_kv_{test}"));
+        let _ = std::fs::remove_dir_all(&dir);
+        KvStore::open(dir.join("game.redb")).unwrap()
+    }
+
+    #[test]
+    fn values_round_trip() {
+        let store = store("round_trip");
+        let mut sink = KvSink::<Level>::new(store.clone()).with_key("level");
+        block_on(async {
+            sink.init().await.unwrap();
+            sink.write(Level { level: 3 }).await.unwrap();
+        });
+
+        let loaded = block_on(load_kv::<Level>(&store, "level", &Migrations::default()));
+        assert_eq!(loaded.unwrap(), Some(Level { level: 3 }));
+        let other = block_on(load_kv::<Level>(&store, "other", &Migrations::default()));
+        assert_eq!(other.unwrap(), None);
+    }
+
+    #[test]
+    fn stamped_values_are_not_migrated_again() {
+        // Not idempotent, a value migrated twice would come back with a higher level.
+        let migrations = Migrations::new().then(|mut payload: Value| {
+            payload["level"] = (payload["level"].as_u64().unwrap_or(0) + 1).into();
+            Ok(payload)
+        });
+        let store = store("stamped");
+        let stamp = VersionStamp::new(&mut App::new(), &migrations);
+        let mut sink = KvSink::<Level>::new(store.clone())
+            .with_key("level")
+            .stamped(stamp);
+        block_on(sink.write(Level { level: 3 })).unwrap();
+
+        let loaded = block_on(load_kv::<Level>(&store, "level", &migrations));
+        assert_eq!(loaded.unwrap(), Some(Level { level: 3 }));
+    }
+
+    #[test]
+    fn wipe_removes_only_the_sink_key() {
+        let store = store("wipe");
+        store.insert("other", b"{\"level\":1}").unwrap();
+        let mut sink = KvSink::<Level>::new(store.clone()).with_key("level");
+        block_on(async {
+            sink.write(Level { level: 3 }).await.unwrap();
+            sink.wipe().await.unwrap();
+        });
+
+        assert_eq!(store.get("level").unwrap(), None);
+        assert!(store.get("other").unwrap().is_some());
+        // The next write isn't skipped as unchanged.
+        block_on(sink.write(Level { level: 3 })).unwrap();
+        assert!(store.get("level").unwrap().is_some());
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{EnqueueError, IoSender, IoSinkPlugin, TransformSink};
+use std::{any::type_name, fmt, marker::PhantomData};
+
+/// An `R` bound for the sink labeled `L`, so one resource type can have several sinks, e.g.
+/// an autosave, a manual save and telemetry. `L` is any type naming the destination; each
+/// label gets its own [`IoSender<Labeled<R, L>>`](LabeledSender), stats, status and events.
+///
+/// Labels are types only, there are no string labels: sinks are told apart by their type
+/// everywhere, and a label chosen at runtime would need its own registry. Labeled sinks only
+/// write: they don't load `R` at startup and don't save it on change, values reach them
+/// through [`save_resource_to`](crate::CommandsSaveExt::save_resource_to) or
+/// [`enqueue_labeled`](IoSender::enqueue_labeled). Keep a
+/// [`FileSinkPlugin<R>`](crate::FileSinkPlugin) as the one sink that loads and syncs `R`.
+///
+/// ```ignore
+/// struct Autosave;
+/// struct ManualSave;
+///
+/// app.add_plugins(IoSinkPlugin::<Labeled<World, Autosave>, _>::labeled(
+///     FileSink::new("saves/autosave.json"),
+/// ))
+/// .add_plugins(IoSinkPlugin::<Labeled<World, ManualSave>, _>::labeled(
+///     FileSink::new("saves/manual.json"),
+/// ));
+///
+/// fn quick_save(mut commands: Commands) {
+///     commands.save_resource_to::<World, ManualSave>();
+/// }
+/// ```
+pub struct Labeled<R, L> {
+    pub value: R,
+    _label: PhantomData<fn() -> L>,
+}
+
+/// The sender of the sink labeled `L` for `R`.
+pub type LabeledSender<R, L> = IoSender<Labeled<R, L>>;
+
+impl<R, L> Labeled<R, L> {
+    pub fn new(value: R) -> Self {
+        Self {
+            value,
+            _label: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.value
+    }
+}
+
+impl<R: Clone, L> Clone for Labeled<R, L> {
+    fn clone(&self) -> Self {
+        Self::new(self.value.clone())
+    }
+}
+
+impl<R: fmt::Debug, L> fmt::Debug for Labeled<R, L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Labeled")
+            .field("label", &type_name::<L>())
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl<R, L> IoSender<Labeled<R, L>> {
+    /// [`enqueue`](IoSender::enqueue) `value` for the sink labeled `L`.
+    pub fn enqueue_labeled(&self, value: R) -> Result<(), EnqueueError<Labeled<R, L>>> {
+        self.enqueue(Labeled::new(value))
+    }
+}
+
+type Unlabel<R, L> = fn(Labeled<R, L>) -> R;
+
+impl<R, L, W> IoSinkPlugin<Labeled<R, L>, TransformSink<W, Unlabel<R, L>>> {
+    /// A sink labeled `L` around a writer of plain `R`.
+    pub fn labeled(writer: W) -> Self {
+        Self::new(TransformSink::new(writer, Labeled::into_inner))
+    }
+}
@@ -0,0 +1,149 @@
+//! Declarative stacking of writer combinators, in the spirit of tower's layers: a [`Layer`]
+//! wraps a writer in another, and stacks of layers are plain tuples that can be defined once
+//! and applied to several sinks.
+//!
+//! Layers cover the combinators of this crate: retrying, buffering, deduplicating, filtering,
+//! mapping and sampling. There are no compression or encryption layers, implement [`Layer`]
+//! for a writer of your own to add one to a stack.
+//!
+//! ```ignore
+//! let remote = (RetryPolicy::new(5), DedupLayer, Sample::interval(Duration::from_secs(10)));
+//! let sink = HttpSink::new("https://example.com/saves/world").layer(remote);
+//! app.add_plugins(IoSinkPlugin::<World, _>::new(sink));
+//! ```
+
+use crate::{
+    BufferedSink, Dedup, DedupSink, FilterSink, IoWriter, RetryPolicy, RetrySink, Sample,
+    SampleSink, TransformSink,
+};
+
+/// Wraps a writer of `R` in another writer, see [`IoWriterExt::layer`](crate::IoWriterExt::layer).
+///
+/// `()` leaves the writer as it is, and a tuple applies its layers from first to last, so
+/// the last one sees messages first and the first one is closest to the backend. Layers in
+/// a tuple keep the message type; put a [`MapLayer`] last.
+pub trait Layer<R, W> {
+    type Writer;
+
+    fn layer(self, inner: W) -> Self::Writer;
+}
+
+impl<R, W> Layer<R, W> for () {
+    type Writer = W;
+
+    fn layer(self, inner: W) -> W {
+        inner
+    }
+}
+
+impl<R, W, A, B> Layer<R, W> for (A, B)
+where
+    A: Layer<R, W>,
+    B: Layer<R, A::Writer>,
+{
+    type Writer = B::Writer;
+
+    fn layer(self, inner: W) -> Self::Writer {
+        self.1.layer(self.0.layer(inner))
+    }
+}
+
+impl<R, W, A, B, C> Layer<R, W> for (A, B, C)
+where
+    A: Layer<R, W>,
+    B: Layer<R, A::Writer>,
+    C: Layer<R, B::Writer>,
+{
+    type Writer = C::Writer;
+
+    fn layer(self, inner: W) -> Self::Writer {
+        self.2.layer(self.1.layer(self.0.layer(inner)))
+    }
+}
+
+impl<R, W, A, B, C, D> Layer<R, W> for (A, B, C, D)
+where
+    A: Layer<R, W>,
+    B: Layer<R, A::Writer>,
+    C: Layer<R, B::Writer>,
+    D: Layer<R, C::Writer>,
+{
+    type Writer = D::Writer;
+
+    fn layer(self, inner: W) -> Self::Writer {
+        self.3
+            .layer(self.2.layer(self.1.layer(self.0.layer(inner))))
+    }
+}
+
+/// Retries with this policy, see [`RetrySink`].
+impl<R, W: IoWriter<R>> Layer<R, W> for RetryPolicy {
+    type Writer = RetrySink<W>;
+
+    fn layer(self, inner: W) -> RetrySink<W> {
+        RetrySink::new(inner, self)
+    }
+}
+
+/// Holds messages back until a flush or a full batch, see [`BufferedSink`].
+#[derive(Debug, Clone, Copy)]
+pub struct BufferLayer {
+    pub capacity: usize,
+}
+
+impl<R, W: IoWriter<R>> Layer<R, W> for BufferLayer {
+    type Writer = BufferedSink<R, W>;
+
+    fn layer(self, inner: W) -> BufferedSink<R, W> {
+        BufferedSink::new(inner).with_capacity(self.capacity)
+    }
+}
+
+/// Skips messages equal to the last one written, see [`IoWriterExt::dedup`](crate::IoWriterExt::dedup).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DedupLayer;
+
+impl<R, W> Layer<R, W> for DedupLayer
+where
+    R: Clone + PartialEq + Send + Sync + 'static,
+    W: IoWriter<R>,
+{
+    type Writer = DedupSink<R, W, R, fn(&R) -> R>;
+
+    fn layer(self, inner: W) -> Self::Writer {
+        FilterSink::new(inner, Dedup::new(R::clone))
+    }
+}
+
+/// Drops the messages the predicate rejects, see [`FilterSink`].
+#[derive(Debug, Clone, Copy)]
+pub struct FilterLayer<F>(pub F);
+
+impl<R, W: IoWriter<R>, F> Layer<R, W> for FilterLayer<F> {
+    type Writer = FilterSink<W, F>;
+
+    fn layer(self, inner: W) -> FilterSink<W, F> {
+        FilterSink::new(inner, self.0)
+    }
+}
+
+/// Converts messages before writing them, see [`TransformSink`].
+#[derive(Debug, Clone, Copy)]
+pub struct MapLayer<F>(pub F);
+
+impl<R, W: IoWriter<R>, F> Layer<R, W> for MapLayer<F> {
+    type Writer = TransformSink<W, F>;
+
+    fn layer(self, inner: W) -> TransformSink<W, F> {
+        TransformSink::new(inner, self.0)
+    }
+}
+
+/// Writes only a sample of the messages, see [`SampleSink`].
+impl<R, W: IoWriter<R>> Layer<R, W> for Sample {
+    type Writer = SampleSink<W>;
+
+    fn layer(self, inner: W) -> SampleSink<W> {
+        FilterSink::new(inner, self)
+    }
+}
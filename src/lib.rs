@@ -1,165 +1,595 @@
 use async_channel::{unbounded, Receiver, Sender};
-use async_fs::{File, OpenOptions};
-use async_std::{
-    io::{self, BufWriter, ReadExt, SeekExt, WriteExt},
-    path::PathBuf,
-    sync::Mutex,
+use async_std::sync::Mutex;
+use bevy::prelude::*;
+use std::{marker::PhantomData, sync::Arc, time::Duration};
+
+#[cfg(feature = "asset")]
+mod asset;
+#[cfg(feature = "file")]
+mod batch;
+mod boxed;
+mod buffered;
+#[cfg(feature = "bug-report")]
+mod bug_report;
+mod channel;
+#[cfg(feature = "chaos")]
+mod chaos;
+mod circuit;
+pub mod codec;
+mod compat;
+#[cfg(feature = "file")]
+mod component;
+mod dead_letter;
+mod dedup;
+#[cfg(feature = "file")]
+mod document;
+#[cfg(feature = "testing")]
+pub mod test_utils;
+// Only the file, journal and database backends construct envelopes.
+#[cfg_attr(
+    not(any(
+        feature = "file",
+        feature = "journal",
+        feature = "kv",
+        feature = "sqlite",
+        feature = "redis"
+    )),
+    allow(dead_code)
+)]
+mod envelope;
+mod error;
+mod events;
+mod ext;
+mod fallback;
+mod fanout;
+#[cfg(feature = "testing")]
+mod faulty;
+#[cfg(feature = "file")]
+mod file;
+mod filter;
+#[cfg(any(feature = "tcp", feature = "ipc"))]
+mod frame;
+mod groups;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+mod indexed_db;
+#[cfg(feature = "debug")]
+mod inspect;
+#[cfg(all(feature = "ipc", any(unix, windows)))]
+mod ipc;
+#[cfg(feature = "journal")]
+mod journal;
+#[cfg(feature = "kafka")]
+mod kafka;
+#[cfg(feature = "kv")]
+mod kv;
+mod label;
+pub mod layer;
+#[cfg(feature = "file")]
+mod load;
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+mod local_storage;
+#[cfg(feature = "testing")]
+mod memory;
+#[cfg(feature = "file")]
+mod metadata;
+#[cfg(feature = "file")]
+mod migrate;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+#[cfg(feature = "nats")]
+mod nats;
+#[cfg(all(target_arch = "wasm32", feature = "opfs"))]
+mod opfs;
+#[cfg(feature = "file")]
+mod path;
+#[cfg(feature = "file")]
+mod persist;
+#[cfg_attr(
+    not(any(
+        feature = "file",
+        feature = "journal",
+        feature = "kv",
+        feature = "redis",
+        feature = "sqlite"
+    )),
+    allow(dead_code)
+)]
+mod ready;
+#[cfg(feature = "redis")]
+mod redis;
+#[cfg(feature = "reflect")]
+mod reflect;
+#[cfg(feature = "file")]
+mod remote;
+#[cfg(feature = "file")]
+mod requests;
+mod retry;
+mod rng;
+#[cfg(feature = "s3")]
+mod s3;
+mod sample;
+#[cfg(feature = "file")]
+mod save_list;
+mod saver;
+#[cfg(feature = "scene")]
+mod scene;
+#[cfg(feature = "file")]
+mod slots;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(all(feature = "file", feature = "states"))]
+mod state;
+mod stats;
+mod status;
+#[cfg(feature = "stdio")]
+mod stdio;
+#[cfg(feature = "steam")]
+mod steam;
+#[cfg(feature = "file")]
+mod storage;
+mod task;
+#[cfg(feature = "tcp")]
+mod tcp;
+mod telemetry;
+#[cfg(feature = "text")]
+mod text;
+#[cfg(feature = "thumbnail")]
+mod thumbnail;
+mod transform;
+#[cfg(feature = "udp")]
+mod udp;
+#[cfg(feature = "file")]
+mod vfs;
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+mod web;
+#[cfg(feature = "websocket")]
+mod websocket;
+
+#[cfg(feature = "asset")]
+pub use asset::{AssetPersistPlugin, AssetSnapshot, PersistedAsset};
+#[cfg(feature = "file")]
+pub use batch::{BatchOperation, BatchRunner};
+pub use buffered::BufferedSink;
+use buffered::FlushTimer;
+#[cfg(feature = "bug-report")]
+pub use bug_report::{BugReportFailed, BugReportPlugin, BugReportRequest, BugReportWritten};
+pub use channel::{ChannelConfig, EnqueueError, IoSender, OverflowPolicy};
+#[cfg(feature = "chaos")]
+pub use chaos::ChaosSink;
+pub use circuit::{CircuitBreaker, CircuitState, CircuitStateChanged};
+pub use codec::{Migrations, SaveFormat};
+pub use compat::{check_saves, check_saves_with_migrations, CompatibilityReport, SaveCheck};
+#[cfg(feature = "file")]
+pub use component::{ComponentSinkPlugin, ComponentSnapshot, PersistId, PersistedComponent};
+pub use dead_letter::{DeadLetter, DeadLetters};
+pub use dedup::{Dedup, DedupSink};
+#[cfg(feature = "file")]
+pub use document::{DocumentPlugin, SaveDocument};
+pub use envelope::{ClockSource, Envelope, EnvelopeClock, EnvelopeSink};
+pub use error::{BoxedError, IoSinkError, SinkErrorKind, SinkResult, TargetError};
+pub use events::{
+    LoggedSinkError, PersistedWiped, SaveCompleted, SinkErrorLog, SinkFailed, SinkPanicked,
+    SinkPhase,
 };
-use bevy::{prelude::*, tasks::IoTaskPool};
-use serde::{Deserialize, Serialize};
-use std::{io::SeekFrom, marker::PhantomData, sync::Arc};
+use events::{TaskReportReceiver, TaskReporter};
+pub use ext::{CommandsSaveExt, IoWriterExt, WorldSaveExt};
+pub use fallback::{FallbackPlugin, FallbackSink, FallbackStatus, SinkDegraded, SinkRecovered};
+pub use fanout::FanoutSink;
+#[cfg(feature = "testing")]
+pub use faulty::{Fault, FaultySink};
+#[cfg(feature = "file")]
+pub use file::{FileSink, FileSinkPlugin};
+pub use filter::{Filter, FilterSink};
+pub use groups::{IoSinks, SinkControl};
+#[cfg(feature = "http")]
+pub use http::HttpSink;
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub use indexed_db::{load_indexed_db, IndexedDbSink};
+#[cfg(feature = "debug")]
+pub use inspect::{InspectSink, InspectedPayload, PayloadInspector, PayloadState};
+#[cfg(all(feature = "ipc", any(unix, windows)))]
+pub use ipc::LocalSocketSink;
+#[cfg(all(feature = "ipc", windows))]
+pub use ipc::NamedPipeSink;
+#[cfg(all(feature = "ipc", unix))]
+pub use ipc::UnixSocketSink;
+#[cfg(feature = "journal")]
+pub use journal::{
+    detect_gaps, load_journal, JournalLoaded, JournalReport, JournalSink, JournalSinkPlugin,
+    SequenceGap,
+};
+#[cfg(feature = "kafka")]
+pub use kafka::KafkaSink;
+#[cfg(feature = "kv")]
+pub use kv::{load_kv, KvPlugin, KvSink, KvStore};
+pub use label::{Labeled, LabeledSender};
+pub use layer::Layer;
+#[cfg(feature = "file")]
+pub use load::{LoadCompleted, LoadFailed, LoadSource, MissingSavePolicy, UnreadableSavePolicy};
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub use local_storage::LocalStorageSink;
+#[cfg(feature = "testing")]
+pub use memory::{MemorySink, MemorySinkHandle};
+#[cfg(feature = "file")]
+pub use metadata::{read_save_metadata, MetadataRecord, MetadataSink, SaveInfo, SaveMetadata};
+#[cfg(feature = "file")]
+pub use migrate::{migrate_save_dir, SaveDirMigrated, SaveDirMigrationPlugin};
+#[cfg(feature = "mqtt")]
+pub use mqtt::{MqttOptions, MqttSink, QoS};
+// `self::` as the module shares its name with the `nats` crate.
+#[cfg(feature = "nats")]
+pub use self::nats::{JetStreamOptions, NatsSink, StorageType};
+#[cfg(all(target_arch = "wasm32", feature = "opfs"))]
+pub use opfs::OpfsSink;
+#[cfg(feature = "file")]
+pub use path::{PathVariables, SavePath};
+#[cfg(feature = "file")]
+pub use persist::{AppPersistExt, Persist, PersistOptions, PersistPlugins};
+#[cfg(feature = "states")]
+pub use ready::LoadingStatePlugin;
+pub use ready::{
+    persistence_ready, AllLoaded, LoadSet, LoadTracker, PausedLoadPlugin, PersistenceReady,
+    ResumeAfterLoad,
+};
+// `self::` as the module shares its name with the `redis` crate.
+#[cfg(feature = "redis")]
+pub use self::redis::{load_redis, RedisPlugin, RedisSink};
+#[cfg(feature = "reflect")]
+pub use reflect::{
+    ReflectPersistPlugin, ReflectPersistence, ReflectTarget, ReflectedFileSink, ReflectedSave,
+};
+#[cfg(feature = "file")]
+pub use remote::{
+    PutOutcome, RemoteObject, RemoteStore, RemoteSyncPlugin, ResolveSyncConflict, Revision,
+    SyncCompleted, SyncConflict, SyncFailed, SyncOutcome, SyncRemote, SyncResolution,
+};
+#[cfg(feature = "file")]
+pub use requests::{LoadRequest, SaveRequest};
+pub use retry::{RetryPolicy, RetrySink};
+// `self::` as the module shares its name with the `s3` crate.
+#[cfg(feature = "s3")]
+pub use self::s3::{load_s3, Bucket, Credentials, Region, S3Sink};
+pub use sample::{Sample, SampleSink};
+#[cfg(feature = "file")]
+pub use save_list::{scan_saves, SaveEntry, SaveList, SaveListPlugin, SaveListUpdated, ScanSaves};
+pub use saver::{PersistGuard, Saver};
+#[cfg(feature = "scene")]
+pub use scene::{scene_from_ron, scene_to_ron, SceneSink, SceneSinkPlugin, SceneSnapshot};
+#[cfg(feature = "file")]
+pub use slots::{SaveSlots, SaveSlotsPlugin, SlotChange, SlotChanged, SlotFailed, SlotRecord};
+#[cfg(feature = "sqlite")]
+pub use sqlite::{load_sqlite, SqlitePlugin, SqliteSink};
+#[cfg(all(feature = "file", feature = "states"))]
+pub use state::{PersistedState, StatePersistPlugin};
+use stats::SinkShared;
+pub use stats::{HeartbeatConfig, IoSinkStats, SinkStalled};
+pub use status::{SinkState, SinkStatus};
+#[cfg(feature = "stdio")]
+pub use stdio::{StdStream, StdStreamSink};
+#[cfg(feature = "steam")]
+pub use steam::{load_steam_cloud, SteamCloudSink};
+use task::IoSinkTaskData;
+pub use task::PanicPolicy;
+#[cfg(feature = "tcp")]
+pub use tcp::TcpSink;
+pub use telemetry::TelemetryConsent;
+#[cfg(feature = "file")]
+pub use telemetry::TelemetryConsentPlugin;
+use telemetry::TelemetryGatePlugin;
+#[cfg(feature = "text")]
+pub use text::{sanitize, SaveString};
+#[cfg(feature = "thumbnail")]
+pub use thumbnail::{
+    read_thumbnail, thumbnail_path, AttachThumbnail, CaptureThumbnail, ThumbnailPlugin,
+    ThumbnailWritten,
+};
+pub use transform::TransformSink;
+#[cfg(feature = "udp")]
+pub use udp::UdpSink;
+#[cfg(feature = "file")]
+pub use vfs::{MemoryFs, StorageFs, Vfs, VfsFile, VfsFuture};
+#[cfg(feature = "websocket")]
+pub use websocket::WebSocketSink;
+
+#[cfg(feature = "derive")]
+pub use bevy_io_sink_derive::Persist;
+
+/// Used by `#[derive(Persist)]`.
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use serde;
+}
 
-#[derive(Resource, Clone, Deref, DerefMut)]
-pub struct IoSender<R>(Sender<R>);
+/// The commonly used plugins, sinks, events and resources: `use bevy_io_sink::prelude::*;`.
+pub mod prelude {
+    #[cfg(feature = "states")]
+    pub use crate::LoadingStatePlugin;
+    #[cfg(all(feature = "file", feature = "states"))]
+    pub use crate::StatePersistPlugin;
+    pub use crate::{
+        persistence_ready, AllLoaded, CircuitBreaker, CircuitStateChanged, CommandsSaveExt,
+        EnqueueError, IoSender, IoSinkError, IoSinkPlugin, IoSinkStats, IoSinks, IoWriter,
+        IoWriterExt, LoadTracker, Migrations, OverflowPolicy, PanicPolicy, PausedLoadPlugin,
+        PersistGuard, PersistedWiped, ResumeAfterLoad, RetryPolicy, SaveCompleted, SaveFormat,
+        Saver, SinkFailed, SinkPanicked, SinkStalled, SinkState, SinkStatus, SinkTag,
+        TelemetryConsent, WorldSaveExt, WriterCapabilities,
+    };
+    #[cfg(feature = "file")]
+    pub use crate::{
+        AppPersistExt, FileSink, FileSinkPlugin, LoadCompleted, LoadFailed, LoadRequest,
+        PathVariables, Persist, PersistPlugins, SaveInfo, SaveList, SaveListPlugin, SaveMetadata,
+        SavePath, SaveRequest, SaveSlots, SaveSlotsPlugin, TelemetryConsentPlugin,
+    };
+    #[cfg(feature = "journal")]
+    pub use crate::{JournalLoaded, JournalSink, JournalSinkPlugin};
+}
 
-#[derive(Resource)]
-struct IoSinkTaskData<R, W> {
-    rx: Receiver<R>,
-    writer: Arc<Mutex<W>>,
+/// Category a sink belongs to, used by cross-cutting switches such as [`TelemetryConsent`]
+/// and by group operations on [`IoSinks`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SinkTag {
+    #[default]
+    Save,
+    Telemetry,
+    Debug,
 }
 
-struct IoSinkPlugin<R, W> {
+const DEFAULT_PRE_INIT_CAPACITY: usize = 64;
+
+pub struct IoSinkPlugin<R, W> {
     writer: Arc<Mutex<W>>,
+    tag: SinkTag,
+    channel: ChannelConfig,
+    heartbeat: HeartbeatConfig,
+    panic_policy: PanicPolicy,
+    pre_init_capacity: usize,
+    retry: Option<RetryPolicy>,
+    dead_letter_capacity: Option<usize>,
+    clone: Option<fn(&R) -> R>,
+    circuit_breaker: Option<CircuitBreaker>,
+    flush_interval: Option<Duration>,
     _phantom: PhantomData<R>,
 }
 
 impl<R, W> IoSinkPlugin<R, W> {
-    fn new(writer: W) -> Self {
+    pub fn new(writer: W) -> Self {
         Self {
             writer: Arc::new(Mutex::new(writer)),
+            tag: SinkTag::default(),
+            channel: ChannelConfig::default(),
+            heartbeat: HeartbeatConfig::default(),
+            panic_policy: PanicPolicy::default(),
+            pre_init_capacity: DEFAULT_PRE_INIT_CAPACITY,
+            retry: None,
+            dead_letter_capacity: None,
+            clone: None,
+            circuit_breaker: None,
+            flush_interval: None,
             _phantom: PhantomData,
         }
     }
+
+    pub fn with_tag(mut self, tag: SinkTag) -> Self {
+        self.tag = tag;
+        self
+    }
+
+    pub fn with_channel(mut self, channel: ChannelConfig) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    pub fn with_heartbeat(mut self, heartbeat: HeartbeatConfig) -> Self {
+        self.heartbeat = heartbeat;
+        self
+    }
+
+    pub fn with_panic_policy(mut self, panic_policy: PanicPolicy) -> Self {
+        self.panic_policy = panic_policy;
+        self
+    }
+
+    /// How many messages sent before `init` completes are kept for replay, the oldest are
+    /// dropped beyond that.
+    pub fn with_pre_init_capacity(mut self, capacity: usize) -> Self {
+        self.pre_init_capacity = capacity;
+        self
+    }
+
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// Flush the writer every `interval` of real time, e.g. to write out what a
+    /// [`BufferedSink`] holds back. Writers are otherwise only flushed on request, see
+    /// [`SinkControl::Flush`].
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = Some(interval);
+        self
+    }
+}
+
+impl<R: Clone, W> IoSinkPlugin<R, W> {
+    /// Retry failed writes with exponential backoff before reporting a [`SinkFailed`] event.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self.clone = Some(R::clone);
+        self
+    }
+
+    /// Keep up to `capacity` messages whose write ultimately failed in a [`DeadLetters<R>`]
+    /// resource instead of discarding them.
+    pub fn with_dead_letters(mut self, capacity: usize) -> Self {
+        self.dead_letter_capacity = Some(capacity);
+        self.clone = Some(R::clone);
+        self
+    }
 }
 
 impl<R, W> Plugin for IoSinkPlugin<R, W>
 where
-    R: Resource + Send + Sync + 'static,
+    R: Send + Sync + 'static,
     W: IoWriter<R> + Send + Sync + 'static,
 {
     fn build(&self, app: &mut App) {
-        let (tx, rx): (Sender<R>, Receiver<R>) = unbounded();
-
-        app.insert_resource(IoSender(tx));
+        let (tx, rx): (Sender<R>, Receiver<R>) = self.channel.channel();
+        let (control_tx, control_rx) = unbounded();
+        let shared = Arc::new(SinkShared::new(self.heartbeat));
+        let (report_tx, report_rx) = unbounded();
+        let dead_letters = self.dead_letter_capacity.map(DeadLetters::<R>::new);
+
+        app.insert_resource(IoSender::new(
+            tx,
+            rx.clone(),
+            self.channel.policy,
+            shared.clone(),
+        ));
 
         app.insert_resource(IoSinkTaskData {
             rx,
             writer: self.writer.clone(),
+            control: control_rx,
+            shared,
+            reporter: TaskReporter(report_tx),
+            tag: self.tag,
+            panic_policy: self.panic_policy,
+            pre_init_capacity: self.pre_init_capacity,
+            retry: self.retry,
+            dead_letters: dead_letters.clone(),
+            clone: self.clone,
+            circuit_breaker: self.circuit_breaker,
         });
+        if let Some(dead_letters) = dead_letters {
+            app.insert_resource(dead_letters);
+        }
+        app.insert_resource(TaskReportReceiver::<R> {
+            rx: report_rx,
+            _marker: PhantomData,
+        });
+        app.add_event::<SaveCompleted<R>>();
+        app.add_event::<SinkFailed<R>>();
+        app.add_event::<SinkStalled<R>>();
+        app.add_event::<SinkPanicked<R>>();
+        app.add_event::<CircuitStateChanged<R>>();
+        app.add_event::<PersistedWiped<R>>();
+        app.init_resource::<IoSinkStats<R>>();
+        app.init_resource::<SinkStatus<R>>();
+
+        // Nothing else holds the writer before the task is spawned.
+        let capabilities = self
+            .writer
+            .try_lock()
+            .map(|writer| writer.capabilities())
+            .unwrap_or_default();
+        if let Some(interval) = self.flush_interval {
+            app.insert_resource(FlushTimer::<R>::new(interval, control_tx.clone()))
+                .add_systems(Update, buffered::flush_periodically::<R>);
+        }
+        app.world_mut()
+            .get_resource_or_init::<IoSinks>()
+            .register::<R>(self.tag, control_tx, capabilities);
 
-        app.add_systems(Startup, spawn_io_sink_task::<R, W>);
-    }
-}
+        if self.tag == SinkTag::Telemetry && !app.is_plugin_added::<TelemetryGatePlugin>() {
+            app.add_plugins(TelemetryGatePlugin);
+        }
 
-fn spawn_io_sink_task<R, W>(task_data: Res<IoSinkTaskData<R, W>>)
-where
-    R: Send + 'static,
-    W: IoWriter<R> + Send + Sync + 'static,
-{
-    let rx = task_data.rx.clone();
-    let writer = task_data.writer.clone();
-
-    IoTaskPool::get()
-        .spawn(async move {
-            let mut writer_lock = writer.lock().await;
-            if let Err(e) = writer_lock.init().await {
-                error!("{}", e);
-            }
-
-            while let Ok(msg) = rx.recv().await {
-                if let Err(e) = writer_lock.write(msg).await {
-                    error!("{}", e);
-                }
-            }
-
-            if let Err(e) = writer_lock.close().await {
-                error!("{}", e);
-            }
-        })
-        .detach();
+        app.add_systems(Startup, task::spawn_io_sink_task::<R, W>);
+        app.add_systems(
+            PreUpdate,
+            (
+                stats::update_sink_stats::<R>,
+                events::forward_task_reports::<R>,
+            ),
+        );
+    }
 }
 
 pub trait IoWriter<R>: Send + Sync + 'static {
-    fn init(&mut self) -> impl std::future::Future<Output = io::Result<()>> + Send {
+    fn init(&mut self) -> impl std::future::Future<Output = SinkResult> + Send {
         async { Ok(()) }
     }
 
-    fn write(&mut self, data: R) -> impl std::future::Future<Output = io::Result<()>> + Send;
+    fn write(&mut self, data: R) -> impl std::future::Future<Output = SinkResult> + Send;
 
-    fn flush(&mut self) -> impl std::future::Future<Output = io::Result<()>> + Send {
+    fn flush(&mut self) -> impl std::future::Future<Output = SinkResult> + Send {
         async { Ok(()) }
     }
 
-    fn close(&mut self) -> impl std::future::Future<Output = io::Result<()>> + Send {
+    fn close(&mut self) -> impl std::future::Future<Output = SinkResult> + Send {
         async { Ok(()) }
     }
-}
-
-pub struct FileSink<R> {
-    path: PathBuf,
-    writer: Option<BufWriter<File>>,
-    _marker: PhantomData<R>,
-}
 
-impl<R> FileSink<R> {
-    pub fn new(path: impl Into<PathBuf>) -> Self {
-        Self {
-            path: path.into(),
-            writer: None,
-            _marker: PhantomData,
-        }
+    /// Bytes produced by the most recent successful `write`, if the writer knows.
+    fn last_write_len(&self) -> Option<u64> {
+        None
     }
-}
 
-impl<R> IoWriter<R> for FileSink<R>
-where
-    R: Serialize + Send + Sync + 'static,
-{
-    async fn init(&mut self) -> io::Result<()> {
-        let file = OpenOptions::new()
-            .create(true)
-            .read(true)
-            .write(true)
-            .append(false)
-            .open(&self.path)
-            .await?;
-        self.writer = Some(BufWriter::with_capacity(64 * 1024, file));
-        Ok(())
+    /// How many of the bytes of the most recent successful `write` differ from what was
+    /// there before, if the writer can tell. Feeds [`IoSinkStats::write_amplification`].
+    fn last_write_changed(&self) -> Option<u64> {
+        None
     }
 
-    async fn write(&mut self, data: R) -> io::Result<()> {
-        let json =
-            serde_json::to_vec(&data).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
-
-        let writer = self.writer.as_mut().expect("FileSink::init not called");
-
-        writer.seek(SeekFrom::Start(0)).await?;
-        writer.write_all(&json).await?;
-        writer.get_mut().set_len(json.len() as u64).await?;
+    /// What the backend supports, recorded in [`IoSinks`] when the sink is added.
+    fn capabilities(&self) -> WriterCapabilities {
+        WriterCapabilities::default()
+    }
 
-        writer.flush().await
+    /// Delete everything the writer persisted, see [`CommandsSaveExt::wipe_persisted`]. The
+    /// next `write` starts over. Unsupported by default.
+    fn wipe(&mut self) -> impl std::future::Future<Output = SinkResult> + Send {
+        async {
+            Err(IoSinkError::Other(
+                "this sink can't delete what it wrote".into(),
+            ))
+        }
     }
 }
-pub struct FileSinkPlugin<R> {
-    /// If true, the resource will be synced to disk on every change.
-    sync_res: bool,
-    path: PathBuf,
-    _phantom: PhantomData<R>,
+
+/// Operations a writer's backend supports, so features built on top of a sink can pick the
+/// best strategy for it instead of assuming a file. Everything is unsupported by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct WriterCapabilities {
+    /// New records can be added without rewriting earlier ones.
+    pub append: bool,
+    /// Existing contents can be overwritten in place.
+    pub seek: bool,
+    /// A complete write can be staged and swapped in at once, readers never see it half done.
+    pub atomic_rename: bool,
+    /// Several buffers can be written in one call.
+    pub vectored: bool,
 }
 
-impl<R> FileSinkPlugin<R> {
-    pub fn new(path: impl Into<PathBuf>) -> Self {
+impl WriterCapabilities {
+    /// Only what both support, for sinks writing to either.
+    pub fn intersection(self, other: Self) -> Self {
         Self {
-            path: path.into(),
-            _phantom: PhantomData,
-            sync_res: false,
+            append: self.append && other.append,
+            seek: self.seek && other.seek,
+            atomic_rename: self.atomic_rename && other.atomic_rename,
+            vectored: self.vectored && other.vectored,
         }
     }
 }
 
+/// Periodic save, see [`FileSinkPlugin::with_autosave`](crate::FileSinkPlugin::with_autosave).
+#[derive(Debug, Clone)]
 pub struct AutoSave {
     pub enabled: bool,
     pub timer: Timer,
+    /// Adjust the timer to how often the resource changes, see [`AutoSave::adaptive`].
+    pub adaptive: Option<AdaptiveInterval>,
+}
+
+/// Bounds of an adaptive [`AutoSave`] interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdaptiveInterval {
+    pub min: Duration,
+    pub max: Duration,
 }
 
 impl AutoSave {
@@ -167,98 +597,23 @@ impl AutoSave {
         Self {
             enabled: true,
             timer,
+            adaptive: None,
         }
     }
-}
-
-#[derive(Resource)]
-struct LoadFileReceiver<R>(Receiver<R>);
-
-impl<R> Plugin for FileSinkPlugin<R>
-where
-    R: for<'de> Deserialize<'de> + Clone + Serialize + Resource + Default + Send + Sync + 'static,
-{
-    fn build(&self, app: &mut App) {
-        let path = self.path.clone();
-        let (tx, rx): (Sender<R>, Receiver<R>) = unbounded();
-
-        let file_sink = FileSink::<R>::new(self.path.clone());
-        app.add_plugins(IoSinkPlugin::<R, FileSink<R>>::new(file_sink));
-
-        app.insert_resource(LoadFileReceiver(rx));
 
-        app.add_systems(
-            FixedUpdate,
-            |mut commands: Commands, load_file_recv: Res<LoadFileReceiver<R>>| {
-                if let Ok(msg) = load_file_recv.0.try_recv() {
-                    commands.insert_resource(msg);
-                }
-            },
-        );
-        if self.sync_res {
-            app.add_systems(
-                Update,
-                sync_file::<R>.run_if(resource_exists_and_changed::<R>),
-            );
-        }
-        app.add_systems(Startup, move || {
-            let path = path.clone();
-            let tx = tx.clone();
-            IoTaskPool::get()
-                .spawn(async move {
-                    if let Some(parent) = path.parent() {
-                        if let Err(e) = async_fs::create_dir_all(parent).await {
-                            error!("{e}");
-                            return Ok::<(), io::Error>(());
-                        }
-                    }
-                    let mut file = match OpenOptions::new()
-                        .create(true)
-                        .read(true)
-                        .write(true)
-                        .append(false)
-                        .open(&path)
-                        .await
-                    {
-                        Ok(file) => file,
-                        Err(e) => {
-                            error!("{e}");
-                            return Ok::<(), io::Error>(());
-                        }
-                    };
-
-                    let metadata = file.metadata().await?;
-                    if metadata.len() == 0 {
-                        let default = R::default();
-                        let json = serde_json::to_vec_pretty(&default)
-                            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
-                        file.write_all(&json).await?;
-                        file.flush().await?;
-                        file.seek(SeekFrom::Start(0)).await?;
-                    }
-
-                    let mut buf = String::new();
-                    if let Err(e) = file.read_to_string(&mut buf).await {
-                        error!("{e}");
-                        return Ok::<(), io::Error>(());
-                    }
-
-                    let msg: R = serde_json::from_str(&buf).unwrap_or_default();
-                    if let Err(e) = tx.send(msg).await {
-                        error!("{e}");
-                    }
-                    Ok::<(), io::Error>(())
-                })
-                .detach();
-        });
+    /// Save every `interval`.
+    pub fn every(interval: Duration) -> Self {
+        Self::from_timer(Timer::new(interval, TimerMode::Repeating))
     }
-}
 
-fn sync_file<R>(sender: Res<IoSender<R>>, res: Res<R>)
-where
-    R: for<'de> Deserialize<'de> + Clone + Serialize + Resource + Default + Send + Sync + 'static,
-{
-    if let Err(err) = sender.try_send(res.clone()) {
-        error!("{err}");
+    /// Start at `max` and, after each interval, halve it if the resource changed on several
+    /// frames, double it if it didn't change at all, staying within `min..=max`. Intervals
+    /// without changes don't save.
+    pub fn adaptive(min: Duration, max: Duration) -> Self {
+        assert!(min <= max, "adaptive autosave needs min <= max");
+        Self {
+            adaptive: Some(AdaptiveInterval { min, max }),
+            ..Self::every(max)
+        }
     }
 }
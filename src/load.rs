@@ -0,0 +1,262 @@
+use crate::{
+    codec, path::SharedPath, storage, vfs::Vfs, IoSender, IoSinkError, LoadTracker, Migrations,
+};
+use async_channel::{Receiver, Sender};
+use async_std::path::{Path, PathBuf};
+use bevy::{prelude::*, tasks::IoTaskPool};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{io::ErrorKind, marker::PhantomData, sync::Arc};
+
+/// Where the value inserted by a successful load came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LoadSource {
+    /// Decoded from the save file.
+    File,
+    /// There was no save yet, handled according to the [`MissingSavePolicy`].
+    Missing,
+}
+
+/// What to do on a fresh install, when there is no save file yet.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MissingSavePolicy {
+    /// Insert `R::default()` and write it out.
+    #[default]
+    WriteDefault,
+    /// Insert `R::default()` without creating the file.
+    UseDefault,
+    /// Leave `R` out of the world.
+    Skip,
+}
+
+/// What to do when the save file exists but can't be read or decoded, e.g. wrong
+/// permissions or a corrupted file. A [`LoadFailed<R>`] is emitted in every case.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnreadableSavePolicy {
+    /// Insert `R::default()`, the next save overwrites the unreadable file.
+    #[default]
+    UseDefault,
+    /// Copy the file to `<path>.unreadable` first, then insert `R::default()`.
+    BackupAndUseDefault,
+    /// Leave `R` out of the world so the game can ask the player what to do.
+    Skip,
+    /// Panic on the main thread.
+    Panic,
+}
+
+/// Emitted when the persisted value of `R` has been loaded, or found missing.
+#[derive(Event, Debug)]
+pub struct LoadCompleted<R> {
+    pub source: LoadSource,
+    _marker: PhantomData<fn() -> R>,
+}
+
+/// Emitted when the save file of `R` exists but could not be read or decoded, see
+/// [`UnreadableSavePolicy`] for what is inserted in its place.
+#[derive(Event, Debug)]
+pub struct LoadFailed<R> {
+    pub error: IoSinkError,
+    /// Copy of the unreadable file, with [`UnreadableSavePolicy::BackupAndUseDefault`].
+    pub backup: Option<PathBuf>,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R> LoadFailed<R> {
+    pub(crate) fn new(error: IoSinkError, backup: Option<PathBuf>) -> Self {
+        Self {
+            error,
+            backup,
+            _marker: PhantomData,
+        }
+    }
+}
+
+pub(crate) enum LoadOutcome {
+    Loaded(LoadSource),
+    Failed {
+        error: IoSinkError,
+        backup: Option<PathBuf>,
+    },
+}
+
+pub(crate) struct LoadResult<R> {
+    /// `None` when the policy says to leave the resource out.
+    pub(crate) value: Option<R>,
+    pub(crate) outcome: LoadOutcome,
+}
+
+#[derive(Resource)]
+pub(crate) struct LoadFileReceiver<R>(pub(crate) Receiver<LoadResult<R>>);
+
+/// Everything needed to (re)load the save of `R`, results arrive on [`LoadFileReceiver<R>`].
+#[derive(Resource)]
+pub(crate) struct FileLoader<R> {
+    pub(crate) path: SharedPath,
+    pub(crate) migrations: Migrations,
+    pub(crate) vfs: Arc<dyn Vfs>,
+    pub(crate) missing: MissingSavePolicy,
+    pub(crate) unreadable: UnreadableSavePolicy,
+    /// Whether inserting `R` gets it saved anyway, by the change-driven sync.
+    pub(crate) sync_on_change: bool,
+    pub(crate) tx: Sender<LoadResult<R>>,
+}
+
+impl<R> FileLoader<R>
+where
+    R: DeserializeOwned + Serialize + Default + Send + 'static,
+{
+    pub(crate) fn spawn(&self) {
+        let path = self.path.latest();
+        let (migrations, vfs) = (self.migrations.clone(), self.vfs.clone());
+        let (missing, unreadable) = (self.missing, self.unreadable);
+        let tx = self.tx.clone();
+        IoTaskPool::get()
+            .spawn(async move {
+                let loaded = load_file::<R>(&*vfs, path, &migrations, missing, unreadable).await;
+                if let Err(e) = tx.send(loaded).await {
+                    error!("{e}");
+                }
+            })
+            .detach();
+    }
+}
+
+/// Read the save at `path`, applying `missing` when there is none yet and `unreadable` when
+/// it can't be read or decoded.
+pub(crate) async fn load_file<R>(
+    vfs: &dyn Vfs,
+    path: PathBuf,
+    migrations: &Migrations,
+    missing: MissingSavePolicy,
+    unreadable: UnreadableSavePolicy,
+) -> LoadResult<R>
+where
+    R: DeserializeOwned + Default,
+{
+    let bytes = match vfs.read(&path).await {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == ErrorKind::NotFound => return load_missing(missing),
+        Err(e) => return load_unreadable(vfs, &path, e.into(), unreadable).await,
+    };
+    // The sink creates the file when it starts, possibly before this load.
+    if bytes.is_empty() {
+        return load_missing(missing);
+    }
+
+    match codec::decode(&bytes, migrations) {
+        Ok(value) => LoadResult {
+            value: Some(value),
+            outcome: LoadOutcome::Loaded(LoadSource::File),
+        },
+        Err(e) => load_unreadable(vfs, &path, e, unreadable).await,
+    }
+}
+
+/// The default of [`MissingSavePolicy::WriteDefault`] is written by [`receive_loaded`],
+/// through the sink so it's encoded like every other save.
+fn load_missing<R: Default>(policy: MissingSavePolicy) -> LoadResult<R> {
+    let value = match policy {
+        MissingSavePolicy::WriteDefault | MissingSavePolicy::UseDefault => Some(R::default()),
+        MissingSavePolicy::Skip => None,
+    };
+    LoadResult {
+        value,
+        outcome: LoadOutcome::Loaded(LoadSource::Missing),
+    }
+}
+
+pub(crate) async fn load_unreadable<R>(
+    vfs: &dyn Vfs,
+    path: &Path,
+    error: IoSinkError,
+    policy: UnreadableSavePolicy,
+) -> LoadResult<R>
+where
+    R: Default,
+{
+    error!("save {} is unreadable: {error}", path.display());
+    let mut backup = None;
+    if policy == UnreadableSavePolicy::BackupAndUseDefault {
+        let mut name = path.as_os_str().to_owned();
+        name.push(".unreadable");
+        let backup_path = PathBuf::from(name);
+        match vfs.copy(path, &backup_path).await {
+            Ok(()) => backup = Some(backup_path),
+            Err(e) => error!("could not back up {}: {e}", path.display()),
+        }
+    }
+    let value = match policy {
+        UnreadableSavePolicy::UseDefault | UnreadableSavePolicy::BackupAndUseDefault => {
+            Some(R::default())
+        }
+        UnreadableSavePolicy::Skip | UnreadableSavePolicy::Panic => None,
+    };
+    LoadResult {
+        value,
+        outcome: LoadOutcome::Failed { error, backup },
+    }
+}
+
+/// Decode the save at `path`, `None` if there is none yet.
+pub(crate) async fn read_save<R>(path: &Path) -> Result<Option<R>, IoSinkError>
+where
+    R: DeserializeOwned,
+{
+    let bytes = match storage::read(path).await {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    if bytes.is_empty() {
+        return Ok(None);
+    }
+    codec::decode(&bytes, &Migrations::default()).map(Some)
+}
+
+pub(crate) fn receive_loaded<R>(
+    mut commands: Commands,
+    receiver: Res<LoadFileReceiver<R>>,
+    loader: Res<FileLoader<R>>,
+    sender: Res<IoSender<R>>,
+    mut completed: EventWriter<LoadCompleted<R>>,
+    mut failed: EventWriter<LoadFailed<R>>,
+    mut tracker: ResMut<LoadTracker>,
+) where
+    R: Resource + Clone,
+{
+    // Leave the result queued until the resources `R` depends on are in the world.
+    if !tracker.dependencies_loaded::<R>() {
+        return;
+    }
+    let Ok(LoadResult { value, outcome }) = receiver.0.try_recv() else {
+        return;
+    };
+    if let Some(value) = value {
+        let write_default = matches!(outcome, LoadOutcome::Loaded(LoadSource::Missing))
+            && loader.missing == MissingSavePolicy::WriteDefault
+            && !loader.sync_on_change;
+        if write_default {
+            if let Err(e) = sender.enqueue(value.clone()) {
+                error!("{e}");
+            }
+        }
+        commands.insert_resource(value);
+    }
+    tracker.mark_loaded::<R>();
+    match outcome {
+        LoadOutcome::Loaded(source) => {
+            completed.write(LoadCompleted {
+                source,
+                _marker: PhantomData,
+            });
+        }
+        LoadOutcome::Failed { error, backup } => {
+            if loader.unreadable == UnreadableSavePolicy::Panic {
+                panic!(
+                    "save of {} is unreadable: {error}",
+                    std::any::type_name::<R>()
+                );
+            }
+            failed.write(LoadFailed::new(error, backup));
+        }
+    }
+}
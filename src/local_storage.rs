@@ -0,0 +1,112 @@
+use crate::{path::SharedPath, IoSinkError, IoWriter, SaveFormat, SinkResult};
+use async_std::io;
+use serde::Serialize;
+use std::marker::PhantomData;
+use web_sys::Storage;
+
+fn storage() -> io::Result<Storage> {
+    web_sys::window()
+        .ok_or_else(|| io::Error::other("no window"))?
+        .local_storage()
+        .map_err(js_error)?
+        .ok_or_else(|| io::Error::other("localStorage is unavailable"))
+}
+
+fn js_error(value: wasm_bindgen::JsValue) -> io::Error {
+    io::Error::other(format!("{value:?}"))
+}
+
+pub(crate) fn get(key: &str) -> io::Result<String> {
+    storage()?
+        .get_item(key)
+        .map_err(js_error)?
+        .ok_or_else(|| io::ErrorKind::NotFound.into())
+}
+
+pub(crate) fn set(key: &str, value: &str) -> io::Result<()> {
+    storage()?.set_item(key, value).map_err(js_error)
+}
+
+/// Removing a missing key is not an error for `localStorage`, so neither is it here.
+pub(crate) fn remove(key: &str) -> io::Result<()> {
+    storage()?.remove_item(key).map_err(js_error)
+}
+
+/// Stores each `R` under a `window.localStorage` key, the browser counterpart of
+/// [`FileSink`](crate::FileSink). Picked by [`FileSinkPlugin`](crate::FileSinkPlugin) on
+/// `wasm32` with the `wasm` feature, the key being the save path.
+pub struct LocalStorageSink<R> {
+    key: SharedPath,
+    format: SaveFormat,
+    max_message_size: Option<u64>,
+    last_contents: Option<String>,
+    last_write_len: Option<u64>,
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R> LocalStorageSink<R> {
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: SharedPath::new(key.into().into()),
+            format: SaveFormat::default(),
+            max_message_size: None,
+            last_contents: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub(crate) fn with_shared_path(mut self, key: SharedPath) -> Self {
+        self.key = key;
+        self
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Reject encoded messages larger than `bytes` with [`IoSinkError::MessageTooLarge`].
+    /// Browsers cap `localStorage` at a few megabytes per origin.
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+}
+
+impl<R> IoWriter<R> for LocalStorageSink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        storage()?;
+        self.key.open();
+        self.last_contents = None;
+        Ok(())
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let json = self.format.encode(&data)?;
+        IoSinkError::check_size(json.len(), self.max_message_size)?;
+        let json = String::from_utf8(json).map_err(IoSinkError::serialization)?;
+
+        if self.last_contents.as_ref() == Some(&json) {
+            self.last_write_len = Some(0);
+            return Ok(());
+        }
+        set(&self.key.get().to_string_lossy(), &json)?;
+        self.last_write_len = Some(json.len() as u64);
+        self.last_contents = Some(json);
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        remove(&self.key.get().to_string_lossy())?;
+        self.last_contents = None;
+        Ok(())
+    }
+}
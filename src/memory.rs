@@ -0,0 +1,137 @@
+use crate::{IoWriter, SinkResult};
+use bevy::prelude::*;
+use std::sync::{Arc, Mutex};
+
+struct Recorded<R> {
+    messages: Vec<R>,
+    inits: usize,
+    flushes: usize,
+    closed: bool,
+}
+
+/// What a [`MemorySink`] was given, shared between the sink and the test asserting on it.
+/// Insert it as a resource to reach it from systems.
+#[derive(Resource)]
+pub struct MemorySinkHandle<R> {
+    recorded: Arc<Mutex<Recorded<R>>>,
+}
+
+impl<R> Clone for MemorySinkHandle<R> {
+    fn clone(&self) -> Self {
+        Self {
+            recorded: self.recorded.clone(),
+        }
+    }
+}
+
+impl<R> Default for MemorySinkHandle<R> {
+    fn default() -> Self {
+        Self {
+            recorded: Arc::new(Mutex::new(Recorded {
+                messages: Vec::new(),
+                inits: 0,
+                flushes: 0,
+                closed: false,
+            })),
+        }
+    }
+}
+
+impl<R> MemorySinkHandle<R> {
+    /// Every message written so far, oldest first.
+    pub fn messages(&self) -> Vec<R>
+    where
+        R: Clone,
+    {
+        self.recorded.lock().unwrap().messages.clone()
+    }
+
+    pub fn last(&self) -> Option<R>
+    where
+        R: Clone,
+    {
+        self.recorded.lock().unwrap().messages.last().cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.recorded.lock().unwrap().messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Take every message written so far out of the handle.
+    pub fn take(&self) -> Vec<R> {
+        std::mem::take(&mut self.recorded.lock().unwrap().messages)
+    }
+
+    /// How many times the sink was initialized, more than once after a reopen.
+    pub fn inits(&self) -> usize {
+        self.recorded.lock().unwrap().inits
+    }
+
+    pub fn flushes(&self) -> usize {
+        self.recorded.lock().unwrap().flushes
+    }
+
+    /// Whether the sink was closed, e.g. on app exit.
+    pub fn is_closed(&self) -> bool {
+        self.recorded.lock().unwrap().closed
+    }
+}
+
+/// Keeps every message in memory instead of persisting it, so tests can assert on what the
+/// app saved without temp files or real IO.
+///
+/// ```ignore
+/// let written = MemorySinkHandle::<Settings>::default();
+/// app.add_plugins(IoSinkPlugin::<Settings, _>::new(MemorySink::new(written.clone())));
+/// // ... run the app ...
+/// assert_eq!(written.last(), Some(Settings { volume: 0.5 }));
+/// ```
+pub struct MemorySink<R> {
+    handle: MemorySinkHandle<R>,
+}
+
+impl<R> MemorySink<R> {
+    pub fn new(handle: MemorySinkHandle<R>) -> Self {
+        Self { handle }
+    }
+
+    pub fn handle(&self) -> MemorySinkHandle<R> {
+        self.handle.clone()
+    }
+}
+
+impl<R> IoWriter<R> for MemorySink<R>
+where
+    R: Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        let mut recorded = self.handle.recorded.lock().unwrap();
+        recorded.inits += 1;
+        recorded.closed = false;
+        Ok(())
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        self.handle.recorded.lock().unwrap().messages.push(data);
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        self.handle.recorded.lock().unwrap().flushes += 1;
+        Ok(())
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        self.handle.recorded.lock().unwrap().closed = true;
+        Ok(())
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        self.handle.recorded.lock().unwrap().messages.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,258 @@
+use crate::{path::SharedPath, storage, IoSinkError, IoWriter, SinkResult, WriterCapabilities};
+use async_std::path::Path;
+use bevy::prelude::*;
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    io::ErrorKind,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+/// Summary stored alongside a save by [`FileSinkPlugin::with_metadata`], for save selection
+/// screens. Read it with [`read_save_metadata`] without decoding the save itself.
+///
+/// [`FileSinkPlugin::with_metadata`]: crate::FileSinkPlugin::with_metadata
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SaveMetadata {
+    /// Wall-clock milliseconds since the unix epoch when the save was first written.
+    pub created_ms: u64,
+    /// Wall-clock milliseconds since the unix epoch of the latest write.
+    pub modified_ms: u64,
+    /// Unpaused [`Time<Virtual>`] accumulated over every session that wrote the save.
+    pub playtime_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_version: Option<String>,
+    /// The [`SaveInfo::fields`] at the latest write.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, String>,
+}
+
+impl SaveMetadata {
+    /// The metadata of an encoded save, `None` if it was written without. The payload is
+    /// skipped over, not decoded.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Option<Self>, IoSinkError> {
+        #[derive(Deserialize)]
+        struct Head {
+            metadata: Option<SaveMetadata>,
+            payload: Option<IgnoredAny>,
+        }
+
+        match serde_json::from_slice::<Head>(bytes) {
+            Ok(Head {
+                metadata: Some(metadata),
+                payload: Some(_),
+            }) => Ok(Some(metadata)),
+            Ok(_) => Ok(None),
+            // Not an object, so not written with metadata.
+            Err(e) if e.is_data() => Ok(None),
+            Err(e) => Err(IoSinkError::deserialization(e)),
+        }
+    }
+}
+
+/// Read the [`SaveMetadata`] of the save at `path`, `None` if there is no save or it was
+/// written without metadata.
+pub async fn read_save_metadata(path: &Path) -> Result<Option<SaveMetadata>, IoSinkError> {
+    match storage::read(path).await {
+        Ok(bytes) => SaveMetadata::from_bytes(&bytes),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// A record and its [`SaveMetadata`], as written by a [`MetadataSink`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetadataRecord<T> {
+    pub metadata: SaveMetadata,
+    pub payload: T,
+}
+
+/// What the game wants stored in the [`SaveMetadata`] of every save, e.g. the current level
+/// for the load menu.
+///
+/// ```ignore
+/// app.insert_resource(SaveInfo::new(env!("CARGO_PKG_VERSION")));
+///
+/// fn track_level(level: Res<Level>, mut info: ResMut<SaveInfo>) {
+///     info.set("level", level.name.clone());
+/// }
+/// ```
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SaveInfo {
+    pub game_version: Option<String>,
+    pub fields: BTreeMap<String, String>,
+}
+
+impl SaveInfo {
+    pub fn new(game_version: impl Into<String>) -> Self {
+        Self {
+            game_version: Some(game_version.into()),
+            fields: BTreeMap::new(),
+        }
+    }
+
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.fields.insert(key.into(), value.into());
+    }
+}
+
+/// The app's [`SaveInfo`] and playtime, mirrored for sink tasks.
+#[derive(Resource, Clone, Default)]
+pub(crate) struct SaveInfoMirror {
+    info: Arc<RwLock<SaveInfo>>,
+    playtime_ms: Arc<AtomicU64>,
+}
+
+pub(crate) struct MetadataPlugin;
+
+impl Plugin for MetadataPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SaveInfoMirror>();
+        app.init_resource::<SaveInfo>();
+        app.add_systems(First, mirror_save_info);
+    }
+}
+
+fn mirror_save_info(
+    info: Res<SaveInfo>,
+    virt: Option<Res<Time<Virtual>>>,
+    mirror: Res<SaveInfoMirror>,
+) {
+    if info.is_changed() {
+        *mirror.info.write().unwrap_or_else(|e| e.into_inner()) = info.clone();
+    }
+    if let Some(virt) = virt {
+        mirror
+            .playtime_ms
+            .store(virt.elapsed().as_millis() as u64, Ordering::Relaxed);
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// Wraps every record in a [`MetadataRecord`] before handing it to the inner writer, carrying
+/// the creation time and playtime of the existing save over.
+pub struct MetadataSink<W> {
+    inner: W,
+    path: SharedPath,
+    mirror: SaveInfoMirror,
+    created_ms: Option<u64>,
+    /// Playtime stored in the save when the sink opened it.
+    playtime_base_ms: u64,
+    /// Session playtime when the sink opened the save.
+    session_start_ms: u64,
+    /// The last payload and the metadata written with it.
+    last: Option<(Vec<u8>, SaveMetadata)>,
+}
+
+impl<W> MetadataSink<W> {
+    pub(crate) fn new(inner: W, path: &SharedPath, mirror: &SaveInfoMirror) -> Self {
+        Self {
+            inner,
+            path: path.clone(),
+            mirror: mirror.clone(),
+            created_ms: None,
+            playtime_base_ms: 0,
+            session_start_ms: 0,
+            last: None,
+        }
+    }
+
+    fn session_playtime_ms(&self) -> u64 {
+        self.mirror.playtime_ms.load(Ordering::Relaxed)
+    }
+}
+
+impl<R, W> IoWriter<R> for MetadataSink<W>
+where
+    R: Serialize + Send + Sync + 'static,
+    W: IoWriter<MetadataRecord<R>>,
+{
+    async fn init(&mut self) -> SinkResult {
+        let path = self.path.open();
+        let previous = read_save_metadata(&path).await.unwrap_or_else(|e| {
+            warn!("ignoring the metadata of {}: {e}", path.display());
+            None
+        });
+        self.last = None;
+        self.created_ms = previous.as_ref().map(|previous| previous.created_ms);
+        self.playtime_base_ms = previous.map_or(0, |previous| previous.playtime_ms);
+        self.session_start_ms = self.session_playtime_ms();
+        self.inner.init().await
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let payload = serde_json::to_vec(&data).map_err(IoSinkError::serialization)?;
+        let info = self
+            .mirror
+            .info
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        // Keep the metadata of an unchanged save so the inner writer can skip it.
+        let metadata = match &self.last {
+            Some((last, metadata))
+                if *last == payload
+                    && metadata.game_version == info.game_version
+                    && metadata.fields == info.fields =>
+            {
+                metadata.clone()
+            }
+            _ => {
+                let modified_ms = now_ms();
+                SaveMetadata {
+                    created_ms: *self.created_ms.get_or_insert(modified_ms),
+                    modified_ms,
+                    playtime_ms: self.playtime_base_ms + self.session_playtime_ms()
+                        - self.session_start_ms,
+                    game_version: info.game_version,
+                    fields: info.fields,
+                }
+            }
+        };
+        self.last = Some((payload, metadata.clone()));
+        self.inner
+            .write(MetadataRecord {
+                metadata,
+                payload: data,
+            })
+            .await
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        self.inner.flush().await
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        self.inner.close().await
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.inner.last_write_len()
+    }
+
+    fn last_write_changed(&self) -> Option<u64> {
+        self.inner.last_write_changed()
+    }
+
+    fn capabilities(&self) -> WriterCapabilities {
+        self.inner.capabilities()
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        // The next save starts a new playthrough.
+        self.last = None;
+        self.created_ms = None;
+        self.playtime_base_ms = 0;
+        self.session_start_ms = self.session_playtime_ms();
+        self.inner.wipe().await
+    }
+}
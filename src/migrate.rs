@@ -0,0 +1,126 @@
+use crate::IoSinkError;
+use async_std::path::{Path, PathBuf};
+use bevy::{prelude::*, tasks::block_on};
+use futures_lite::StreamExt;
+
+/// Written to the new directory once a migration succeeded, holding the old directory.
+const MIGRATION_MARKER: &str = ".migrated";
+
+/// Emitted at startup when a [`SaveDirMigrationPlugin`] moved saves to the new directory.
+#[derive(Event, Debug, Clone)]
+pub struct SaveDirMigrated {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub files: usize,
+}
+
+/// Copy every file under `from` to the same place under `to`, compare each copy with its
+/// original and record the migration in `to`. Files already in `to` are kept. Returns the
+/// number of files copied, `None` if there was nothing to migrate or it already happened.
+pub async fn migrate_save_dir(
+    from: &Path,
+    to: &Path,
+    remove_old: bool,
+) -> Result<Option<usize>, IoSinkError> {
+    let marker = to.join(MIGRATION_MARKER);
+    if marker.exists().await || !from.is_dir().await {
+        return Ok(None);
+    }
+
+    let mut files = 0;
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(relative) = dirs.pop() {
+        async_fs::create_dir_all(to.join(&relative)).await?;
+        let mut entries = async_fs::read_dir(from.join(&relative)).await?;
+        while let Some(entry) = entries.next().await {
+            let entry = entry?;
+            let relative = relative.join(entry.file_name());
+            if entry.file_type().await?.is_dir() {
+                dirs.push(relative);
+                continue;
+            }
+            let source = from.join(&relative);
+            let target = to.join(&relative);
+            if target.exists().await {
+                continue;
+            }
+            async_fs::copy(&source, &target).await?;
+            if async_fs::read(&source).await? != async_fs::read(&target).await? {
+                return Err(IoSinkError::Other(format!(
+                    "{} differs from {} after copying",
+                    target.display(),
+                    source.display()
+                )));
+            }
+            files += 1;
+        }
+    }
+
+    async_fs::write(&marker, from.to_string_lossy().as_bytes()).await?;
+    if remove_old {
+        async_fs::remove_dir_all(from).await?;
+    }
+    Ok(Some(files))
+}
+
+/// Moves saves from the directory used by earlier versions of the game, e.g. before a studio
+/// rename, before anything is loaded. Runs once, the migration is recorded in the new
+/// directory.
+///
+/// ```ignore
+/// app.add_plugins(SaveDirMigrationPlugin::new("saves/old_studio", "saves/new_studio"));
+/// ```
+pub struct SaveDirMigrationPlugin {
+    from: PathBuf,
+    to: PathBuf,
+    remove_old: bool,
+}
+
+impl SaveDirMigrationPlugin {
+    pub fn new(from: impl Into<PathBuf>, to: impl Into<PathBuf>) -> Self {
+        Self {
+            from: from.into(),
+            to: to.into(),
+            remove_old: false,
+        }
+    }
+
+    /// Delete the old directory once every file has been copied and verified.
+    pub fn with_remove_old(mut self) -> Self {
+        self.remove_old = true;
+        self
+    }
+}
+
+impl Plugin for SaveDirMigrationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveDirMigrated>();
+        let (from, to, remove_old) = (self.from.clone(), self.to.clone(), self.remove_old);
+        // Blocking, loads spawned at `Startup` must see the migrated files.
+        app.add_systems(
+            PreStartup,
+            move |mut migrated: EventWriter<SaveDirMigrated>| match block_on(migrate_save_dir(
+                &from, &to, remove_old,
+            )) {
+                Ok(Some(files)) => {
+                    info!(
+                        "migrated {files} saves from {} to {}",
+                        from.display(),
+                        to.display()
+                    );
+                    migrated.write(SaveDirMigrated {
+                        from: from.clone(),
+                        to: to.clone(),
+                        files,
+                    });
+                }
+                Ok(None) => {}
+                Err(e) => error!(
+                    "failed to migrate saves from {} to {}: {e}",
+                    from.display(),
+                    to.display()
+                ),
+            },
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use crate::{IoSinkError, IoWriter, SaveFormat, SinkResult};
+use bevy::prelude::*;
+use rumqttc::{Client, ConnectionError};
+pub use rumqttc::{MqttOptions, QoS};
+use serde::Serialize;
+use std::{marker::PhantomData, thread, time::Duration};
+
+/// Publishes every message to an MQTT broker, so dashboards and services can subscribe to
+/// game state. The topic is the type path of `R` with `::` replaced by `/`, e.g.
+/// `my_game/stats/PlayerStats`, unless set with [`with_topic`](Self::with_topic).
+///
+/// The connection runs on its own thread and reconnects by itself, publishing only fails
+/// when the outgoing queue is full.
+///
+/// ```ignore
+/// let options = MqttOptions::new("my_game", "localhost", 1883);
+/// app.add_plugins(IoSinkPlugin::<PlayerStats, _>::new(
+///     MqttSink::new(options).with_qos(QoS::AtLeastOnce).with_retain(true),
+/// ));
+/// ```
+pub struct MqttSink<R> {
+    options: MqttOptions,
+    topic: String,
+    qos: QoS,
+    retain: bool,
+    /// Publishes buffered while the broker is slow or unreachable.
+    capacity: usize,
+    format: SaveFormat,
+    max_message_size: Option<u64>,
+    client: Option<Client>,
+    last_write_len: Option<u64>,
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R> MqttSink<R> {
+    pub fn new(options: MqttOptions) -> Self {
+        Self {
+            options,
+            topic: std::any::type_name::<R>().replace("::", "/"),
+            qos: QoS::AtMostOnce,
+            retain: false,
+            capacity: 64,
+            format: SaveFormat::default(),
+            max_message_size: None,
+            client: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn with_topic(mut self, topic: impl Into<String>) -> Self {
+        self.topic = topic.into();
+        self
+    }
+
+    /// Delivery guarantee of every publish, [`QoS::AtMostOnce`] by default.
+    pub fn with_qos(mut self, qos: QoS) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    /// Have the broker keep the latest message for subscribers that connect later, e.g. for
+    /// state rather than events.
+    pub fn with_retain(mut self, retain: bool) -> Self {
+        self.retain = retain;
+        self
+    }
+
+    /// Publishes queued while the broker is slow or unreachable, 64 by default.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Reject encoded messages larger than `bytes` with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+}
+
+impl<R> IoWriter<R> for MqttSink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        let (client, mut connection) = Client::new(self.options.clone(), self.capacity);
+        let broker = self.options.broker_address();
+        thread::Builder::new()
+            .name("mqtt sink".into())
+            .spawn(move || {
+                for notification in connection.iter() {
+                    match notification {
+                        Ok(_) => {}
+                        // Every client was dropped, the sink is gone.
+                        Err(ConnectionError::RequestsDone) => break,
+                        Err(e) => {
+                            warn!("MQTT broker {}:{} unreachable: {e}", broker.0, broker.1);
+                            thread::sleep(Duration::from_secs(1));
+                        }
+                    }
+                }
+            })?;
+        self.client = Some(client);
+        Ok(())
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let payload = self.format.encode(&data)?;
+        IoSinkError::check_size(payload.len(), self.max_message_size)?;
+        let client = self.client.as_ref().ok_or(IoSinkError::NotInitialized)?;
+        let len = payload.len() as u64;
+        client
+            .try_publish(self.topic.as_str(), self.qos, self.retain, payload)
+            .map_err(|e| IoSinkError::Other(format!("publishing to {}: {e}", self.topic)))?;
+        self.last_write_len = Some(len);
+        Ok(())
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        if let Some(client) = self.client.take() {
+            // Fails if the connection thread is already gone, which is what closing wants.
+            let _ = client.disconnect();
+        }
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+}
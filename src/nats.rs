@@ -0,0 +1,171 @@
+use crate::{IoSinkError, IoWriter, SaveFormat, SinkResult};
+use async_std::task::spawn_blocking;
+pub use nats::jetstream::StorageType;
+use nats::{
+    jetstream::{JetStream, StreamConfig},
+    Connection,
+};
+use serde::Serialize;
+use std::{marker::PhantomData, time::Duration};
+
+/// Where a [`NatsSink`] with [`with_jetstream`](NatsSink::with_jetstream) keeps its messages.
+#[derive(Debug, Clone)]
+pub struct JetStreamOptions {
+    /// Name of the stream, created for the sink's subject if it doesn't exist.
+    pub stream: String,
+    pub storage: StorageType,
+    /// Discard messages older than this, kept forever if `None`.
+    pub max_age: Option<Duration>,
+    /// Discard the oldest messages beyond this count, unlimited if `None`.
+    pub max_messages: Option<i64>,
+}
+
+impl JetStreamOptions {
+    /// A file-backed stream keeping every message.
+    pub fn new(stream: impl Into<String>) -> Self {
+        Self {
+            stream: stream.into(),
+            storage: StorageType::File,
+            max_age: None,
+            max_messages: None,
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Publisher {
+    Core(Connection),
+    JetStream(Connection, JetStream),
+}
+
+/// Publishes every message to a NATS subject, the type path of `R` with `::` replaced by
+/// `.` unless set with [`with_subject`](Self::with_subject). Core NATS is fire-and-forget,
+/// with [`with_jetstream`](Self::with_jetstream) a write completes once the server stored
+/// the message.
+///
+/// ```ignore
+/// let sink = NatsSink::new("nats://localhost:4222")
+///     .with_subject("lobby.state")
+///     .with_jetstream(JetStreamOptions::new("LOBBY"));
+/// app.add_plugins(IoSinkPlugin::<LobbyState, _>::new(sink));
+/// ```
+pub struct NatsSink<R> {
+    url: String,
+    subject: String,
+    jetstream: Option<JetStreamOptions>,
+    format: SaveFormat,
+    max_message_size: Option<u64>,
+    publisher: Option<Publisher>,
+    last_write_len: Option<u64>,
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R> NatsSink<R> {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            subject: std::any::type_name::<R>().replace("::", "."),
+            jetstream: None,
+            format: SaveFormat::default(),
+            max_message_size: None,
+            publisher: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn with_subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = subject.into();
+        self
+    }
+
+    /// Publish through JetStream so messages are persisted and acknowledged.
+    pub fn with_jetstream(mut self, options: JetStreamOptions) -> Self {
+        self.jetstream = Some(options);
+        self
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Reject encoded messages larger than `bytes` with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+}
+
+fn connect(url: &str, subject: &str, jetstream: Option<JetStreamOptions>) -> SinkResult<Publisher> {
+    let connection = nats::connect(url)?;
+    let Some(options) = jetstream else {
+        return Ok(Publisher::Core(connection));
+    };
+    let js = nats::jetstream::new(connection.clone());
+    if js.stream_info(&options.stream).is_err() {
+        js.add_stream(StreamConfig {
+            name: options.stream,
+            subjects: vec![subject.to_owned()],
+            storage: options.storage,
+            max_age: options.max_age.unwrap_or_default(),
+            max_msgs: options.max_messages.unwrap_or(-1),
+            ..Default::default()
+        })?;
+    }
+    Ok(Publisher::JetStream(connection, js))
+}
+
+impl<R> IoWriter<R> for NatsSink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        let (url, subject, jetstream) = (
+            self.url.clone(),
+            self.subject.clone(),
+            self.jetstream.clone(),
+        );
+        // The client blocks, keep it off the sink task's executor thread.
+        let publisher = spawn_blocking(move || connect(&url, &subject, jetstream)).await?;
+        self.publisher = Some(publisher);
+        Ok(())
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let payload = self.format.encode(&data)?;
+        IoSinkError::check_size(payload.len(), self.max_message_size)?;
+        let publisher = self.publisher.clone().ok_or(IoSinkError::NotInitialized)?;
+        let subject = self.subject.clone();
+        let len = payload.len() as u64;
+        spawn_blocking(move || match publisher {
+            Publisher::Core(connection) => connection.publish(&subject, payload),
+            Publisher::JetStream(_, js) => js.publish(&subject, payload).map(|_| ()),
+        })
+        .await?;
+        self.last_write_len = Some(len);
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        if let Some(Publisher::Core(connection) | Publisher::JetStream(connection, _)) =
+            self.publisher.clone()
+        {
+            spawn_blocking(move || connection.flush()).await?;
+        }
+        Ok(())
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        if let Some(Publisher::Core(connection) | Publisher::JetStream(connection, _)) =
+            self.publisher.take()
+        {
+            spawn_blocking(move || connection.close()).await;
+        }
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{
+    path::SharedPath,
+    web::{js_error, run_local},
+    IoSinkError, IoWriter, SaveFormat, SinkResult, WriterCapabilities,
+};
+use async_std::{
+    io,
+    path::{Path, PathBuf},
+};
+use js_sys::{Promise, Uint8Array};
+use serde::Serialize;
+use std::marker::PhantomData;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    DomException, File, FileSystemDirectoryHandle, FileSystemFileHandle,
+    FileSystemGetDirectoryOptions, FileSystemGetFileOptions, FileSystemWritableFileStream,
+};
+
+async fn resolve<T: JsCast>(promise: Promise) -> Result<T, IoSinkError> {
+    JsFuture::from(promise)
+        .await
+        .map(JsCast::unchecked_into)
+        .map_err(|value| {
+            let not_found = value
+                .dyn_ref::<DomException>()
+                .is_some_and(|e| e.name() == "NotFoundError");
+            if not_found {
+                io::Error::from(io::ErrorKind::NotFound).into()
+            } else {
+                js_error(value)
+            }
+        })
+}
+
+/// The handle of the directory holding `path` in the origin private file system and the
+/// file name, directories are path segments.
+async fn parent_handle(
+    path: &str,
+    create: bool,
+) -> Result<(FileSystemDirectoryHandle, &str), IoSinkError> {
+    let mut segments: Vec<_> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect();
+    let name = segments
+        .pop()
+        .ok_or_else(|| IoSinkError::Other(format!("`{path}` has no file name")))?;
+
+    let storage = web_sys::window()
+        .ok_or_else(|| IoSinkError::Other("no window".into()))?
+        .navigator()
+        .storage();
+    let mut dir: FileSystemDirectoryHandle = resolve(storage.get_directory()).await?;
+    let dir_options = FileSystemGetDirectoryOptions::new();
+    dir_options.set_create(create);
+    for segment in segments {
+        dir = resolve(dir.get_directory_handle_with_options(segment, &dir_options)).await?;
+    }
+    Ok((dir, name))
+}
+
+/// The handle of `path` in the origin private file system.
+async fn file_handle(path: &str, create: bool) -> Result<FileSystemFileHandle, IoSinkError> {
+    let (dir, name) = parent_handle(path, create).await?;
+    let file_options = FileSystemGetFileOptions::new();
+    file_options.set_create(create);
+    resolve(dir.get_file_handle_with_options(name, &file_options)).await
+}
+
+async fn read_file(path: String) -> Result<Vec<u8>, IoSinkError> {
+    let file: File = resolve(file_handle(&path, false).await?.get_file()).await?;
+    let buffer = JsFuture::from(file.array_buffer())
+        .await
+        .map_err(js_error)?;
+    Ok(Uint8Array::new(&buffer).to_vec())
+}
+
+async fn write_file(path: String, text: String) -> SinkResult {
+    let handle = file_handle(&path, true).await?;
+    let stream: FileSystemWritableFileStream = resolve(handle.create_writable()).await?;
+    // Nothing is visible until `close`, a failed write leaves the previous save intact.
+    resolve::<JsValue>(stream.write_with_str(&text).map_err(js_error)?).await?;
+    resolve::<JsValue>(stream.close()).await?;
+    Ok(())
+}
+
+async fn remove_file(path: String) -> SinkResult {
+    let (dir, name) = parent_handle(&path, false).await?;
+    resolve::<JsValue>(dir.remove_entry(name)).await?;
+    Ok(())
+}
+
+/// Read a file written by an [`OpfsSink`], failing with [`io::ErrorKind::NotFound`] if
+/// there is none.
+pub(crate) async fn read(path: &Path) -> io::Result<Vec<u8>> {
+    let path = path.to_string_lossy().into_owned();
+    run_local(move || read_file(path))
+        .await
+        .map_err(|e| match e {
+            IoSinkError::Io(e) => e,
+            e => io::Error::other(e),
+        })
+}
+
+pub(crate) async fn write(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let path = path.to_string_lossy().into_owned();
+    let text = String::from_utf8(bytes.to_vec()).map_err(io::Error::other)?;
+    run_local(move || write_file(path, text))
+        .await
+        .map_err(|e| match e {
+            IoSinkError::Io(e) => e,
+            e => io::Error::other(e),
+        })
+}
+
+/// Delete a file written by an [`OpfsSink`], failing with [`io::ErrorKind::NotFound`] if
+/// there is none.
+pub(crate) async fn remove(path: &Path) -> io::Result<()> {
+    let path = path.to_string_lossy().into_owned();
+    run_local(move || remove_file(path))
+        .await
+        .map_err(|e| match e {
+            IoSinkError::Io(e) => e,
+            e => io::Error::other(e),
+        })
+}
+
+/// Overwrites a single file in the browser's origin private file system, the browser
+/// counterpart of [`FileSink`](crate::FileSink). Picked by
+/// [`FileSinkPlugin`](crate::FileSinkPlugin) on `wasm32` with the `opfs` feature, so the same
+/// save path works natively and in browsers.
+pub struct OpfsSink<R> {
+    path: SharedPath,
+    format: SaveFormat,
+    max_message_size: Option<u64>,
+    last_contents: Option<String>,
+    last_write_len: Option<u64>,
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R> OpfsSink<R> {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: SharedPath::new(path.into()),
+            format: SaveFormat::default(),
+            max_message_size: None,
+            last_contents: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub(crate) fn with_shared_path(mut self, path: SharedPath) -> Self {
+        self.path = path;
+        self
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Reject encoded messages larger than `bytes` with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+}
+
+impl<R> IoWriter<R> for OpfsSink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        let path = self.path.open().to_string_lossy().into_owned();
+        self.last_contents = None;
+        run_local(move || async move { file_handle(&path, true).await.map(|_| ()) }).await
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let json = self.format.encode(&data)?;
+        IoSinkError::check_size(json.len(), self.max_message_size)?;
+        let json = String::from_utf8(json).map_err(IoSinkError::serialization)?;
+
+        // Change detection fires on any `ResMut` deref, so identical payloads are common.
+        if self.last_contents.as_ref() == Some(&json) {
+            self.last_write_len = Some(0);
+            return Ok(());
+        }
+        let (path, text) = (self.path.get().to_string_lossy().into_owned(), json.clone());
+        run_local(move || write_file(path, text)).await?;
+        self.last_write_len = Some(json.len() as u64);
+        self.last_contents = Some(json);
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+
+    fn capabilities(&self) -> WriterCapabilities {
+        WriterCapabilities {
+            atomic_rename: true,
+            ..Default::default()
+        }
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        match remove(&self.path.get()).await {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        self.last_contents = None;
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{IoSinkError, IoSinks};
+use async_std::path::{Path, PathBuf};
+use bevy::prelude::*;
+use directories::ProjectDirs;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+/// The platform's directory for a game's data, so saves don't depend on the working
+/// directory the game was launched from.
+///
+/// ```ignore
+/// app.add_plugins(FileSinkPlugin::<Settings>::new(
+///     SavePath::project("studio", "game").join("settings.json"),
+/// ));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SavePath(PathBuf);
+
+impl SavePath {
+    /// `$XDG_DATA_HOME/<game>` on Linux, `%APPDATA%\<studio>\<game>\data` on Windows and
+    /// `~/Library/Application Support/<studio>.<game>` on macOS. Falls back to the directory
+    /// of the executable when there is no home directory, e.g. in browsers.
+    pub fn project(studio: &str, game: &str) -> Self {
+        let dir = match ProjectDirs::from("", studio, game) {
+            Some(dirs) => dirs.data_dir().to_owned(),
+            None => std::env::current_exe()
+                .ok()
+                .and_then(|exe| Some(exe.parent()?.to_owned()))
+                .unwrap_or_default(),
+        };
+        Self(dir.into())
+    }
+
+    /// Use `dir` as is.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self(dir.into())
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.0
+    }
+
+    /// A file or directory inside, missing directories are created when a sink starts.
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.0.join(path)
+    }
+}
+
+impl From<SavePath> for PathBuf {
+    fn from(path: SavePath) -> Self {
+        path.0
+    }
+}
+
+/// Values of the `{name}` placeholders in templated save paths such as
+/// `"{data_dir}/{profile}/{slot}/save.json"`, resolved before the saves are loaded.
+///
+/// ```ignore
+/// app.insert_resource(
+///     PathVariables::new()
+///         .with_data_dir(&SavePath::project("studio", "game"))
+///         .with("profile", "alice")
+///         .with("slot", "1"),
+/// )
+/// .add_plugins(FileSinkPlugin::<World>::new("{data_dir}/{profile}/{slot}/world.json"));
+/// ```
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathVariables(HashMap<String, String>);
+
+impl PathVariables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    /// Set `{data_dir}`.
+    pub fn with_data_dir(self, dir: &SavePath) -> Self {
+        self.with("data_dir", dir.dir().to_string_lossy())
+    }
+
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.0.insert(name.into(), value.into());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+
+    /// Replace every `{name}` in `template`, failing on unknown or unclosed placeholders.
+    pub fn resolve(&self, template: &Path) -> Result<PathBuf, IoSinkError> {
+        let template = template.to_string_lossy();
+        let mut resolved = String::with_capacity(template.len());
+        let mut rest = template.as_ref();
+        while let Some(start) = rest.find('{') {
+            resolved.push_str(&rest[..start]);
+            let Some(len) = rest[start..].find('}') else {
+                return Err(IoSinkError::Other(format!(
+                    "unclosed placeholder in save path `{template}`"
+                )));
+            };
+            let name = &rest[start + 1..start + len];
+            let Some(value) = self.get(name) else {
+                return Err(IoSinkError::Other(format!(
+                    "no value for `{{{name}}}` in save path `{template}`"
+                )));
+            };
+            resolved.push_str(value);
+            rest = &rest[start + len + 1..];
+        }
+        resolved.push_str(rest);
+        Ok(resolved.into())
+    }
+}
+
+pub(crate) fn is_template(path: &Path) -> bool {
+    path.to_string_lossy().contains('{')
+}
+
+#[derive(Debug, Default)]
+struct PathState {
+    active: PathBuf,
+    /// Switched to the next time the writer is opened.
+    requested: Option<PathBuf>,
+}
+
+/// A save path shared between a sink and its loader, so it can be resolved after both were
+/// created and switched while the app runs.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SharedPath(Arc<RwLock<PathState>>);
+
+impl SharedPath {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self(Arc::new(RwLock::new(PathState {
+            active: path,
+            requested: None,
+        })))
+    }
+
+    /// The path the writer has open.
+    pub(crate) fn get(&self) -> PathBuf {
+        self.0
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .active
+            .clone()
+    }
+
+    /// The path loads read from, ahead of the writer during a switch.
+    pub(crate) fn latest(&self) -> PathBuf {
+        let state = self.0.read().unwrap_or_else(|e| e.into_inner());
+        state.requested.as_ref().unwrap_or(&state.active).clone()
+    }
+
+    pub(crate) fn set(&self, path: PathBuf) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = PathState {
+            active: path,
+            requested: None,
+        };
+    }
+
+    /// Switch to `path` once the writer is reopened.
+    pub(crate) fn request(&self, path: PathBuf) {
+        self.0.write().unwrap_or_else(|e| e.into_inner()).requested = Some(path);
+    }
+
+    /// Apply a requested switch, called by writers when they are opened.
+    pub(crate) fn open(&self) -> PathBuf {
+        let mut state = self.0.write().unwrap_or_else(|e| e.into_inner());
+        if let Some(requested) = state.requested.take() {
+            state.active = requested;
+        }
+        state.active.clone()
+    }
+}
+
+/// Resolve `template` into `path` at `PreStartup`, before the load and the sink start.
+pub(crate) fn resolve_at_startup<R: 'static>(app: &mut App, template: PathBuf, path: SharedPath) {
+    app.add_systems(
+        PreStartup,
+        move |variables: Option<Res<PathVariables>>, mut sinks: ResMut<IoSinks>| {
+            let resolved = variables
+                .as_deref()
+                .cloned()
+                .unwrap_or_default()
+                .resolve(&template)
+                .unwrap_or_else(|e| panic!("{e}, insert PathVariables before Startup"));
+            sinks.set_path::<R>(resolved.clone());
+            path.set(resolved);
+        },
+    );
+}
@@ -0,0 +1,163 @@
+use crate::{AutoSave, FileSinkPlugin, Migrations, SaveFormat};
+use async_std::path::{Path, PathBuf};
+use bevy::{app::PluginGroupBuilder, prelude::*};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// How [`AppPersistExt::persist_resource_with`] configures the [`FileSinkPlugin`].
+#[derive(Debug, Clone)]
+pub struct PersistOptions {
+    /// Save whenever the resource changes, see [`FileSinkPlugin::with_sync_on_change`].
+    pub sync_on_change: bool,
+    /// Cap on change-driven syncs per second, see
+    /// [`FileSinkPlugin::with_max_writes_per_second`]. Autosaves aren't limited.
+    pub max_writes_per_second: Option<f32>,
+    /// Version the save and migrate older ones on load, see [`FileSinkPlugin::with_migrations`].
+    pub migrations: Option<Migrations>,
+    /// Periodic saves on top of the change-driven ones, see [`FileSinkPlugin::with_autosave`].
+    pub autosave: Option<AutoSave>,
+    pub format: SaveFormat,
+    /// Saves of previous sessions to keep, see [`FileSinkPlugin::with_backups`].
+    pub backups: usize,
+}
+
+impl PersistOptions {
+    /// The [`FileSinkPlugin`] these options describe.
+    pub fn plugin<R>(&self, path: impl Into<PathBuf>) -> FileSinkPlugin<R> {
+        let mut plugin = FileSinkPlugin::<R>::new(path)
+            .with_sync_on_change(self.sync_on_change)
+            .with_format(self.format)
+            .with_backups(self.backups);
+        if let Some(max) = self.max_writes_per_second {
+            plugin = plugin.with_max_writes_per_second(max);
+        }
+        if let Some(autosave) = &self.autosave {
+            plugin = plugin.with_autosave(autosave.clone());
+        }
+        if let Some(migrations) = &self.migrations {
+            plugin = plugin.with_migrations(migrations.clone());
+        }
+        plugin
+    }
+}
+
+impl Default for PersistOptions {
+    fn default() -> Self {
+        Self {
+            sync_on_change: true,
+            max_writes_per_second: None,
+            migrations: None,
+            autosave: None,
+            format: SaveFormat::default(),
+            backups: 0,
+        }
+    }
+}
+
+/// A resource that knows how to build its own [`FileSinkPlugin`], usually implemented with
+/// `#[derive(Persist)]` under the `derive` feature.
+pub trait Persist: Resource + Clone + Default + Serialize + DeserializeOwned {
+    /// Load from `path` at startup and save back whenever the resource changes.
+    fn persist(path: impl Into<PathBuf>) -> FileSinkPlugin<Self> {
+        FileSinkPlugin::new(path).with_sync_on_change(true)
+    }
+}
+
+/// One-line persistence: `app.persist_resource::<Settings>("settings.json")`.
+pub trait AppPersistExt {
+    /// Load `R` from `path` at startup and save it back whenever it changes.
+    fn persist_resource<R>(&mut self, path: impl Into<PathBuf>) -> &mut Self
+    where
+        R: DeserializeOwned + Clone + Serialize + Resource + Default,
+    {
+        self.persist_resource_with::<R>(path, PersistOptions::default())
+    }
+
+    fn persist_resource_with<R>(
+        &mut self,
+        path: impl Into<PathBuf>,
+        options: PersistOptions,
+    ) -> &mut Self
+    where
+        R: DeserializeOwned + Clone + Serialize + Resource + Default;
+}
+
+impl AppPersistExt for App {
+    fn persist_resource_with<R>(
+        &mut self,
+        path: impl Into<PathBuf>,
+        options: PersistOptions,
+    ) -> &mut Self
+    where
+        R: DeserializeOwned + Clone + Serialize + Resource + Default,
+    {
+        self.add_plugins(options.plugin::<R>(path))
+    }
+}
+
+/// Registers many resources at once under a shared directory and [`PersistOptions`]:
+///
+/// ```ignore
+/// app.add_plugins(
+///     PersistPlugins::new("saves")
+///         .add::<Settings>("settings.json")
+///         .add::<Player>("player.json"),
+/// );
+/// ```
+pub struct PersistPlugins {
+    dir: PathBuf,
+    options: PersistOptions,
+    resources: Vec<AddResource>,
+}
+
+type AddResource =
+    Box<dyn FnOnce(PluginGroupBuilder, &Path, &PersistOptions) -> PluginGroupBuilder>;
+
+impl PersistPlugins {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            options: PersistOptions::default(),
+            resources: Vec::new(),
+        }
+    }
+
+    /// Options shared by every resource added without its own.
+    pub fn with_options(mut self, options: PersistOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Persist `R` to `file` inside the directory.
+    pub fn add<R>(mut self, file: impl Into<PathBuf>) -> Self
+    where
+        R: DeserializeOwned + Clone + Serialize + Resource + Default,
+    {
+        let file = file.into();
+        self.resources.push(Box::new(move |group, dir, options| {
+            group.add(options.plugin::<R>(dir.join(file)))
+        }));
+        self
+    }
+
+    /// Persist `R` to `file` inside the directory, ignoring the shared options.
+    pub fn add_with<R>(mut self, file: impl Into<PathBuf>, options: PersistOptions) -> Self
+    where
+        R: DeserializeOwned + Clone + Serialize + Resource + Default,
+    {
+        let file = file.into();
+        self.resources.push(Box::new(move |group, dir, _| {
+            group.add(options.plugin::<R>(dir.join(file)))
+        }));
+        self
+    }
+}
+
+impl PluginGroup for PersistPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        let mut group = PluginGroupBuilder::start::<Self>();
+        for add in self.resources {
+            group = add(group, &self.dir, &self.options);
+        }
+        group
+    }
+}
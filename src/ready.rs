@@ -0,0 +1,265 @@
+use bevy::prelude::*;
+use std::{
+    any::{type_name, TypeId},
+    collections::HashMap,
+};
+
+/// Which persisted resources and journals have finished their startup load.
+#[derive(Resource, Debug, Default)]
+pub struct LoadTracker {
+    /// `true` once loaded.
+    resources: HashMap<TypeId, (&'static str, bool)>,
+    /// Resources that must be inserted before the key is.
+    dependencies: HashMap<TypeId, Vec<TypeId>>,
+}
+
+impl LoadTracker {
+    pub(crate) fn register<R: 'static>(&mut self) {
+        self.resources
+            .insert(TypeId::of::<R>(), (type_name::<R>(), false));
+    }
+
+    /// Panics if `dependency` already waits on `R`, neither would ever load.
+    pub(crate) fn add_dependency<R: 'static>(&mut self, dependency: TypeId) {
+        let id = TypeId::of::<R>();
+        if self.waits_on(dependency, id) {
+            let name = self
+                .resources
+                .get(&dependency)
+                .map_or("a resource", |(name, _)| *name);
+            panic!(
+                "{} can't load after {name}, which already loads after it",
+                type_name::<R>()
+            );
+        }
+        self.dependencies.entry(id).or_default().push(dependency);
+    }
+
+    /// Whether `id` is `target` or loads after it, directly or through other dependencies.
+    fn waits_on(&self, id: TypeId, target: TypeId) -> bool {
+        let mut stack = vec![id];
+        let mut seen = Vec::new();
+        while let Some(id) = stack.pop() {
+            if id == target {
+                return true;
+            }
+            if seen.contains(&id) {
+                continue;
+            }
+            seen.push(id);
+            stack.extend(self.dependencies.get(&id).into_iter().flatten());
+        }
+        false
+    }
+
+    /// Whether every registered resource `R` depends on is already loaded. Dependencies
+    /// without a loader are ignored.
+    pub(crate) fn dependencies_loaded<R: 'static>(&self) -> bool {
+        self.dependencies
+            .get(&TypeId::of::<R>())
+            .into_iter()
+            .flatten()
+            .all(|dependency| {
+                self.resources
+                    .get(dependency)
+                    .is_none_or(|(_, loaded)| *loaded)
+            })
+    }
+
+    pub(crate) fn mark_loaded<R: 'static>(&mut self) {
+        if let Some((_, loaded)) = self.resources.get_mut(&TypeId::of::<R>()) {
+            *loaded = true;
+        }
+    }
+
+    /// `R` is being loaded again, e.g. from another save.
+    pub(crate) fn mark_unloaded<R: 'static>(&mut self) {
+        if let Some((_, loaded)) = self.resources.get_mut(&TypeId::of::<R>()) {
+            *loaded = false;
+        }
+    }
+
+    pub fn is_loaded<R: 'static>(&self) -> bool {
+        self.resources
+            .get(&TypeId::of::<R>())
+            .is_some_and(|(_, loaded)| *loaded)
+    }
+
+    /// Whether every registered resource has been loaded, successfully or via its default.
+    pub fn all_loaded(&self) -> bool {
+        self.resources.values().all(|(_, loaded)| *loaded)
+    }
+
+    /// Type names of the resources still waiting on their load.
+    pub fn pending(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.resources
+            .values()
+            .filter(|(_, loaded)| !loaded)
+            .map(|(name, _)| *name)
+    }
+}
+
+/// Systems inserting loaded resources, they run in `PreUpdate`.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LoadSet;
+
+/// Emitted once every registered sink has finished its startup load, successfully or by
+/// falling back to defaults. See also the [`persistence_ready`] run condition.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PersistenceReady;
+
+/// Name of [`PersistenceReady`] used by staged loads.
+pub type AllLoaded = PersistenceReady;
+
+/// Run condition, true once every registered sink has finished its startup load. Apps
+/// without persisted resources are always ready.
+pub fn persistence_ready(tracker: Option<Res<LoadTracker>>) -> bool {
+    tracker.is_none_or(|tracker| tracker.all_loaded())
+}
+
+/// Shared by every loader, added by the first one.
+pub(crate) struct LoadTrackerPlugin;
+
+impl Plugin for LoadTrackerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadTracker>();
+        app.add_event::<PersistenceReady>();
+        app.add_systems(PreUpdate, emit_persistence_ready.after(LoadSet));
+    }
+}
+
+fn emit_persistence_ready(
+    mut sent: Local<bool>,
+    tracker: Res<LoadTracker>,
+    mut ready: EventWriter<PersistenceReady>,
+) {
+    if !*sent && tracker.all_loaded() {
+        *sent = true;
+        ready.write(PersistenceReady);
+    }
+}
+
+/// Triggered by [`PausedLoadPlugin`] once every persisted resource is in place and
+/// virtual time runs again.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ResumeAfterLoad;
+
+/// Keeps [`Time<Virtual>`] paused until every persisted resource has been loaded, so
+/// gameplay systems never tick against a half-restored world, then triggers
+/// [`ResumeAfterLoad`].
+pub struct PausedLoadPlugin;
+
+impl Plugin for PausedLoadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadTracker>();
+        app.add_systems(Startup, pause_for_load);
+        app.add_systems(
+            PreUpdate,
+            resume_after_load
+                .after(LoadSet)
+                .run_if(resource_exists::<PausedForLoad>),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct PausedForLoad {
+    /// Virtual time was running before the load, as opposed to paused by the game.
+    unpause: bool,
+}
+
+fn pause_for_load(mut commands: Commands, mut time: ResMut<Time<Virtual>>) {
+    commands.insert_resource(PausedForLoad {
+        unpause: !time.is_paused(),
+    });
+    time.pause();
+}
+
+fn resume_after_load(
+    mut commands: Commands,
+    tracker: Res<LoadTracker>,
+    paused: Res<PausedForLoad>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    if !tracker.all_loaded() {
+        return;
+    }
+    if paused.unpause {
+        time.unpause();
+    }
+    commands.remove_resource::<PausedForLoad>();
+    commands.trigger(ResumeAfterLoad);
+}
+
+#[cfg(feature = "states")]
+pub use states::LoadingStatePlugin;
+
+#[cfg(feature = "states")]
+mod states {
+    use super::LoadTracker;
+    use bevy::{prelude::*, state::state::FreelyMutableState};
+
+    /// Moves the app from `loading` to `ready` once every persisted resource has been
+    /// inserted, replacing bespoke `resource_added` run conditions.
+    pub struct LoadingStatePlugin<S> {
+        loading: S,
+        ready: S,
+    }
+
+    impl<S> LoadingStatePlugin<S> {
+        pub fn new(loading: S, ready: S) -> Self {
+            Self { loading, ready }
+        }
+    }
+
+    impl<S> Plugin for LoadingStatePlugin<S>
+    where
+        S: FreelyMutableState + Clone,
+    {
+        fn build(&self, app: &mut App) {
+            app.init_resource::<LoadTracker>();
+            let ready = self.ready.clone();
+            app.add_systems(
+                Update,
+                (move |tracker: Res<LoadTracker>, mut next: ResMut<NextState<S>>| {
+                    if tracker.all_loaded() {
+                        next.set(ready.clone());
+                    }
+                })
+                .run_if(in_state(self.loading.clone())),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Settings;
+    struct Profile;
+    struct World;
+
+    #[test]
+    fn dependency_chains_are_allowed() {
+        let mut tracker = LoadTracker::default();
+        tracker.add_dependency::<Profile>(TypeId::of::<Settings>());
+        tracker.add_dependency::<World>(TypeId::of::<Profile>());
+        tracker.add_dependency::<World>(TypeId::of::<Settings>());
+    }
+
+    #[test]
+    #[should_panic(expected = "already loads after it")]
+    fn self_dependency_panics() {
+        LoadTracker::default().add_dependency::<World>(TypeId::of::<World>());
+    }
+
+    #[test]
+    #[should_panic(expected = "already loads after it")]
+    fn indirect_cycle_panics() {
+        let mut tracker = LoadTracker::default();
+        tracker.add_dependency::<Profile>(TypeId::of::<Settings>());
+        tracker.add_dependency::<World>(TypeId::of::<Profile>());
+        tracker.add_dependency::<Settings>(TypeId::of::<World>());
+    }
+}
@@ -0,0 +1,277 @@
+use crate::{
+    codec, envelope::VersionStamp, ready::LoadTrackerPlugin, IoSender, IoSinkError, IoSinkPlugin,
+    IoWriter, LoadSet, LoadTracker, Migrations, SaveFormat, SinkResult,
+};
+use async_channel::{unbounded, Receiver};
+use bevy::{prelude::*, tasks::IoTaskPool};
+use redis::{aio::MultiplexedConnection, streams::StreamMaxlen, AsyncCommands, Client};
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+
+fn redis_error(e: redis::RedisError) -> IoSinkError {
+    IoSinkError::Other(format!("redis: {e}"))
+}
+
+async fn connect(url: &str) -> SinkResult<MultiplexedConnection> {
+    Client::open(url)
+        .map_err(redis_error)?
+        .get_multiplexed_async_std_connection()
+        .await
+        .map_err(redis_error)
+}
+
+/// Read back what a [`RedisSink`] stored under `key` with `SET`, `None` if nothing was. Only
+/// values stored by a [`RedisPlugin`] carry their schema version, pass
+/// [`Migrations::default`] for those of a bare sink.
+pub async fn load_redis<R>(
+    url: &str,
+    key: &str,
+    migrations: &Migrations,
+) -> Result<Option<R>, IoSinkError>
+where
+    R: DeserializeOwned,
+{
+    let mut connection = connect(url).await?;
+    let bytes: Option<Vec<u8>> = connection.get(key).await.map_err(redis_error)?;
+    bytes
+        .map(|bytes| codec::decode(&bytes, migrations))
+        .transpose()
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RedisMode {
+    Set,
+    Stream { max_len: Option<usize> },
+}
+
+/// Stores each `R` in Redis: the latest value under a key with `SET`, or every value
+/// appended to a stream with `XADD` after [`as_stream`](Self::as_stream). Stream entries keep
+/// the encoded message in their `payload` field.
+///
+/// ```ignore
+/// app.add_plugins(IoSinkPlugin::<MatchEvent, _>::new(
+///     RedisSink::new("redis://127.0.0.1/", "match:42:events").as_stream(Some(10_000)),
+/// ));
+/// ```
+pub struct RedisSink<R> {
+    url: String,
+    key: String,
+    mode: RedisMode,
+    format: SaveFormat,
+    stamp: Option<VersionStamp>,
+    max_message_size: Option<u64>,
+    connection: Option<MultiplexedConnection>,
+    /// Payload of the last `SET`, without the envelope.
+    last_contents: Option<Vec<u8>>,
+    last_write_len: Option<u64>,
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R> RedisSink<R> {
+    pub fn new(url: impl Into<String>, key: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            key: key.into(),
+            mode: RedisMode::Set,
+            format: SaveFormat::default(),
+            stamp: None,
+            max_message_size: None,
+            connection: None,
+            last_contents: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Store messages in a versioned envelope, so the loader only migrates older ones.
+    pub(crate) fn stamped(mut self, stamp: VersionStamp) -> Self {
+        self.stamp = Some(stamp);
+        self
+    }
+
+    /// Append every message to the stream at the key instead of replacing its value, trimmed
+    /// to about `max_len` entries if set.
+    pub fn as_stream(mut self, max_len: Option<usize>) -> Self {
+        self.mode = RedisMode::Stream { max_len };
+        self
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Reject encoded messages larger than `bytes` with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+}
+
+impl<R> IoWriter<R> for RedisSink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        self.connection = Some(connect(&self.url).await?);
+        self.last_contents = None;
+        Ok(())
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let payload = self.format.encode(&data)?;
+        let bytes = match &self.stamp {
+            Some(stamp) => stamp.encode(self.format, &data)?,
+            None => payload.clone(),
+        };
+        IoSinkError::check_size(bytes.len(), self.max_message_size)?;
+        let connection = self
+            .connection
+            .as_mut()
+            .ok_or(IoSinkError::NotInitialized)?;
+        let len = bytes.len() as u64;
+
+        match self.mode {
+            RedisMode::Set => {
+                // Change detection fires on any `ResMut` deref, so identical payloads are
+                // common.
+                if self.last_contents.as_deref() == Some(payload.as_slice()) {
+                    self.last_write_len = Some(0);
+                    return Ok(());
+                }
+                let () = connection
+                    .set(&self.key, bytes.as_slice())
+                    .await
+                    .map_err(redis_error)?;
+                self.last_contents = Some(payload);
+            }
+            RedisMode::Stream { max_len: None } => {
+                let _: String = connection
+                    .xadd(&self.key, "*", &[("payload", bytes.as_slice())])
+                    .await
+                    .map_err(redis_error)?;
+            }
+            RedisMode::Stream { max_len: Some(max) } => {
+                let _: String = connection
+                    .xadd_maxlen(
+                        &self.key,
+                        StreamMaxlen::Approx(max),
+                        "*",
+                        &[("payload", bytes.as_slice())],
+                    )
+                    .await
+                    .map_err(redis_error)?;
+            }
+        }
+        self.last_write_len = Some(len);
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        let connection = self
+            .connection
+            .as_mut()
+            .ok_or(IoSinkError::NotInitialized)?;
+        let () = connection.del(&self.key).await.map_err(redis_error)?;
+        self.last_contents = None;
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+struct RedisLoadReceiver<R>(Receiver<Option<R>>);
+
+/// Persists the resource `R` under a Redis key: it is fetched at startup, like a
+/// [`FileSinkPlugin`](crate::FileSinkPlugin) save, then stored with `SET` on every change.
+/// `R::default()` is inserted when the key doesn't exist or can't be read.
+///
+/// ```ignore
+/// app.add_plugins(RedisPlugin::<Lobby>::new("redis://127.0.0.1/", "lobby:42"));
+/// ```
+pub struct RedisPlugin<R> {
+    url: String,
+    key: String,
+    migrations: Migrations,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R> RedisPlugin<R> {
+    pub fn new(url: impl Into<String>, key: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            key: key.into(),
+            migrations: Migrations::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Upgrade values stored by older builds while loading. Values are stored with the
+    /// current version of `migrations`.
+    pub fn with_migrations(mut self, migrations: Migrations) -> Self {
+        self.migrations = migrations;
+        self
+    }
+}
+
+impl<R> Plugin for RedisPlugin<R>
+where
+    R: Resource + Clone + Serialize + DeserializeOwned + Default,
+{
+    fn build(&self, app: &mut App) {
+        let stamp = VersionStamp::new(app, &self.migrations);
+        app.add_plugins(IoSinkPlugin::<R, _>::new(
+            RedisSink::new(self.url.clone(), self.key.clone()).stamped(stamp),
+        ));
+        if !app.is_plugin_added::<LoadTrackerPlugin>() {
+            app.add_plugins(LoadTrackerPlugin);
+        }
+        app.world_mut()
+            .resource_mut::<LoadTracker>()
+            .register::<R>();
+
+        let (tx, rx) = unbounded();
+        app.insert_resource(RedisLoadReceiver::<R>(rx));
+        let (url, key, migrations) = (self.url.clone(), self.key.clone(), self.migrations.clone());
+        app.add_systems(Startup, move || {
+            let (url, key, migrations, tx) =
+                (url.clone(), key.clone(), migrations.clone(), tx.clone());
+            IoTaskPool::get()
+                .spawn(async move {
+                    let loaded = load_redis::<R>(&url, &key, &migrations)
+                        .await
+                        .unwrap_or_else(|e| {
+                            error!("failed to load {key} from redis: {e}");
+                            None
+                        });
+                    let _ = tx.send(loaded).await;
+                })
+                .detach();
+        });
+        app.add_systems(PreUpdate, receive_redis_load::<R>.in_set(LoadSet));
+        app.add_systems(
+            PostUpdate,
+            sync_redis::<R>.run_if(resource_exists_and_changed::<R>),
+        );
+    }
+}
+
+fn receive_redis_load<R: Resource + Default>(
+    mut commands: Commands,
+    receiver: Res<RedisLoadReceiver<R>>,
+    mut tracker: ResMut<LoadTracker>,
+) {
+    if let Ok(loaded) = receiver.0.try_recv() {
+        commands.insert_resource(loaded.unwrap_or_default());
+        tracker.mark_loaded::<R>();
+    }
+}
+
+fn sync_redis<R: Resource + Clone>(sender: Res<IoSender<R>>, res: Res<R>) {
+    if let Err(err) = sender.enqueue(res.clone()) {
+        error!("{err}");
+    }
+}
@@ -0,0 +1,327 @@
+use crate::{IoSender, IoSinkError, IoSinkPlugin, IoWriter, SinkResult, WriterCapabilities};
+use async_channel::{unbounded, Receiver, Sender};
+use async_std::path::PathBuf;
+use bevy::{
+    prelude::*,
+    reflect::{ReflectDeserialize, ReflectSerialize},
+    tasks::IoTaskPool,
+};
+use std::{any::TypeId, io::ErrorKind, time::Duration};
+
+const DEFAULT_REFLECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A resource to persist, identified at runtime instead of by a type parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReflectTarget {
+    TypeId(TypeId),
+    /// Full type path, e.g. `my_mod::Settings`.
+    TypePath(String),
+}
+
+impl From<TypeId> for ReflectTarget {
+    fn from(id: TypeId) -> Self {
+        Self::TypeId(id)
+    }
+}
+
+impl From<&str> for ReflectTarget {
+    fn from(path: &str) -> Self {
+        Self::TypePath(path.to_owned())
+    }
+}
+
+impl From<String> for ReflectTarget {
+    fn from(path: String) -> Self {
+        Self::TypePath(path)
+    }
+}
+
+struct ReflectEntry {
+    type_id: TypeId,
+    type_path: &'static str,
+    path: PathBuf,
+    loaded: bool,
+    last_saved: Option<Vec<u8>>,
+}
+
+/// Resources persisted through their reflection data, registered at any time, e.g. by mods
+/// added after the app was built. The type must be registered with
+/// `#[reflect(Resource, Serialize, Deserialize)]`.
+///
+/// ```ignore
+/// world
+///     .resource_mut::<ReflectPersistence>()
+///     .register("my_mod::Settings", "saves/my_mod.json");
+/// ```
+#[derive(Resource, Default)]
+pub struct ReflectPersistence {
+    pending: Vec<(ReflectTarget, PathBuf)>,
+    entries: Vec<ReflectEntry>,
+}
+
+impl ReflectPersistence {
+    /// Load `target` from `path` once it is found in the type registry, then save it there
+    /// whenever it changes. Unknown types are logged and dropped.
+    pub fn register(&mut self, target: impl Into<ReflectTarget>, path: impl Into<PathBuf>) {
+        self.pending.push((target.into(), path.into()));
+    }
+
+    /// Whether the resource of `type_id` has been read back from its file.
+    pub fn is_loaded(&self, type_id: TypeId) -> bool {
+        self.entries
+            .iter()
+            .any(|entry| entry.type_id == type_id && entry.loaded)
+    }
+
+    /// Type path and file of every resolved registration.
+    pub fn files(&self) -> impl Iterator<Item = (&'static str, &PathBuf)> + '_ {
+        self.entries
+            .iter()
+            .map(|entry| (entry.type_path, &entry.path))
+    }
+}
+
+/// Serialized bytes of a reflected resource, sent through the sink of a
+/// [`ReflectPersistPlugin`].
+#[derive(Resource, Debug, Clone)]
+pub struct ReflectedSave {
+    pub path: PathBuf,
+    pub bytes: Vec<u8>,
+}
+
+/// Writes each [`ReflectedSave`] to its own path.
+#[derive(Default)]
+pub struct ReflectedFileSink;
+
+impl IoWriter<ReflectedSave> for ReflectedFileSink {
+    async fn write(&mut self, save: ReflectedSave) -> SinkResult {
+        if let Some(parent) = save.path.parent() {
+            async_fs::create_dir_all(parent).await?;
+        }
+        // Staged next to the save, so a crash mid-write leaves the previous one intact.
+        let mut staged = save.path.as_os_str().to_owned();
+        staged.push(".tmp");
+        async_fs::write(&staged, &save.bytes).await?;
+        async_fs::rename(&staged, &save.path).await?;
+        Ok(())
+    }
+
+    fn capabilities(&self) -> WriterCapabilities {
+        WriterCapabilities {
+            atomic_rename: true,
+            ..default()
+        }
+    }
+}
+
+/// Adds [`ReflectPersistence`], which persists resources registered at runtime by
+/// [`TypeId`] or type path.
+pub struct ReflectPersistPlugin {
+    interval: Duration,
+}
+
+impl Default for ReflectPersistPlugin {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_REFLECT_INTERVAL,
+        }
+    }
+}
+
+impl ReflectPersistPlugin {
+    /// How often changed resources are saved, 5 seconds by default.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+#[derive(Resource)]
+struct ReflectSaveTimer(Timer);
+
+type LoadedBytes = (TypeId, Result<Option<Vec<u8>>, IoSinkError>);
+
+#[derive(Resource)]
+struct ReflectLoadChannel {
+    tx: Sender<LoadedBytes>,
+    rx: Receiver<LoadedBytes>,
+}
+
+impl Plugin for ReflectPersistPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(IoSinkPlugin::<ReflectedSave, _>::new(ReflectedFileSink));
+        let (tx, rx) = unbounded();
+        app.insert_resource(ReflectLoadChannel { tx, rx });
+        app.insert_resource(ReflectSaveTimer(Timer::new(
+            self.interval,
+            TimerMode::Repeating,
+        )));
+        app.init_resource::<ReflectPersistence>();
+        app.add_systems(PreUpdate, (resolve_registrations, apply_loaded).chain());
+        app.add_systems(Update, save_reflected);
+    }
+}
+
+fn resolve_registrations(world: &mut World) {
+    if world.resource::<ReflectPersistence>().pending.is_empty() {
+        return;
+    }
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    let tx = world.resource::<ReflectLoadChannel>().tx.clone();
+    let mut persistence = world.resource_mut::<ReflectPersistence>();
+
+    for (target, path) in std::mem::take(&mut persistence.pending) {
+        let registration = match &target {
+            ReflectTarget::TypeId(id) => registry.get(*id),
+            ReflectTarget::TypePath(type_path) => registry.get_with_type_path(type_path),
+        };
+        let Some(registration) = registration else {
+            error!("cannot persist {target:?}, it is not in the type registry");
+            continue;
+        };
+        let type_path = registration.type_info().type_path();
+        if registration.data::<ReflectResource>().is_none()
+            || registration.data::<ReflectSerialize>().is_none()
+            || registration.data::<ReflectDeserialize>().is_none()
+        {
+            error!(
+                "cannot persist {type_path}, it must reflect Resource, Serialize and Deserialize"
+            );
+            continue;
+        }
+        let type_id = registration.type_id();
+        if persistence
+            .entries
+            .iter()
+            .any(|entry| entry.type_id == type_id)
+        {
+            warn!("{type_path} is already persisted");
+            continue;
+        }
+
+        persistence.entries.push(ReflectEntry {
+            type_id,
+            type_path,
+            path: path.clone(),
+            loaded: false,
+            last_saved: None,
+        });
+        let tx = tx.clone();
+        IoTaskPool::get()
+            .spawn(async move {
+                let bytes = match async_fs::read(&path).await {
+                    Ok(bytes) if bytes.is_empty() => Ok(None),
+                    Ok(bytes) => Ok(Some(bytes)),
+                    Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+                    Err(e) => Err(e.into()),
+                };
+                let _ = tx.send((type_id, bytes)).await;
+            })
+            .detach();
+    }
+}
+
+fn apply_loaded(world: &mut World) {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+
+    while let Ok((type_id, bytes)) = world.resource::<ReflectLoadChannel>().rx.try_recv() {
+        let registration = registry
+            .get(type_id)
+            .expect("persisted types were resolved from the registry");
+        let type_path = registration.type_info().type_path();
+
+        match bytes {
+            Ok(Some(bytes)) => {
+                let mut deserializer = serde_json::Deserializer::from_slice(&bytes);
+                match registration
+                    .data::<ReflectDeserialize>()
+                    .expect("checked at registration")
+                    .deserialize(&mut deserializer)
+                {
+                    Ok(value) => registration
+                        .data::<ReflectResource>()
+                        .expect("checked at registration")
+                        .insert(world, value.as_partial_reflect(), &registry),
+                    Err(e) => error!("save of {type_path} is unreadable: {e}"),
+                }
+            }
+            Ok(None) => {}
+            Err(e) => error!("failed to read the save of {type_path}: {e}"),
+        }
+
+        let mut persistence = world.resource_mut::<ReflectPersistence>();
+        if let Some(entry) = persistence
+            .entries
+            .iter_mut()
+            .find(|entry| entry.type_id == type_id)
+        {
+            entry.loaded = true;
+        }
+    }
+}
+
+fn save_reflected(world: &mut World) {
+    let delta = world.resource::<Time>().delta();
+    if !world
+        .resource_mut::<ReflectSaveTimer>()
+        .0
+        .tick(delta)
+        .just_finished()
+    {
+        return;
+    }
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+
+    let mut saves = Vec::new();
+    for entry in &world.resource::<ReflectPersistence>().entries {
+        // Never before the load, the save would overwrite the file with defaults.
+        if !entry.loaded {
+            continue;
+        }
+        let Some(registration) = registry.get(entry.type_id) else {
+            continue;
+        };
+        let reflect_resource = registration
+            .data::<ReflectResource>()
+            .expect("checked at registration");
+        let Ok(value) = reflect_resource.reflect(&*world) else {
+            continue;
+        };
+        let serializable = registration
+            .data::<ReflectSerialize>()
+            .expect("checked at registration")
+            .get_serializable(value);
+        match serde_json::to_vec_pretty(&*serializable) {
+            Ok(bytes) if entry.last_saved.as_ref() != Some(&bytes) => {
+                saves.push((entry.type_id, entry.path.clone(), bytes))
+            }
+            Ok(_) => {}
+            Err(e) => error!("failed to serialize {}: {e}", entry.type_path),
+        }
+    }
+
+    for (type_id, path, bytes) in saves {
+        let sent = world
+            .resource::<IoSender<ReflectedSave>>()
+            .enqueue(ReflectedSave {
+                path,
+                bytes: bytes.clone(),
+            });
+        match sent {
+            Ok(()) => {
+                let mut persistence = world.resource_mut::<ReflectPersistence>();
+                if let Some(entry) = persistence
+                    .entries
+                    .iter_mut()
+                    .find(|entry| entry.type_id == type_id)
+                {
+                    entry.last_saved = Some(bytes);
+                }
+            }
+            Err(err) => error!("{err}"),
+        }
+    }
+}
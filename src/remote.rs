@@ -0,0 +1,444 @@
+use crate::{load::FileLoader, storage, IoSinkError, IoSinks, LoadCompleted, SinkResult};
+use async_channel::{unbounded, Receiver, Sender};
+use async_std::path::{Path, PathBuf};
+use bevy::{prelude::*, tasks::IoTaskPool};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{future::Future, io::ErrorKind, marker::PhantomData, sync::Arc};
+
+/// Opaque token identifying one version of a remote object, e.g. an HTTP `ETag` or an object
+/// generation. It changes whenever the object does.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Revision(pub String);
+
+/// The contents of a remote object and their revision.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteObject {
+    pub bytes: Vec<u8>,
+    pub revision: Revision,
+}
+
+/// Result of a conditional [`RemoteStore::put`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PutOutcome {
+    Stored(Revision),
+    /// The object isn't at the expected revision anymore, nothing was written.
+    Conflict,
+}
+
+/// A remote key-value store holding save files, the backend of a [`RemoteSyncPlugin`].
+/// Implementations wrap a cloud API: object storage, a game backend, a platform's cloud
+/// saves.
+pub trait RemoteStore: Send + Sync + 'static {
+    /// The object at `key`, `None` if there is none.
+    fn get(
+        &self,
+        key: &str,
+    ) -> impl Future<Output = Result<Option<RemoteObject>, IoSinkError>> + Send;
+
+    /// The current revision of the object at `key` without downloading it, `None` if there is
+    /// none.
+    fn head(&self, key: &str)
+        -> impl Future<Output = Result<Option<Revision>, IoSinkError>> + Send;
+
+    /// Store `bytes` at `key` if the object is still at `expected`, `None` meaning it must not
+    /// exist yet.
+    fn put(
+        &self,
+        key: &str,
+        bytes: &[u8],
+        expected: Option<&Revision>,
+    ) -> impl Future<Output = Result<PutOutcome, IoSinkError>> + Send;
+}
+
+/// What a sync of `R`'s save did, see [`SyncCompleted`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyncOutcome {
+    /// Both copies were already in sync.
+    Unchanged,
+    /// The local save was uploaded.
+    Pushed(Revision),
+    /// The remote save replaced the local one and `R` is being reloaded.
+    Pulled(Revision),
+}
+
+/// Emitted when a sync of `R`'s save completed without conflict.
+#[derive(Event, Debug)]
+pub struct SyncCompleted<R> {
+    pub outcome: SyncOutcome,
+    _marker: PhantomData<fn() -> R>,
+}
+
+/// Emitted when both the local save of `R` and its remote copy changed since the last sync.
+/// Nothing is overwritten until the game picks a side with [`ResolveSyncConflict`], e.g.
+/// after asking the player.
+#[derive(Event, Debug)]
+pub struct SyncConflict<R> {
+    pub local: Vec<u8>,
+    pub remote: RemoteObject,
+    _marker: PhantomData<fn() -> R>,
+}
+
+/// Emitted when a sync of `R`'s save failed, it is attempted again on the next
+/// [`SyncRemote`].
+#[derive(Event, Debug)]
+pub struct SyncFailed<R> {
+    pub error: IoSinkError,
+    _marker: PhantomData<fn() -> R>,
+}
+
+/// Trigger to reconcile the save of `R` with its remote copy:
+/// `commands.trigger(SyncRemote::<R>::default())`.
+#[derive(Event, Debug)]
+pub struct SyncRemote<R>(PhantomData<fn() -> R>);
+
+impl<R> Default for SyncRemote<R> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// Which copy wins a [`SyncConflict`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyncResolution {
+    /// Overwrite the remote copy with the local save.
+    KeepLocal,
+    /// Overwrite the local save with the remote copy and reload `R`.
+    KeepRemote,
+}
+
+/// Trigger settling the latest [`SyncConflict<R>`]:
+/// `commands.trigger(ResolveSyncConflict::<R>::new(SyncResolution::KeepRemote))`.
+#[derive(Event, Debug)]
+pub struct ResolveSyncConflict<R> {
+    pub resolution: SyncResolution,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R> ResolveSyncConflict<R> {
+    pub fn new(resolution: SyncResolution) -> Self {
+        Self {
+            resolution,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// What both copies looked like after the last successful sync, stored next to the save as
+/// `<save>.sync`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct SyncState {
+    revision: Option<Revision>,
+    /// FNV-1a hash of the local save, stable across builds unlike `std`'s hasher.
+    local_hash: Option<u64>,
+}
+
+fn sync_state_path(save: &Path) -> PathBuf {
+    let mut name = save.as_os_str().to_owned();
+    name.push(".sync");
+    PathBuf::from(name)
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+async fn read_optional(path: &Path) -> Result<Option<Vec<u8>>, IoSinkError> {
+    match storage::read(path).await {
+        // The sink creates the file when it starts, possibly before anything was saved.
+        Ok(bytes) if bytes.is_empty() => Ok(None),
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn read_sync_state(save: &Path) -> Result<SyncState, IoSinkError> {
+    match read_optional(&sync_state_path(save)).await? {
+        Some(bytes) => serde_json::from_slice(&bytes).map_err(IoSinkError::deserialization),
+        None => Ok(SyncState::default()),
+    }
+}
+
+async fn write_sync_state(save: &Path, revision: Revision, local: &[u8]) -> SinkResult {
+    let state = SyncState {
+        revision: Some(revision),
+        local_hash: Some(fnv1a(local)),
+    };
+    let json = serde_json::to_vec(&state).map_err(IoSinkError::serialization)?;
+    storage::write(&sync_state_path(save), &json).await?;
+    Ok(())
+}
+
+enum SyncMessage {
+    Completed(SyncOutcome),
+    Conflict(Vec<u8>, RemoteObject),
+    Failed(IoSinkError),
+}
+
+/// Reconcile the save at `path` with the object at `key`.
+async fn reconcile<S: RemoteStore>(
+    store: &S,
+    key: &str,
+    path: &Path,
+) -> Result<SyncMessage, IoSinkError> {
+    let state = read_sync_state(path).await?;
+    let local = read_optional(path).await?;
+    let local_changed = local.as_deref().map(fnv1a) != state.local_hash;
+    let remote_revision = store.head(key).await?;
+    let remote_changed = remote_revision != state.revision;
+
+    match (local, local_changed, remote_changed) {
+        // A deleted local save isn't uploaded, wiping it must not wipe the remote copy.
+        (_, false, false) | (None, true, false) => {
+            Ok(SyncMessage::Completed(SyncOutcome::Unchanged))
+        }
+        (Some(local), true, false) => push(store, key, path, local, remote_revision).await,
+        (_, false, true) | (None, true, true) => match store.get(key).await? {
+            Some(remote) => take_remote(path, remote).await,
+            None => Ok(SyncMessage::Completed(SyncOutcome::Unchanged)),
+        },
+        (Some(local), true, true) => {
+            let Some(remote) = store.get(key).await? else {
+                // Deleted remotely, nothing to lose by uploading.
+                return push(store, key, path, local, None).await;
+            };
+            if remote.bytes == local {
+                write_sync_state(path, remote.revision.clone(), &local).await?;
+                return Ok(SyncMessage::Completed(SyncOutcome::Unchanged));
+            }
+            Ok(SyncMessage::Conflict(local, remote))
+        }
+    }
+}
+
+async fn push<S: RemoteStore>(
+    store: &S,
+    key: &str,
+    path: &Path,
+    local: Vec<u8>,
+    expected: Option<Revision>,
+) -> Result<SyncMessage, IoSinkError> {
+    match store.put(key, &local, expected.as_ref()).await? {
+        PutOutcome::Stored(revision) => {
+            write_sync_state(path, revision.clone(), &local).await?;
+            Ok(SyncMessage::Completed(SyncOutcome::Pushed(revision)))
+        }
+        // Changed remotely since `head`, the next sync sees both sides changed.
+        PutOutcome::Conflict => Err(IoSinkError::Other(format!(
+            "{key} changed remotely during the sync"
+        ))),
+    }
+}
+
+async fn take_remote(path: &Path, remote: RemoteObject) -> Result<SyncMessage, IoSinkError> {
+    storage::write(path, &remote.bytes).await?;
+    write_sync_state(path, remote.revision.clone(), &remote.bytes).await?;
+    Ok(SyncMessage::Completed(SyncOutcome::Pulled(remote.revision)))
+}
+
+#[derive(Resource)]
+struct RemoteSync<R, S> {
+    store: Arc<S>,
+    key: String,
+    /// A sync is running, further requests wait for it.
+    running: bool,
+    requested: bool,
+    /// The latest conflict, kept until it is resolved.
+    conflict: Option<(Vec<u8>, RemoteObject)>,
+    tx: Sender<SyncMessage>,
+    rx: Receiver<SyncMessage>,
+    _marker: PhantomData<fn() -> R>,
+}
+
+/// Keeps the save of `R` written by a [`FileSinkPlugin`](crate::FileSinkPlugin) in sync
+/// with a copy in a [`RemoteStore`], once loaded and on every [`SyncRemote<R>`]. A remote
+/// copy newer than the local save replaces it and `R` is reloaded, a local save newer than
+/// the remote copy is uploaded, and [`SyncConflict<R>`] is emitted when both changed.
+///
+/// ```ignore
+/// app.add_plugins((
+///     FileSinkPlugin::<World>::new("saves/world.json"),
+///     RemoteSyncPlugin::<World, _>::new(MyCloud::new(token), "world.json"),
+/// ));
+/// ```
+pub struct RemoteSyncPlugin<R, S> {
+    store: Arc<S>,
+    key: String,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R, S: RemoteStore> RemoteSyncPlugin<R, S> {
+    pub fn new(store: S, key: impl Into<String>) -> Self {
+        Self {
+            store: Arc::new(store),
+            key: key.into(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<R, S> Plugin for RemoteSyncPlugin<R, S>
+where
+    R: Resource + DeserializeOwned + Serialize + Default,
+    S: RemoteStore,
+{
+    fn build(&self, app: &mut App) {
+        let (tx, rx) = unbounded();
+        app.insert_resource(RemoteSync::<R, S> {
+            store: self.store.clone(),
+            key: self.key.clone(),
+            running: false,
+            requested: false,
+            conflict: None,
+            tx,
+            rx,
+            _marker: PhantomData,
+        });
+        app.add_event::<SyncCompleted<R>>();
+        app.add_event::<SyncConflict<R>>();
+        app.add_event::<SyncFailed<R>>();
+        app.add_observer(
+            |_: Trigger<SyncRemote<R>>, sinks: Res<IoSinks>, mut sync: ResMut<RemoteSync<R, S>>| {
+                request_sync(&sinks, &mut sync);
+            },
+        );
+        app.add_observer(resolve_conflict::<R, S>);
+        app.add_systems(
+            PreUpdate,
+            (
+                sync_after_load::<R, S>,
+                receive_sync::<R, S>.after(sync_after_load::<R, S>),
+            ),
+        );
+    }
+}
+
+fn request_sync<R, S: RemoteStore>(sinks: &IoSinks, sync: &mut RemoteSync<R, S>) {
+    if sync.running {
+        sync.requested = true;
+        return;
+    }
+    let Some(path) = sinks.path::<R>() else {
+        error!("{} has no save file to sync", std::any::type_name::<R>());
+        return;
+    };
+    sync.running = true;
+    sync.requested = false;
+    let (store, key, path, tx) = (
+        sync.store.clone(),
+        sync.key.clone(),
+        path.to_path_buf(),
+        sync.tx.clone(),
+    );
+    IoTaskPool::get()
+        .spawn(async move {
+            let synced = reconcile(&*store, &key, &path).await;
+            let _ = tx.send(synced.unwrap_or_else(SyncMessage::Failed)).await;
+        })
+        .detach();
+}
+
+fn sync_after_load<R: Resource, S: RemoteStore>(
+    mut loaded: EventReader<LoadCompleted<R>>,
+    sinks: Res<IoSinks>,
+    mut sync: ResMut<RemoteSync<R, S>>,
+) {
+    if loaded.read().count() > 0 {
+        request_sync(&sinks, &mut sync);
+    }
+}
+
+fn receive_sync<R, S>(
+    sinks: Res<IoSinks>,
+    mut sync: ResMut<RemoteSync<R, S>>,
+    loader: Option<Res<FileLoader<R>>>,
+    mut completed: EventWriter<SyncCompleted<R>>,
+    mut conflicts: EventWriter<SyncConflict<R>>,
+    mut failed: EventWriter<SyncFailed<R>>,
+) where
+    R: Resource + DeserializeOwned + Serialize + Default,
+    S: RemoteStore,
+{
+    while let Ok(message) = sync.rx.try_recv() {
+        sync.running = false;
+        match message {
+            SyncMessage::Completed(outcome) => {
+                if matches!(outcome, SyncOutcome::Pulled(_)) {
+                    match &loader {
+                        Some(loader) => loader.spawn(),
+                        None => warn!(
+                            "{} isn't loaded by a FileSinkPlugin, restart to load the pulled save",
+                            std::any::type_name::<R>()
+                        ),
+                    }
+                }
+                completed.write(SyncCompleted {
+                    outcome,
+                    _marker: PhantomData,
+                });
+            }
+            SyncMessage::Conflict(local, remote) => {
+                sync.conflict = Some((local.clone(), remote.clone()));
+                conflicts.write(SyncConflict {
+                    local,
+                    remote,
+                    _marker: PhantomData,
+                });
+            }
+            SyncMessage::Failed(error) => {
+                error!(
+                    "failed to sync the save of {}: {error}",
+                    std::any::type_name::<R>()
+                );
+                failed.write(SyncFailed {
+                    error,
+                    _marker: PhantomData,
+                });
+            }
+        }
+    }
+    if sync.requested {
+        request_sync(&sinks, &mut sync);
+    }
+}
+
+fn resolve_conflict<R, S>(
+    trigger: Trigger<ResolveSyncConflict<R>>,
+    sinks: Res<IoSinks>,
+    mut sync: ResMut<RemoteSync<R, S>>,
+) where
+    R: Resource,
+    S: RemoteStore,
+{
+    let Some((local, remote)) = sync.conflict.take() else {
+        warn!(
+            "no sync conflict of {} to resolve",
+            std::any::type_name::<R>()
+        );
+        return;
+    };
+    let Some(path) = sinks.path::<R>() else {
+        return;
+    };
+    sync.running = true;
+    let resolution = trigger.event().resolution;
+    let (store, key, path, tx) = (
+        sync.store.clone(),
+        sync.key.clone(),
+        path.to_path_buf(),
+        sync.tx.clone(),
+    );
+    IoTaskPool::get()
+        .spawn(async move {
+            let resolved = match resolution {
+                SyncResolution::KeepLocal => {
+                    push(&*store, &key, &path, local, Some(remote.revision)).await
+                }
+                SyncResolution::KeepRemote => take_remote(&path, remote).await,
+            };
+            let _ = tx.send(resolved.unwrap_or_else(SyncMessage::Failed)).await;
+        })
+        .detach();
+}
@@ -0,0 +1,38 @@
+use crate::CommandsSaveExt;
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+
+/// Trigger to persist the current `R`: `commands.trigger(SaveRequest::<R>::default())`.
+#[derive(Event, Debug)]
+pub struct SaveRequest<R>(PhantomData<fn() -> R>);
+
+impl<R> Default for SaveRequest<R> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// Trigger to re-read `R` from its save, see [`CommandsSaveExt::reload_resource`].
+#[derive(Event, Debug)]
+pub struct LoadRequest<R>(PhantomData<fn() -> R>);
+
+impl<R> Default for LoadRequest<R> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+pub(crate) fn on_save_request<R: Resource + Clone>(
+    _: Trigger<SaveRequest<R>>,
+    mut commands: Commands,
+) {
+    commands.save_resource::<R>();
+}
+
+pub(crate) fn on_load_request<R>(_: Trigger<LoadRequest<R>>, mut commands: Commands)
+where
+    R: Resource + DeserializeOwned + Serialize + Default,
+{
+    commands.reload_resource::<R>();
+}
@@ -0,0 +1,234 @@
+use crate::{rng::Rng, IoSinkError, IoWriter, SinkResult, WriterCapabilities};
+use async_std::task::sleep;
+use bevy::log::warn;
+use std::time::Duration;
+
+/// How a failed write is retried before it is reported as failed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt, `0` disables retrying.
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    /// Factor applied to the backoff after every failed attempt.
+    pub multiplier: f32,
+    pub max_backoff: Duration,
+    /// Fraction of every backoff, between `0` and `1`, randomly taken off so clients that
+    /// failed together don't all retry at the same moment. Clamped to that range when used.
+    pub jitter: f32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            multiplier: 2.0,
+            max_backoff: Duration::from_secs(5),
+            jitter: 0.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_jitter(mut self, jitter: f32) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Delay before retry number `attempt`, starting at `0`, never above `max_backoff`.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        // In f64 and clamped before it becomes a `Duration`, which panics on overflow.
+        let exponent = attempt.min(i32::MAX as u32) as i32;
+        let factor = f64::from(self.multiplier.max(1.0)).powi(exponent);
+        let secs = self.initial_backoff.as_secs_f64() * factor;
+        let backoff = if secs < self.max_backoff.as_secs_f64() {
+            Duration::from_secs_f64(secs)
+        } else {
+            self.max_backoff
+        };
+        // The field is public, so it may be out of range without `with_jitter`.
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter > 0.0 {
+            let cut = jitter as f64 * Rng::from_time().next_f64();
+            backoff.mul_f64(1.0 - cut)
+        } else {
+            backoff
+        }
+    }
+}
+
+/// Retries left for one call of a [`RetrySink`].
+struct Attempts {
+    policy: RetryPolicy,
+    attempt: u32,
+}
+
+impl Attempts {
+    fn new(policy: RetryPolicy) -> Self {
+        Self { policy, attempt: 0 }
+    }
+
+    /// Wait out the backoff if `error` is worth another attempt.
+    async fn retry(&mut self, what: &str, error: &IoSinkError) -> bool {
+        if !error.is_retryable() || self.attempt >= self.policy.max_retries {
+            return false;
+        }
+        warn!("{what} failed, retrying: {error}");
+        sleep(self.policy.backoff(self.attempt)).await;
+        self.attempt += 1;
+        true
+    }
+}
+
+/// Retries the `init`, `write` and `flush` calls of any writer with its own
+/// [`RetryPolicy`], e.g. to retry only the remote half of a
+/// [`FanoutSink`](crate::FanoutSink). Unlike
+/// [`IoSinkPlugin::with_retry`](crate::IoSinkPlugin::with_retry), which retries whatever
+/// writer the plugin drives, failed attempts don't reach the sink's circuit breaker or stats.
+///
+/// ```ignore
+/// let backup = RetrySink::new(
+///     HttpSink::new("https://example.com/saves/world"),
+///     RetryPolicy::new(5).with_jitter(0.5),
+/// );
+/// let sink = FanoutSink::new()
+///     .with_target("local", FileSink::new("saves/world.json"))
+///     .with_target("backup", backup);
+/// ```
+pub struct RetrySink<W> {
+    inner: W,
+    policy: RetryPolicy,
+}
+
+impl<W> RetrySink<W> {
+    pub fn new(inner: W, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+}
+
+impl<R, W> IoWriter<R> for RetrySink<W>
+where
+    R: Clone + Send + Sync + 'static,
+    W: IoWriter<R>,
+{
+    async fn init(&mut self) -> SinkResult {
+        let mut attempts = Attempts::new(self.policy);
+        loop {
+            let Err(e) = self.inner.init().await else {
+                return Ok(());
+            };
+            if !attempts.retry("init", &e).await {
+                return Err(e);
+            }
+        }
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let mut attempts = Attempts::new(self.policy);
+        loop {
+            let Err(e) = self.inner.write(data.clone()).await else {
+                return Ok(());
+            };
+            if !attempts.retry("write", &e).await {
+                return Err(e);
+            }
+        }
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        let mut attempts = Attempts::new(self.policy);
+        loop {
+            let Err(e) = self.inner.flush().await else {
+                return Ok(());
+            };
+            if !attempts.retry("flush", &e).await {
+                return Err(e);
+            }
+        }
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        self.inner.close().await
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.inner.last_write_len()
+    }
+
+    fn last_write_changed(&self) -> Option<u64> {
+        self.inner.last_write_changed()
+    }
+
+    fn capabilities(&self) -> WriterCapabilities {
+        self.inner.capabilities()
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        self.inner.wipe().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_by_the_multiplier() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn backoff_is_capped_at_any_attempt() {
+        let policy = RetryPolicy {
+            multiplier: 1e30,
+            ..Default::default()
+        };
+        for attempt in [6, 64, 1_000, i32::MAX as u32 + 1, u32::MAX] {
+            assert_eq!(policy.backoff(attempt), policy.max_backoff, "{attempt}");
+        }
+    }
+
+    #[test]
+    fn backoff_never_shrinks_below_the_initial_delay() {
+        let policy = RetryPolicy {
+            multiplier: 0.5,
+            ..Default::default()
+        };
+        assert_eq!(policy.backoff(10), policy.initial_backoff);
+    }
+
+    #[test]
+    fn jitter_only_takes_time_off() {
+        let policy = RetryPolicy::default().with_jitter(1.0);
+        for attempt in 0..20 {
+            assert!(policy.backoff(attempt) <= RetryPolicy::default().backoff(attempt));
+        }
+    }
+
+    #[test]
+    fn jitter_out_of_range_is_clamped() {
+        for jitter in [2.0, -1.0, f32::INFINITY, f32::NAN] {
+            let policy = RetryPolicy {
+                jitter,
+                ..Default::default()
+            };
+            for attempt in 0..20 {
+                assert!(policy.backoff(attempt) <= policy.max_backoff, "{jitter}");
+            }
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use crate::{codec, IoSinkError, IoWriter, Migrations, RetryPolicy, SaveFormat, SinkResult};
+use async_std::task::sleep;
+use bevy::prelude::*;
+pub use s3::{creds::Credentials, Bucket, Region};
+use s3::{error::S3Error, serde_types::Part};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{future::Future, marker::PhantomData};
+
+const CONTENT_TYPE: &str = "application/json";
+
+/// S3 rejects multipart parts smaller than this, except the last one.
+const MIN_PART_SIZE: usize = 5 * 1024 * 1024;
+
+fn s3_error(key: &str, e: S3Error) -> IoSinkError {
+    IoSinkError::Other(format!("s3 object {key}: {e}"))
+}
+
+/// Server errors and throttling, which the same request can get past later.
+fn is_transient(e: &S3Error) -> bool {
+    matches!(e, S3Error::HttpFailWithBody(status, _) if *status >= 500 || *status == 429)
+}
+
+/// Run `request` again after transient failures, up to `policy.max_retries` times.
+async fn with_retries<T, F, Fut>(policy: &RetryPolicy, mut request: F) -> Result<T, S3Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, S3Error>>,
+{
+    let mut attempt = 0;
+    loop {
+        match request().await {
+            Err(e) if is_transient(&e) && attempt < policy.max_retries => {
+                sleep(policy.backoff(attempt)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// `bytes` split into multipart parts of `part_size`, numbered from 1 as S3 expects.
+fn parts(bytes: &[u8], part_size: usize) -> impl Iterator<Item = (u32, &[u8])> {
+    (1..).zip(bytes.chunks(part_size))
+}
+
+/// Download and decode the object an [`S3Sink`] stored at `key`, `None` if there is none.
+/// Objects don't carry a schema version, so there are no migrations to apply.
+pub async fn load_s3<R>(bucket: &Bucket, key: &str) -> Result<Option<R>, IoSinkError>
+where
+    R: DeserializeOwned,
+{
+    match bucket.get_object(key).await {
+        Ok(response) => codec::decode(response.bytes(), &Migrations::default()).map(Some),
+        Err(S3Error::HttpFailWithBody(404, _)) => Ok(None),
+        Err(e) => Err(s3_error(key, e)),
+    }
+}
+
+/// Uploads every message as an object to an S3-compatible bucket, replacing the previous
+/// one, e.g. for dedicated servers keeping player state in object storage. Payloads larger
+/// than the part size, 8 MiB by default, are sent as a multipart upload.
+///
+/// Requests failing with a 5xx status or throttled with 429 are retried by the sink itself
+/// according to [`with_retry`](Self::with_retry), so a large upload only resends the part
+/// that failed. Any other failure is returned to the sink's own
+/// [`RetryPolicy`](crate::RetryPolicy).
+///
+/// ```ignore
+/// let bucket = Bucket::new(
+///     "player-state",
+///     Region::Custom { region: "eu-central-1".into(), endpoint: "https://s3.example.com".into() },
+///     Credentials::from_env()?,
+/// )?
+/// .with_path_style();
+/// app.add_plugins(IoSinkPlugin::<PlayerState, _>::new(
+///     S3Sink::new(bucket, format!("players/{player_id}.json")),
+/// ));
+/// ```
+pub struct S3Sink<R> {
+    bucket: Box<Bucket>,
+    key: String,
+    part_size: usize,
+    retry: RetryPolicy,
+    format: SaveFormat,
+    max_message_size: Option<u64>,
+    last_contents: Option<Vec<u8>>,
+    last_write_len: Option<u64>,
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R> S3Sink<R> {
+    pub fn new(bucket: Box<Bucket>, key: impl Into<String>) -> Self {
+        Self {
+            bucket,
+            key: key.into(),
+            part_size: 8 * 1024 * 1024,
+            retry: RetryPolicy::default(),
+            format: SaveFormat::default(),
+            max_message_size: None,
+            last_contents: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Split payloads larger than `bytes` into parts of that size, at least 5 MiB.
+    pub fn with_part_size(mut self, bytes: usize) -> Self {
+        self.part_size = bytes.max(MIN_PART_SIZE);
+        self
+    }
+
+    /// How single requests failing with a 5xx status are retried.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Reject encoded messages larger than `bytes` with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+
+    async fn put(&self, bytes: &[u8]) -> Result<(), S3Error> {
+        with_retries(&self.retry, || {
+            self.bucket
+                .put_object_with_content_type(&self.key, bytes, CONTENT_TYPE)
+        })
+        .await?;
+        Ok(())
+    }
+
+    async fn put_multipart(&self, bytes: &[u8]) -> Result<(), S3Error> {
+        let upload = with_retries(&self.retry, || {
+            self.bucket
+                .initiate_multipart_upload(&self.key, CONTENT_TYPE)
+        })
+        .await?;
+
+        let mut parts: Vec<Part> = Vec::new();
+        for (number, chunk) in parts(bytes, self.part_size) {
+            let part = with_retries(&self.retry, || {
+                self.bucket.put_multipart_chunk(
+                    chunk.to_vec(),
+                    &self.key,
+                    number,
+                    &upload.upload_id,
+                    CONTENT_TYPE,
+                )
+            })
+            .await;
+            match part {
+                Ok(part) => parts.push(part),
+                Err(e) => {
+                    // Otherwise the uploaded parts are kept, and billed, until a lifecycle
+                    // rule removes them.
+                    if let Err(abort) = self.bucket.abort_upload(&self.key, &upload.upload_id).await
+                    {
+                        warn!("failed to abort upload of {}: {abort}", self.key);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        with_retries(&self.retry, || {
+            self.bucket
+                .complete_multipart_upload(&self.key, &upload.upload_id, parts.clone())
+        })
+        .await?;
+        Ok(())
+    }
+}
+
+impl<R> IoWriter<R> for S3Sink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        self.last_contents = None;
+        Ok(())
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let bytes = self.format.encode(&data)?;
+        IoSinkError::check_size(bytes.len(), self.max_message_size)?;
+        // Change detection fires on any `ResMut` deref, so identical payloads are common.
+        if self.last_contents.as_deref() == Some(bytes.as_slice()) {
+            self.last_write_len = Some(0);
+            return Ok(());
+        }
+
+        let uploaded = if bytes.len() > self.part_size {
+            self.put_multipart(&bytes).await
+        } else {
+            self.put(&bytes).await
+        };
+        uploaded.map_err(|e| s3_error(&self.key, e))?;
+        self.last_write_len = Some(bytes.len() as u64);
+        self.last_contents = Some(bytes);
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        with_retries(&self.retry, || self.bucket.delete_object(&self.key))
+            .await
+            .map_err(|e| s3_error(&self.key, e))?;
+        self.last_contents = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_lite::future::block_on;
+    use std::time::Duration;
+
+    fn status(code: u16) -> S3Error {
+        S3Error::HttpFailWithBody(code, String::new())
+    }
+
+    fn no_backoff(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            initial_backoff: Duration::ZERO,
+            ..RetryPolicy::new(max_retries)
+        }
+    }
+
+    /// Calls made by `with_retries` to a request failing with `failures` in order.
+    fn calls(policy: &RetryPolicy, failures: &[u16]) -> (usize, Result<(), S3Error>) {
+        let mut calls = 0;
+        let result = block_on(with_retries(policy, || {
+            let failure = failures.get(calls).copied();
+            calls += 1;
+            async move { failure.map_or(Ok(()), |code| Err(status(code))) }
+        }));
+        (calls, result)
+    }
+
+    #[test]
+    fn server_errors_and_throttling_are_transient() {
+        for code in [500, 502, 503, 429] {
+            assert!(is_transient(&status(code)), "{code}");
+        }
+        for code in [400, 403, 404, 412] {
+            assert!(!is_transient(&status(code)), "{code}");
+        }
+    }
+
+    #[test]
+    fn transient_failures_are_retried() {
+        let (calls, result) = calls(&no_backoff(3), &[503, 429]);
+        assert_eq!(calls, 3);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn retries_stop_at_the_policy_limit() {
+        let (calls, result) = calls(&no_backoff(2), &[500, 500, 500, 500]);
+        assert_eq!(calls, 3);
+        assert!(matches!(result, Err(S3Error::HttpFailWithBody(500, _))));
+    }
+
+    #[test]
+    fn other_failures_are_not_retried() {
+        let (calls, result) = calls(&no_backoff(3), &[403]);
+        assert_eq!(calls, 1);
+        assert!(matches!(result, Err(S3Error::HttpFailWithBody(403, _))));
+    }
+
+    #[test]
+    fn parts_are_numbered_from_one_with_a_short_last_part() {
+        let bytes: Vec<u8> = (0..25).collect();
+        let sizes: Vec<_> = parts(&bytes, 10).map(|(n, part)| (n, part.len())).collect();
+        assert_eq!(sizes, [(1, 10), (2, 10), (3, 5)]);
+        assert_eq!(parts(&bytes, 25).count(), 1);
+    }
+}
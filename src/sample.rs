@@ -0,0 +1,120 @@
+use crate::{Filter, FilterSink};
+use bevy::platform::time::Instant;
+use std::time::Duration;
+
+/// Writes only a sample of the messages it gets, e.g. for a high-frequency telemetry
+/// resource synced on every change when storage should only keep some of them, see
+/// [`Sample`]. Built with [`IoWriterExt::sample_every`](crate::IoWriterExt::sample_every) or
+/// [`IoWriterExt::sample_interval`](crate::IoWriterExt::sample_interval).
+///
+/// ```ignore
+/// let sink = UdpSink::new("127.0.0.1:9000").sample_interval(Duration::from_secs(1));
+/// app.add_plugins(IoSinkPlugin::<FrameStats, _>::new(sink).with_tag(SinkTag::Telemetry));
+/// ```
+pub type SampleSink<W> = FilterSink<W, Sample>;
+
+/// Accepts every `n`th message, at most one per interval, or both. The first message is
+/// always accepted.
+#[derive(Debug, Clone)]
+pub struct Sample {
+    every: u64,
+    min_interval: Option<Duration>,
+    /// Messages received since the last one accepted.
+    since_accepted: Option<u64>,
+    last_accepted_at: Option<Instant>,
+}
+
+impl Default for Sample {
+    /// Accepts everything until configured.
+    fn default() -> Self {
+        Self {
+            every: 1,
+            min_interval: None,
+            since_accepted: None,
+            last_accepted_at: None,
+        }
+    }
+}
+
+impl Sample {
+    /// Accept one message out of every `n`.
+    pub fn every(n: u64) -> Self {
+        Self::default().with_every(n)
+    }
+
+    /// Accept at most one message per `interval`.
+    pub fn interval(interval: Duration) -> Self {
+        Self::default().with_min_interval(interval)
+    }
+
+    pub fn with_every(mut self, n: u64) -> Self {
+        self.every = n.max(1);
+        self
+    }
+
+    /// Drop messages arriving less than `interval` after the last one accepted.
+    pub fn with_min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = Some(interval);
+        self
+    }
+
+    fn sampled(&mut self) -> bool {
+        let count = self.since_accepted.map_or(self.every, |n| n + 1);
+        let waited = match (self.min_interval, self.last_accepted_at) {
+            (Some(interval), Some(at)) => at.elapsed() >= interval,
+            _ => true,
+        };
+        if count < self.every || !waited {
+            self.since_accepted = Some(count);
+            return false;
+        }
+        self.since_accepted = Some(0);
+        self.last_accepted_at = Some(Instant::now());
+        true
+    }
+}
+
+impl<R> Filter<R> for Sample {
+    fn accept(&mut self, _data: &R) -> bool {
+        self.sampled()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn accepted(sample: &mut Sample, messages: usize) -> Vec<bool> {
+        (0..messages).map(|_| sample.sampled()).collect()
+    }
+
+    #[test]
+    fn default_accepts_everything() {
+        assert_eq!(accepted(&mut Sample::default(), 3), [true; 3]);
+    }
+
+    #[test]
+    fn every_nth_starts_with_the_first() {
+        let mut sample = Sample::every(3);
+        assert_eq!(
+            accepted(&mut sample, 7),
+            [true, false, false, true, false, false, true]
+        );
+    }
+
+    #[test]
+    fn interval_drops_messages_until_it_elapsed() {
+        let mut sample = Sample::interval(Duration::from_millis(20));
+        assert_eq!(accepted(&mut sample, 3), [true, false, false]);
+        std::thread::sleep(Duration::from_millis(25));
+        assert_eq!(accepted(&mut sample, 2), [true, false]);
+    }
+
+    #[test]
+    fn every_and_interval_both_apply() {
+        let mut sample = Sample::every(2).with_min_interval(Duration::from_millis(20));
+        assert_eq!(accepted(&mut sample, 2), [true, false]);
+        std::thread::sleep(Duration::from_millis(25));
+        assert_eq!(accepted(&mut sample, 2), [true, false]);
+    }
+}
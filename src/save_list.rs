@@ -0,0 +1,180 @@
+use crate::{read_save_metadata, IoSinkError, SaveMetadata};
+use async_channel::{unbounded, Receiver, Sender};
+use async_std::path::{Path, PathBuf};
+use bevy::{prelude::*, tasks::IoTaskPool};
+use futures_lite::StreamExt;
+use std::{cmp::Reverse, io::ErrorKind, time::SystemTime};
+
+/// A save found by a [`SaveListPlugin`] scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveEntry {
+    pub path: PathBuf,
+    /// File name without the extension.
+    pub name: String,
+    pub size: u64,
+    /// `None` where the platform doesn't record it.
+    pub modified: Option<SystemTime>,
+    /// `None` if the save was written without [`FileSinkPlugin::with_metadata`] or its
+    /// metadata is unreadable.
+    ///
+    /// [`FileSinkPlugin::with_metadata`]: crate::FileSinkPlugin::with_metadata
+    pub metadata: Option<SaveMetadata>,
+}
+
+/// The saves found by the latest [`SaveListPlugin`] scan, most recently modified first.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct SaveList {
+    pub entries: Vec<SaveEntry>,
+    /// Scans requested and not completed yet.
+    pending: usize,
+}
+
+impl SaveList {
+    /// A scan was requested and hasn't completed yet, e.g. to show a spinner.
+    pub fn is_scanning(&self) -> bool {
+        self.pending > 0
+    }
+}
+
+/// Trigger to scan the save directory again: `commands.trigger(ScanSaves)`.
+#[derive(Event, Debug, Default, Clone, Copy)]
+pub struct ScanSaves;
+
+/// Emitted when a scan completed and [`SaveList`] was replaced.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SaveListUpdated;
+
+/// Scan every save in `dir` with the given extension, without decoding the saves.
+pub async fn scan_saves(dir: &Path, extension: &str) -> Result<Vec<SaveEntry>, IoSinkError> {
+    let mut entries = match async_fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut saves = Vec::new();
+    while let Some(entry) = entries.next().await {
+        let entry = entry?;
+        let path = PathBuf::from(entry.path());
+        if !path.extension().is_some_and(|ext| ext == extension) {
+            continue;
+        }
+        let file = entry.metadata().await?;
+        if !file.is_file() {
+            continue;
+        }
+        let metadata = read_save_metadata(&path).await.unwrap_or_else(|e| {
+            warn!("ignoring the metadata of {}: {e}", path.display());
+            None
+        });
+        saves.push(SaveEntry {
+            name: path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            size: file.len(),
+            modified: file.modified().ok(),
+            metadata,
+            path,
+        });
+    }
+    saves.sort_by_key(|save| Reverse(save.modified));
+    Ok(saves)
+}
+
+#[derive(Resource)]
+struct SaveScanner {
+    dir: PathBuf,
+    extension: String,
+    tx: Sender<Result<Vec<SaveEntry>, IoSinkError>>,
+    rx: Receiver<Result<Vec<SaveEntry>, IoSinkError>>,
+}
+
+/// Keeps a [`SaveList`] of the saves in a directory for load game menus, scanned on the IO
+/// task at startup and on [`ScanSaves`].
+///
+/// ```ignore
+/// app.add_plugins(SaveListPlugin::new("saves"));
+///
+/// fn open_load_menu(mut commands: Commands) {
+///     commands.trigger(ScanSaves);
+/// }
+/// ```
+pub struct SaveListPlugin {
+    dir: PathBuf,
+    extension: String,
+}
+
+impl SaveListPlugin {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            extension: "json".into(),
+        }
+    }
+
+    /// Only list files with this extension, `json` by default.
+    pub fn with_extension(mut self, extension: impl Into<String>) -> Self {
+        self.extension = extension.into();
+        self
+    }
+}
+
+impl Plugin for SaveListPlugin {
+    fn build(&self, app: &mut App) {
+        let (tx, rx) = unbounded();
+        app.insert_resource(SaveScanner {
+            dir: self.dir.clone(),
+            extension: self.extension.clone(),
+            tx,
+            rx,
+        });
+        app.init_resource::<SaveList>();
+        app.add_event::<SaveListUpdated>();
+        app.add_observer(
+            |_: Trigger<ScanSaves>, scanner: Res<SaveScanner>, list: ResMut<SaveList>| {
+                spawn_scan(&scanner, list);
+            },
+        );
+        app.add_systems(
+            Startup,
+            |scanner: Res<SaveScanner>, list: ResMut<SaveList>| {
+                spawn_scan(&scanner, list);
+            },
+        );
+        app.add_systems(PreUpdate, receive_save_list);
+    }
+}
+
+fn spawn_scan(scanner: &SaveScanner, mut list: ResMut<SaveList>) {
+    list.pending += 1;
+    let (dir, extension, tx) = (
+        scanner.dir.clone(),
+        scanner.extension.clone(),
+        scanner.tx.clone(),
+    );
+    IoTaskPool::get()
+        .spawn(async move {
+            let _ = tx.send(scan_saves(&dir, &extension).await).await;
+        })
+        .detach();
+}
+
+fn receive_save_list(
+    scanner: Res<SaveScanner>,
+    mut list: ResMut<SaveList>,
+    mut updated: EventWriter<SaveListUpdated>,
+) {
+    let mut latest = None;
+    while let Ok(result) = scanner.rx.try_recv() {
+        list.pending = list.pending.saturating_sub(1);
+        match result {
+            Ok(entries) => latest = Some(entries),
+            Err(e) => error!("failed to scan {}: {e}", scanner.dir.display()),
+        }
+    }
+    // Only the latest scan matters when several completed since the last frame.
+    if let Some(entries) = latest {
+        list.entries = entries;
+        updated.write(SaveListUpdated);
+    }
+}
@@ -0,0 +1,98 @@
+use crate::{EnqueueError, IoSender};
+use bevy::{ecs::system::SystemParam, prelude::*};
+use std::ops::{Deref, DerefMut};
+
+/// Mutable access to `R` together with its [`IoSender<R>`].
+///
+/// ```ignore
+/// fn move_player(mut state: Saver<PlayerState>) {
+///     state.pos += Vec2::X;
+///     let _ = state.save();
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct Saver<'w, R: Resource> {
+    res: ResMut<'w, R>,
+    sender: Res<'w, IoSender<R>>,
+}
+
+impl<R: Resource + Clone> Saver<'_, R> {
+    /// Queue a copy of the current value.
+    pub fn save(&self) -> Result<(), EnqueueError<R>> {
+        self.sender.enqueue(self.res.clone())
+    }
+
+    /// Queue `f(&value)` instead of the value itself, e.g. to strip transient fields.
+    pub fn save_with(&self, f: impl FnOnce(&R) -> R) -> Result<(), EnqueueError<R>> {
+        self.sender.enqueue(f(&self.res))
+    }
+}
+
+impl<R: Resource> Deref for Saver<'_, R> {
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        &self.res
+    }
+}
+
+impl<R: Resource> DerefMut for Saver<'_, R> {
+    fn deref_mut(&mut self) -> &mut R {
+        &mut self.res
+    }
+}
+
+/// Owns an `R` and queues it for saving when dropped, including on early returns and while
+/// unwinding from a panic.
+///
+/// ```ignore
+/// fn settle_trade(sender: Res<IoSender<Inventory>>, inventory: Res<Inventory>) {
+///     let mut outcome = PersistGuard::new(inventory.clone(), &sender);
+///     let Some(item) = outcome.take_offer() else {
+///         return; // saved here
+///     };
+///     outcome.add(item);
+/// } // and here
+/// ```
+pub struct PersistGuard<R: Resource> {
+    value: Option<R>,
+    sender: IoSender<R>,
+}
+
+impl<R: Resource> PersistGuard<R> {
+    pub fn new(value: R, sender: &IoSender<R>) -> Self {
+        Self {
+            value: Some(value),
+            sender: sender.clone(),
+        }
+    }
+
+    /// Take the value back without saving it.
+    pub fn dismiss(mut self) -> R {
+        self.value.take().expect("value is only taken once")
+    }
+}
+
+impl<R: Resource> Deref for PersistGuard<R> {
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        self.value.as_ref().expect("value is only taken once")
+    }
+}
+
+impl<R: Resource> DerefMut for PersistGuard<R> {
+    fn deref_mut(&mut self) -> &mut R {
+        self.value.as_mut().expect("value is only taken once")
+    }
+}
+
+impl<R: Resource> Drop for PersistGuard<R> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            if let Err(err) = self.sender.enqueue(value) {
+                error!("{err}");
+            }
+        }
+    }
+}
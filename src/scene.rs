@@ -0,0 +1,252 @@
+use crate::{
+    ComponentSnapshot, IoSender, IoSinkError, IoSinkPlugin, IoSinks, IoWriter, PersistId,
+    PersistedComponent, SinkResult, WriterCapabilities,
+};
+use async_std::path::PathBuf;
+use bevy::{
+    ecs::query::QueryFilter,
+    prelude::*,
+    reflect::{FromReflect, PartialReflect, TypePath},
+    scene::{ron, serde::SceneDeserializer, DynamicEntity, DynamicSceneBuilder, SceneFilter},
+};
+use serde::de::DeserializeSeed;
+use std::{marker::PhantomData, time::Duration};
+
+const DEFAULT_SCENE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A world snapshot sent through the sink of a [`SceneSinkPlugin`].
+#[derive(Resource)]
+pub struct SceneSnapshot(pub DynamicScene);
+
+/// Writes [`SceneSnapshot`]s to a `.scn.ron` file using the app's type registry. Serializing
+/// happens on the sink task, not in the frame.
+pub struct SceneSink {
+    path: PathBuf,
+    registry: AppTypeRegistry,
+    last_write_len: Option<u64>,
+}
+
+impl SceneSink {
+    pub fn new(path: impl Into<PathBuf>, registry: AppTypeRegistry) -> Self {
+        Self {
+            path: path.into(),
+            registry,
+            last_write_len: None,
+        }
+    }
+}
+
+impl IoWriter<SceneSnapshot> for SceneSink {
+    async fn init(&mut self) -> SinkResult {
+        if let Some(parent) = self.path.parent() {
+            async_fs::create_dir_all(parent).await?;
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, snapshot: SceneSnapshot) -> SinkResult {
+        let ron = scene_to_ron(&snapshot.0, &self.registry)?;
+        // Staged next to the scene, so a crash mid-write leaves the previous one intact.
+        let mut staged = self.path.as_os_str().to_owned();
+        staged.push(".tmp");
+        async_fs::write(&staged, ron.as_bytes()).await?;
+        async_fs::rename(&staged, &self.path).await?;
+        self.last_write_len = Some(ron.len() as u64);
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+
+    fn capabilities(&self) -> WriterCapabilities {
+        WriterCapabilities {
+            atomic_rename: true,
+            ..default()
+        }
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        match async_fs::remove_file(&self.path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Encode `scene` in the standard `.scn.ron` format read by Bevy's scene loader and editors.
+pub fn scene_to_ron(
+    scene: &DynamicScene,
+    registry: &AppTypeRegistry,
+) -> Result<String, IoSinkError> {
+    scene
+        .serialize(&registry.read())
+        .map_err(IoSinkError::serialization)
+}
+
+/// Decode a `.scn.ron` scene, every type in it must be registered.
+pub fn scene_from_ron(ron: &str, registry: &AppTypeRegistry) -> Result<DynamicScene, IoSinkError> {
+    let registry = registry.read();
+    let mut deserializer =
+        ron::Deserializer::from_str(ron).map_err(IoSinkError::deserialization)?;
+    SceneDeserializer {
+        type_registry: &registry,
+    }
+    .deserialize(&mut deserializer)
+    .map_err(IoSinkError::deserialization)
+}
+
+impl<C> ComponentSnapshot<C>
+where
+    C: Component + FromReflect + TypePath + Clone,
+{
+    /// One scene entity per persisted entity, holding its [`PersistId`] and `C`. `C` must be
+    /// registered for the scene to be serialized.
+    pub fn to_scene(&self) -> DynamicScene {
+        let entities = self
+            .entities
+            .iter()
+            .enumerate()
+            .map(|(index, persisted)| DynamicEntity {
+                entity: persisted
+                    .entity
+                    .unwrap_or_else(|| Entity::from_raw(index as u32)),
+                components: vec![
+                    Box::new(persisted.id) as Box<dyn PartialReflect>,
+                    Box::new(persisted.component.clone()),
+                ],
+            })
+            .collect();
+        DynamicScene {
+            resources: Vec::new(),
+            entities,
+        }
+    }
+
+    /// The entities of `scene` that have both a [`PersistId`] and `C`, the others are skipped.
+    pub fn from_scene(scene: &DynamicScene) -> Self {
+        let mut entities: Vec<_> = scene
+            .entities
+            .iter()
+            .filter_map(|entity| {
+                let id = find_component::<PersistId>(entity)?;
+                let component = find_component::<C>(entity)?;
+                Some(PersistedComponent {
+                    id,
+                    entity: Some(entity.entity),
+                    component,
+                })
+            })
+            .collect();
+        entities.sort_by_key(|persisted| persisted.id);
+        Self { entities }
+    }
+}
+
+fn find_component<T: FromReflect + TypePath>(entity: &DynamicEntity) -> Option<T> {
+    entity
+        .components
+        .iter()
+        .find(|component| component.represents::<T>())
+        .and_then(|component| T::from_reflect(component.as_ref()))
+}
+
+/// Periodically snapshots the entities matching `F` into a [`DynamicScene`] and persists it
+/// with a [`SceneSink`]. Only reflected, registered components are saved.
+///
+/// ```ignore
+/// app.add_plugins(SceneSinkPlugin::<With<Enemy>>::new("world.scn.ron"));
+/// ```
+pub struct SceneSinkPlugin<F = With<PersistId>> {
+    path: PathBuf,
+    interval: Duration,
+    components: SceneFilter,
+    _marker: PhantomData<fn() -> F>,
+}
+
+impl<F> SceneSinkPlugin<F> {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            interval: DEFAULT_SCENE_INTERVAL,
+            components: SceneFilter::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// How often to snapshot, 30 seconds by default.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Only save the components allowed here. All reflected components are saved by default.
+    pub fn allow_component<T: Component>(mut self) -> Self {
+        self.components = self.components.allow::<T>();
+        self
+    }
+
+    pub fn deny_component<T: Component>(mut self) -> Self {
+        self.components = self.components.deny::<T>();
+        self
+    }
+}
+
+#[derive(Resource)]
+struct SceneSnapshotConfig<F> {
+    timer: Timer,
+    components: SceneFilter,
+    _marker: PhantomData<fn() -> F>,
+}
+
+impl<F> Plugin for SceneSinkPlugin<F>
+where
+    F: QueryFilter + 'static,
+{
+    fn build(&self, app: &mut App) {
+        let registry = app
+            .world_mut()
+            .get_resource_or_init::<AppTypeRegistry>()
+            .clone();
+        registry.write().register::<PersistId>();
+        app.add_plugins(IoSinkPlugin::<SceneSnapshot, _>::new(SceneSink::new(
+            self.path.clone(),
+            registry,
+        )));
+        app.world_mut()
+            .resource_mut::<IoSinks>()
+            .set_path::<SceneSnapshot>(self.path.clone());
+
+        app.insert_resource(SceneSnapshotConfig::<F> {
+            timer: Timer::new(self.interval, TimerMode::Repeating),
+            components: self.components.clone(),
+            _marker: PhantomData,
+        });
+        app.add_systems(Update, snapshot_scene::<F>);
+    }
+}
+
+fn snapshot_scene<F: QueryFilter + 'static>(world: &mut World) {
+    let delta = world.resource::<Time>().delta();
+    let components = {
+        let mut config = world.resource_mut::<SceneSnapshotConfig<F>>();
+        if !config.timer.tick(delta).just_finished() {
+            return;
+        }
+        config.components.clone()
+    };
+
+    let entities: Vec<Entity> = world.query_filtered::<Entity, F>().iter(world).collect();
+    let scene = DynamicSceneBuilder::from_world(world)
+        .with_component_filter(components)
+        .deny_all_resources()
+        .extract_entities(entities.into_iter())
+        .build();
+
+    if let Err(err) = world
+        .resource::<IoSender<SceneSnapshot>>()
+        .enqueue(SceneSnapshot(scene))
+    {
+        error!("{err}");
+    }
+}
@@ -0,0 +1,647 @@
+use crate::{
+    codec,
+    envelope::VersionStamp,
+    load::{self, LoadOutcome, LoadResult, LoadSource},
+    ready::LoadTrackerPlugin,
+    vfs::{StorageFs, Vfs},
+    IoSender, IoSinkError, IoSinkPlugin, IoSinks, IoWriter, LoadFailed, LoadSet, LoadTracker,
+    Migrations, SaveFormat, SinkResult, UnreadableSavePolicy,
+};
+use async_channel::{unbounded, Receiver, Sender};
+use async_std::path::{Path, PathBuf};
+use bevy::{prelude::*, tasks::IoTaskPool};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{io::ErrorKind, marker::PhantomData, sync::Arc};
+
+const SLOT_EXTENSION: &str = "json";
+const DEFAULT_SLOT: &str = "slot1";
+
+/// What happened to a slot of a [`SaveSlots<R>`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlotChange {
+    Created(String),
+    Deleted(String),
+    Renamed {
+        from: String,
+        to: String,
+    },
+    /// `R` now holds the save of `to`, or `R::default()` if the slot had none. An unreadable
+    /// save is handled according to the [`UnreadableSavePolicy`] and also emits a
+    /// [`LoadFailed<R>`].
+    Switched {
+        from: Option<String>,
+        to: String,
+    },
+}
+
+/// Emitted when a [`SaveSlots<R>`] operation succeeded.
+#[derive(Event, Debug)]
+pub struct SlotChanged<R> {
+    pub change: SlotChange,
+    _marker: PhantomData<fn() -> R>,
+}
+
+/// Emitted when a [`SaveSlots<R>`] operation failed, nothing was changed.
+#[derive(Event, Debug)]
+pub struct SlotFailed<R> {
+    pub slot: String,
+    pub error: IoSinkError,
+    _marker: PhantomData<fn() -> R>,
+}
+
+enum SlotOp {
+    Refresh,
+    Create(String),
+    Delete(String),
+    Rename { from: String, to: String },
+    Switch(String),
+}
+
+enum SlotOutcome<R> {
+    Listed(Vec<String>),
+    Changed(SlotChange),
+    Switched {
+        from: Option<String>,
+        to: String,
+        loaded: LoadResult<R>,
+    },
+    Failed {
+        slot: String,
+        error: IoSinkError,
+    },
+}
+
+/// The save slots of `R`, each stored as `<dir>/<slot>.json`. Operations run on the IO task
+/// in the order they were requested, their results arrive as [`SlotChanged<R>`] and
+/// [`SlotFailed<R>`] events.
+///
+/// Changes to `R` are saved to the active slot. The active slot can't be deleted or renamed,
+/// switch to another one first.
+#[derive(Resource)]
+pub struct SaveSlots<R> {
+    slots: Vec<String>,
+    active: Option<String>,
+    ops: Sender<SlotOp>,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R> SaveSlots<R> {
+    /// The slots found in the directory, sorted by name. Up to date once the operations
+    /// requested so far have completed.
+    pub fn slots(&self) -> &[String] {
+        &self.slots
+    }
+
+    /// The slot `R` was loaded from and is saved to, `None` until the first switch completed.
+    pub fn active(&self) -> Option<&str> {
+        self.active.as_deref()
+    }
+
+    /// Scan the directory again, e.g. after saves were copied in by hand.
+    pub fn refresh(&self) {
+        self.request(SlotOp::Refresh);
+    }
+
+    /// Create `slot` holding `R::default()`, failing if it exists.
+    pub fn create(&self, slot: impl Into<String>) {
+        self.request(SlotOp::Create(slot.into()));
+    }
+
+    pub fn delete(&self, slot: impl Into<String>) {
+        self.request(SlotOp::Delete(slot.into()));
+    }
+
+    /// Rename `from` to `to`, failing if `to` exists.
+    pub fn rename(&self, from: impl Into<String>, to: impl Into<String>) {
+        self.request(SlotOp::Rename {
+            from: from.into(),
+            to: to.into(),
+        });
+    }
+
+    /// Load `slot` into `R` and save to it from then on. Saves requested before the switch
+    /// completes still go to the previous slot.
+    pub fn switch(&self, slot: impl Into<String>) {
+        self.request(SlotOp::Switch(slot.into()));
+    }
+
+    fn request(&self, op: SlotOp) {
+        if self.ops.try_send(op).is_err() {
+            error!(
+                "the save slots of {} are closed",
+                std::any::type_name::<R>()
+            );
+        }
+    }
+}
+
+/// A value of `R` bound for one slot, sent through the sink of a [`SaveSlotsPlugin<R>`].
+#[derive(Debug, Clone)]
+pub struct SlotRecord<R> {
+    pub slot: String,
+    pub value: R,
+}
+
+/// Writes each [`SlotRecord`] to the file of its slot.
+struct SlotSink<R> {
+    dir: PathBuf,
+    vfs: Arc<dyn Vfs>,
+    format: SaveFormat,
+    stamp: VersionStamp,
+    /// Slot and payload of the last write, the envelope differs on every write.
+    last: Option<(String, Vec<u8>)>,
+    last_write_len: Option<u64>,
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R> IoWriter<SlotRecord<R>> for SlotSink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn write(&mut self, record: SlotRecord<R>) -> SinkResult {
+        let payload = self.format.encode(&record.value)?;
+        let unchanged = self
+            .last
+            .as_ref()
+            .is_some_and(|(slot, last)| *slot == record.slot && *last == payload);
+        if unchanged {
+            self.last_write_len = Some(0);
+            return Ok(());
+        }
+        let path = slot_path(&self.dir, &record.slot)?;
+        let bytes = self.stamp.encode(self.format, &record.value)?;
+        self.vfs.write(&path, &bytes).await?;
+        self.last_write_len = Some(bytes.len() as u64);
+        self.last = Some((record.slot, payload));
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+}
+
+/// The file of `slot`, failing if the name could reach outside of `dir`.
+fn slot_path(dir: &Path, slot: &str) -> Result<PathBuf, IoSinkError> {
+    check_name(slot)?;
+    Ok(dir.join(format!("{slot}.{SLOT_EXTENSION}")))
+}
+
+fn check_name(slot: &str) -> SinkResult {
+    let valid = !slot.is_empty()
+        && !slot.starts_with('.')
+        && !slot.contains(['/', '\\', ':'])
+        && slot.trim() == slot;
+    if valid {
+        Ok(())
+    } else {
+        Err(IoSinkError::Other(format!(
+            "`{slot}` is not a valid slot name"
+        )))
+    }
+}
+
+async fn list_slots(vfs: &dyn Vfs, dir: &Path) -> Result<Vec<String>, IoSinkError> {
+    let paths = match vfs.list(dir).await {
+        Ok(paths) => paths,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut slots = Vec::new();
+    for path in paths {
+        if path.extension().is_some_and(|ext| ext == SLOT_EXTENSION) {
+            if let Some(slot) = path.file_stem() {
+                slots.push(slot.to_string_lossy().into_owned());
+            }
+        }
+    }
+    slots.sort();
+    Ok(slots)
+}
+
+/// Applies the requested operations one at a time, in order.
+struct SlotWorker<R> {
+    dir: PathBuf,
+    vfs: Arc<dyn Vfs>,
+    format: SaveFormat,
+    stamp: VersionStamp,
+    migrations: Migrations,
+    unreadable: UnreadableSavePolicy,
+    active: Option<String>,
+    outcomes: Sender<SlotOutcome<R>>,
+}
+
+impl<R> SlotWorker<R>
+where
+    R: Serialize + DeserializeOwned + Default,
+{
+    async fn run(mut self, ops: Receiver<SlotOp>) {
+        while let Ok(op) = ops.recv().await {
+            let outcome = self.apply(op).await;
+            if self.outcomes.send(outcome).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    async fn apply(&mut self, op: SlotOp) -> SlotOutcome<R> {
+        let slot = match &op {
+            SlotOp::Refresh => String::new(),
+            SlotOp::Create(slot) | SlotOp::Delete(slot) | SlotOp::Switch(slot) => slot.clone(),
+            SlotOp::Rename { from, .. } => from.clone(),
+        };
+        match self.try_apply(op).await {
+            Ok(outcome) => outcome,
+            Err(error) => SlotOutcome::Failed { slot, error },
+        }
+    }
+
+    async fn try_apply(&mut self, op: SlotOp) -> Result<SlotOutcome<R>, IoSinkError> {
+        match op {
+            SlotOp::Refresh => list_slots(&*self.vfs, &self.dir)
+                .await
+                .map(SlotOutcome::Listed),
+            SlotOp::Create(slot) => {
+                let path = slot_path(&self.dir, &slot)?;
+                if self.vfs.exists(&path).await? {
+                    return Err(IoSinkError::Other(format!("slot `{slot}` already exists")));
+                }
+                let bytes = self.stamp.encode(self.format, &R::default())?;
+                self.vfs.write(&path, &bytes).await?;
+                Ok(SlotOutcome::Changed(SlotChange::Created(slot)))
+            }
+            SlotOp::Delete(slot) => {
+                self.check_inactive(&slot)?;
+                self.vfs.remove(&slot_path(&self.dir, &slot)?).await?;
+                Ok(SlotOutcome::Changed(SlotChange::Deleted(slot)))
+            }
+            SlotOp::Rename { from, to } => {
+                self.check_inactive(&from)?;
+                let source = slot_path(&self.dir, &from)?;
+                let target = slot_path(&self.dir, &to)?;
+                if self.vfs.exists(&target).await? {
+                    return Err(IoSinkError::Other(format!("slot `{to}` already exists")));
+                }
+                self.vfs.rename(&source, &target).await?;
+                Ok(SlotOutcome::Changed(SlotChange::Renamed { from, to }))
+            }
+            SlotOp::Switch(slot) => {
+                let loaded = self.load(&slot_path(&self.dir, &slot)?).await;
+                let from = self.active.replace(slot.clone());
+                Ok(SlotOutcome::Switched {
+                    from,
+                    to: slot,
+                    loaded,
+                })
+            }
+        }
+    }
+
+    /// Read the save at `path`, `R::default()` if there is none and according to the
+    /// [`UnreadableSavePolicy`] if it can't be read or decoded.
+    async fn load(&self, path: &Path) -> LoadResult<R> {
+        let error = match self.vfs.read(path).await {
+            Ok(bytes) if bytes.is_empty() => None,
+            Ok(bytes) => match codec::decode(&bytes, &self.migrations) {
+                Ok(value) => {
+                    return LoadResult {
+                        value: Some(value),
+                        outcome: LoadOutcome::Loaded(LoadSource::File),
+                    }
+                }
+                Err(e) => Some(e),
+            },
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => Some(e.into()),
+        };
+        match error {
+            Some(error) => load::load_unreadable(&*self.vfs, path, error, self.unreadable).await,
+            None => LoadResult {
+                value: Some(R::default()),
+                outcome: LoadOutcome::Loaded(LoadSource::Missing),
+            },
+        }
+    }
+
+    fn check_inactive(&self, slot: &str) -> SinkResult {
+        if self.active.as_deref() == Some(slot) {
+            return Err(IoSinkError::Other(format!(
+                "slot `{slot}` is active, switch to another slot first"
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+struct SlotReceiver<R> {
+    outcomes: Receiver<SlotOutcome<R>>,
+    unreadable: UnreadableSavePolicy,
+}
+
+/// Manages several saves of `R` in one directory through a [`SaveSlots<R>`] resource, e.g.
+/// for a load game menu. `R` is loaded from the initial slot at startup.
+///
+/// ```ignore
+/// app.add_plugins(SaveSlotsPlugin::<World>::new("saves/world"));
+///
+/// fn load_slot(slots: Res<SaveSlots<World>>) {
+///     slots.switch("slot2");
+/// }
+/// ```
+pub struct SaveSlotsPlugin<R> {
+    dir: PathBuf,
+    initial: String,
+    format: SaveFormat,
+    migrations: Migrations,
+    unreadable: UnreadableSavePolicy,
+    vfs: Arc<dyn Vfs>,
+    _phantom: PhantomData<fn() -> R>,
+}
+
+impl<R> SaveSlotsPlugin<R> {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            initial: DEFAULT_SLOT.into(),
+            format: SaveFormat::default(),
+            migrations: Migrations::default(),
+            unreadable: UnreadableSavePolicy::default(),
+            vfs: Arc::new(StorageFs),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The slot loaded at startup, `slot1` by default.
+    pub fn with_initial_slot(mut self, slot: impl Into<String>) -> Self {
+        self.initial = slot.into();
+        self
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Upgrade older saves when switching to their slot.
+    pub fn with_migrations(mut self, migrations: Migrations) -> Self {
+        self.migrations = migrations;
+        self
+    }
+
+    /// What to do when the save of the slot switched to can't be read or decoded, the
+    /// initial slot included.
+    pub fn with_unreadable_save(mut self, policy: UnreadableSavePolicy) -> Self {
+        self.unreadable = policy;
+        self
+    }
+
+    /// Read and write the slots through `vfs` instead of the platform's storage, e.g. a
+    /// [`MemoryFs`](crate::MemoryFs) in tests. Listing the slots needs [`Vfs::list`].
+    pub fn with_vfs(mut self, vfs: impl Vfs) -> Self {
+        self.vfs = Arc::new(vfs);
+        self
+    }
+}
+
+impl<R> Plugin for SaveSlotsPlugin<R>
+where
+    R: Resource + Clone + Serialize + DeserializeOwned + Default,
+{
+    fn build(&self, app: &mut App) {
+        let stamp = VersionStamp::new(app, &self.migrations);
+        let sink = SlotSink::<R> {
+            dir: self.dir.clone(),
+            vfs: self.vfs.clone(),
+            format: self.format,
+            stamp: stamp.clone(),
+            last: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        };
+        app.add_plugins(IoSinkPlugin::<SlotRecord<R>, _>::new(sink));
+        app.world_mut()
+            .resource_mut::<IoSinks>()
+            .set_path::<SlotRecord<R>>(self.dir.clone());
+
+        if !app.is_plugin_added::<LoadTrackerPlugin>() {
+            app.add_plugins(LoadTrackerPlugin);
+        }
+        app.world_mut()
+            .resource_mut::<LoadTracker>()
+            .register::<R>();
+
+        let (ops_tx, ops_rx) = unbounded();
+        let (outcomes_tx, outcomes_rx) = unbounded();
+        // Queued now, applied once the worker starts.
+        let _ = ops_tx.try_send(SlotOp::Refresh);
+        let _ = ops_tx.try_send(SlotOp::Switch(self.initial.clone()));
+        app.insert_resource(SaveSlots::<R> {
+            slots: Vec::new(),
+            active: None,
+            ops: ops_tx,
+            _marker: PhantomData,
+        });
+        app.insert_resource(SlotReceiver::<R> {
+            outcomes: outcomes_rx,
+            unreadable: self.unreadable,
+        });
+        app.add_event::<SlotChanged<R>>();
+        app.add_event::<SlotFailed<R>>();
+        app.add_event::<LoadFailed<R>>();
+
+        app.add_systems(PreUpdate, receive_slot_outcomes::<R>.in_set(LoadSet));
+        // Never before the first switch, the default would overwrite the initial slot.
+        app.add_systems(
+            Update,
+            save_active_slot::<R>.run_if(
+                resource_exists_and_changed::<R>
+                    .and(|tracker: Res<LoadTracker>| tracker.is_loaded::<R>()),
+            ),
+        );
+
+        let worker = SlotWorker::<R> {
+            dir: self.dir.clone(),
+            vfs: self.vfs.clone(),
+            format: self.format,
+            stamp,
+            migrations: self.migrations.clone(),
+            unreadable: self.unreadable,
+            active: None,
+            outcomes: outcomes_tx,
+        };
+        let mut worker = Some((worker, ops_rx));
+        app.add_systems(Startup, move || {
+            if let Some((worker, ops)) = worker.take() {
+                IoTaskPool::get().spawn(worker.run(ops)).detach();
+            }
+        });
+    }
+}
+
+fn receive_slot_outcomes<R>(
+    mut commands: Commands,
+    receiver: Res<SlotReceiver<R>>,
+    mut slots: ResMut<SaveSlots<R>>,
+    mut tracker: ResMut<LoadTracker>,
+    mut changed: EventWriter<SlotChanged<R>>,
+    mut failed: EventWriter<SlotFailed<R>>,
+    mut unreadable: EventWriter<LoadFailed<R>>,
+) where
+    R: Resource,
+{
+    while let Ok(outcome) = receiver.outcomes.try_recv() {
+        let change = match outcome {
+            SlotOutcome::Listed(listed) => {
+                slots.slots = listed;
+                continue;
+            }
+            SlotOutcome::Failed { slot, error } => {
+                error!(
+                    "save slot `{slot}` of {}: {error}",
+                    std::any::type_name::<R>()
+                );
+                failed.write(SlotFailed {
+                    slot,
+                    error,
+                    _marker: PhantomData,
+                });
+                continue;
+            }
+            SlotOutcome::Changed(change) => change,
+            SlotOutcome::Switched { from, to, loaded } => {
+                if let LoadOutcome::Failed { error, backup } = loaded.outcome {
+                    if receiver.unreadable == UnreadableSavePolicy::Panic {
+                        panic!(
+                            "save slot `{to}` of {} is unreadable: {error}",
+                            std::any::type_name::<R>()
+                        );
+                    }
+                    unreadable.write(LoadFailed::new(error, backup));
+                }
+                // Left out, the value of the previous slot would overwrite this one.
+                match loaded.value {
+                    Some(value) => commands.insert_resource(value),
+                    None => commands.remove_resource::<R>(),
+                }
+                slots.active = Some(to.clone());
+                tracker.mark_loaded::<R>();
+                SlotChange::Switched { from, to }
+            }
+        };
+        match &change {
+            SlotChange::Created(slot) => slots.slots.push(slot.clone()),
+            SlotChange::Deleted(slot) => slots.slots.retain(|s| s != slot),
+            SlotChange::Renamed { from, to } => {
+                slots.slots.retain(|s| s != from);
+                slots.slots.push(to.clone());
+            }
+            SlotChange::Switched { to, .. } => {
+                if !slots.slots.contains(to) {
+                    slots.slots.push(to.clone());
+                }
+            }
+        }
+        slots.slots.sort();
+        changed.write(SlotChanged {
+            change,
+            _marker: PhantomData,
+        });
+    }
+}
+
+fn save_active_slot<R>(sender: Res<IoSender<SlotRecord<R>>>, slots: Res<SaveSlots<R>>, res: Res<R>)
+where
+    R: Resource + Clone,
+{
+    let Some(slot) = slots.active.clone() else {
+        return;
+    };
+    if let Err(err) = sender.enqueue(SlotRecord {
+        slot,
+        value: res.clone(),
+    }) {
+        error!("{err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryFs;
+    use futures_lite::future::block_on;
+    use serde::Deserialize;
+    use serde_json::Value;
+
+    #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct Level {
+        level: u64,
+    }
+
+    fn slots(migrations: Migrations) -> (SlotSink<Level>, SlotWorker<Level>) {
+        let vfs: Arc<dyn Vfs> = Arc::new(MemoryFs::default());
+        let stamp = VersionStamp::new(&mut App::new(), &migrations);
+        let sink = SlotSink {
+            dir: "saves".into(),
+            vfs: vfs.clone(),
+            format: SaveFormat::default(),
+            stamp: stamp.clone(),
+            last: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        };
+        let worker = SlotWorker {
+            dir: "saves".into(),
+            vfs,
+            format: SaveFormat::default(),
+            stamp,
+            migrations,
+            unreadable: UnreadableSavePolicy::default(),
+            active: None,
+            outcomes: unbounded().0,
+        };
+        (sink, worker)
+    }
+
+    fn switch(worker: &mut SlotWorker<Level>, slot: &str) -> Level {
+        match block_on(worker.try_apply(SlotOp::Switch(slot.into()))) {
+            Ok(SlotOutcome::Switched { loaded, .. }) => loaded.value.unwrap(),
+            _ => panic!("switching to `{slot}` failed"),
+        }
+    }
+
+    #[test]
+    fn saves_round_trip_without_migrating_again() {
+        // Not idempotent, a save migrated twice would come back with a higher level.
+        let migrations = Migrations::new().then(|mut payload: Value| {
+            payload["level"] = (payload["level"].as_u64().unwrap_or(0) + 1).into();
+            Ok(payload)
+        });
+        let (mut sink, mut worker) = slots(migrations);
+
+        assert_eq!(switch(&mut worker, "a"), Level::default());
+        let record = SlotRecord {
+            slot: "a".into(),
+            value: Level { level: 7 },
+        };
+        block_on(sink.write(record)).unwrap();
+        block_on(worker.try_apply(SlotOp::Create("b".into()))).unwrap();
+        assert_eq!(switch(&mut worker, "b"), Level::default());
+        assert_eq!(switch(&mut worker, "a"), Level { level: 7 });
+    }
+
+    #[test]
+    fn slot_names_stay_inside_the_directory() {
+        for slot in [
+            "", ".hidden", "..", "../../x", "a/b", "a\\b", "C:x", " x", "x ",
+        ] {
+            assert!(slot_path(Path::new("saves"), slot).is_err(), "{slot:?}");
+        }
+    }
+
+    #[test]
+    fn plain_slot_names_are_accepted() {
+        let path = slot_path(Path::new("saves"), "slot 1").unwrap();
+        assert_eq!(path, Path::new("saves").join("slot 1.json"));
+    }
+}
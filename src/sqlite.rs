@@ -0,0 +1,468 @@
+use crate::{
+    codec, envelope::VersionStamp, ready::LoadTrackerPlugin, IoSender, IoSinkError, IoSinkPlugin,
+    IoWriter, LoadSet, LoadTracker, Migrations, SaveFormat, SinkResult,
+};
+use async_channel::{unbounded, Receiver};
+use async_std::task::spawn_blocking;
+use bevy::{prelude::*, tasks::IoTaskPool};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS resources (
+        key TEXT PRIMARY KEY NOT NULL,
+        saved_at_ms INTEGER NOT NULL,
+        data BLOB NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS resource_history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        key TEXT NOT NULL,
+        saved_at_ms INTEGER NOT NULL,
+        data BLOB NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS resource_history_key ON resource_history (key, id);
+";
+
+fn sqlite_error(e: rusqlite::Error) -> IoSinkError {
+    IoSinkError::Other(format!("sqlite: {e}"))
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+fn open(path: &Path) -> SinkResult<Connection> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let connection = Connection::open(path).map_err(sqlite_error)?;
+    // Concurrent readers, e.g. the loader or tools, don't block the sink.
+    connection
+        .pragma_update_and_check(None, "journal_mode", "WAL", |_| Ok(()))
+        .map_err(sqlite_error)?;
+    connection.execute_batch(SCHEMA).map_err(sqlite_error)?;
+    Ok(connection)
+}
+
+/// Read the latest row a [`SqliteSink`] stored under `key`, `None` if the database or the
+/// row doesn't exist. Only rows stored by a [`SqlitePlugin`] carry their schema version, pass
+/// [`Migrations::default`] for those of a bare sink.
+pub async fn load_sqlite<R>(
+    path: impl Into<PathBuf>,
+    key: impl Into<String>,
+    migrations: &Migrations,
+) -> Result<Option<R>, IoSinkError>
+where
+    R: DeserializeOwned,
+{
+    let (path, key) = (path.into(), key.into());
+    let bytes = spawn_blocking(move || -> SinkResult<Option<Vec<u8>>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let connection = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(sqlite_error)?;
+        let has_table = connection
+            .query_row(
+                "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'resources'",
+                [],
+                |_| Ok(()),
+            )
+            .optional()
+            .map_err(sqlite_error)?
+            .is_some();
+        if !has_table {
+            return Ok(None);
+        }
+        connection
+            .query_row(
+                "SELECT data FROM resources WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sqlite_error)
+    })
+    .await?;
+    bytes
+        .map(|bytes| codec::decode(&bytes, migrations))
+        .transpose()
+}
+
+/// Stores each `R` as a row of an SQLite database, keyed by the type path of `R` unless set
+/// with [`with_key`](Self::with_key), so any number of resources share one file. The
+/// `resources` table holds the latest value of every key, with
+/// [`with_history`](Self::with_history) every write is also appended to `resource_history`
+/// along with its time.
+///
+/// ```ignore
+/// app.add_plugins(IoSinkPlugin::<MatchStats, _>::new(
+///     SqliteSink::new("saves/game.db").with_history(true),
+/// ));
+/// ```
+pub struct SqliteSink<R> {
+    path: PathBuf,
+    key: String,
+    history: bool,
+    format: SaveFormat,
+    stamp: Option<VersionStamp>,
+    max_message_size: Option<u64>,
+    /// `Connection` isn't `Sync`, the lock is never contended as only the sink task uses it.
+    connection: Option<Mutex<Connection>>,
+    /// Payload of the last write, without the envelope.
+    last_contents: Option<Vec<u8>>,
+    last_write_len: Option<u64>,
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R> SqliteSink<R> {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            key: std::any::type_name::<R>().into(),
+            history: false,
+            format: SaveFormat::default(),
+            stamp: None,
+            max_message_size: None,
+            connection: None,
+            last_contents: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Store rows in a versioned envelope, so the loader only migrates older ones.
+    pub(crate) fn stamped(mut self, stamp: VersionStamp) -> Self {
+        self.stamp = Some(stamp);
+        self
+    }
+
+    /// Row key of this resource, which must be unique within the database.
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = key.into();
+        self
+    }
+
+    /// Also append every write to the `resource_history` table.
+    pub fn with_history(mut self, history: bool) -> Self {
+        self.history = history;
+        self
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Reject encoded messages larger than `bytes` with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+
+    /// Run `f` on the connection without blocking the sink task's executor thread.
+    async fn with_connection<T, F>(&mut self, f: F) -> SinkResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> SinkResult<T> + Send + 'static,
+    {
+        let mut connection = self
+            .connection
+            .take()
+            .ok_or(IoSinkError::NotInitialized)?
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        let (connection, result) = spawn_blocking(move || {
+            let result = f(&mut connection);
+            (connection, result)
+        })
+        .await;
+        self.connection = Some(Mutex::new(connection));
+        result
+    }
+}
+
+impl<R> IoWriter<R> for SqliteSink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        let path = self.path.clone();
+        let connection = spawn_blocking(move || open(&path)).await?;
+        self.connection = Some(Mutex::new(connection));
+        self.last_contents = None;
+        Ok(())
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let payload = self.format.encode(&data)?;
+        // Change detection fires on any `ResMut` deref, so identical payloads are common.
+        if self.last_contents.as_deref() == Some(payload.as_slice()) {
+            self.last_write_len = Some(0);
+            return Ok(());
+        }
+        let bytes = match &self.stamp {
+            Some(stamp) => stamp.encode(self.format, &data)?,
+            None => payload.clone(),
+        };
+        IoSinkError::check_size(bytes.len(), self.max_message_size)?;
+
+        let (key, history, len) = (self.key.clone(), self.history, bytes.len() as u64);
+        self.with_connection(move |connection| {
+            let saved_at_ms = now_ms();
+            let transaction = connection.transaction().map_err(sqlite_error)?;
+            transaction
+                .execute(
+                    "INSERT INTO resources (key, saved_at_ms, data) VALUES (?1, ?2, ?3)
+                         ON CONFLICT (key) DO UPDATE
+                         SET saved_at_ms = excluded.saved_at_ms, data = excluded.data",
+                    params![key, saved_at_ms, bytes],
+                )
+                .map_err(sqlite_error)?;
+            if history {
+                transaction
+                    .execute(
+                        "INSERT INTO resource_history (key, saved_at_ms, data)
+                             VALUES (?1, ?2, ?3)",
+                        params![key, saved_at_ms, bytes],
+                    )
+                    .map_err(sqlite_error)?;
+            }
+            transaction.commit().map_err(sqlite_error)
+        })
+        .await?;
+        self.last_contents = Some(payload);
+        self.last_write_len = Some(len);
+        Ok(())
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        if let Some(connection) = self.connection.take() {
+            let connection = connection
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner);
+            spawn_blocking(move || connection.close().map_err(|(_, e)| sqlite_error(e))).await?;
+        }
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        let key = self.key.clone();
+        self.with_connection(move |connection| {
+            connection
+                .execute("DELETE FROM resources WHERE key = ?1", params![key])
+                .map_err(sqlite_error)?;
+            connection
+                .execute("DELETE FROM resource_history WHERE key = ?1", params![key])
+                .map_err(sqlite_error)?;
+            Ok(())
+        })
+        .await?;
+        self.last_contents = None;
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+struct SqliteLoadReceiver<R>(Receiver<Option<R>>);
+
+/// Persists the resource `R` as a row of an SQLite database: the latest row is loaded at
+/// startup, like a [`FileSinkPlugin`](crate::FileSinkPlugin) save, then rewritten on every
+/// change. `R::default()` is inserted when there is no row or it can't be read.
+///
+/// ```ignore
+/// app.add_plugins((
+///     SqlitePlugin::<Settings>::new("saves/game.db"),
+///     SqlitePlugin::<Progress>::new("saves/game.db").with_history(true),
+/// ));
+/// ```
+pub struct SqlitePlugin<R> {
+    path: PathBuf,
+    key: String,
+    history: bool,
+    migrations: Migrations,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R> SqlitePlugin<R> {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            key: std::any::type_name::<R>().into(),
+            history: false,
+            migrations: Migrations::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Row key of this resource, the type path of `R` by default.
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = key.into();
+        self
+    }
+
+    /// Also append every save to the `resource_history` table.
+    pub fn with_history(mut self, history: bool) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Upgrade rows stored by older builds while loading. Rows are stored with the current
+    /// version of `migrations`.
+    pub fn with_migrations(mut self, migrations: Migrations) -> Self {
+        self.migrations = migrations;
+        self
+    }
+}
+
+impl<R> Plugin for SqlitePlugin<R>
+where
+    R: Resource + Clone + Serialize + DeserializeOwned + Default,
+{
+    fn build(&self, app: &mut App) {
+        let stamp = VersionStamp::new(app, &self.migrations);
+        app.add_plugins(IoSinkPlugin::<R, _>::new(
+            SqliteSink::new(self.path.clone())
+                .with_key(self.key.clone())
+                .with_history(self.history)
+                .stamped(stamp),
+        ));
+        if !app.is_plugin_added::<LoadTrackerPlugin>() {
+            app.add_plugins(LoadTrackerPlugin);
+        }
+        app.world_mut()
+            .resource_mut::<LoadTracker>()
+            .register::<R>();
+
+        let (tx, rx) = unbounded();
+        app.insert_resource(SqliteLoadReceiver::<R>(rx));
+        let (path, key, migrations) =
+            (self.path.clone(), self.key.clone(), self.migrations.clone());
+        app.add_systems(Startup, move || {
+            let (path, key, migrations, tx) =
+                (path.clone(), key.clone(), migrations.clone(), tx.clone());
+            IoTaskPool::get()
+                .spawn(async move {
+                    let loaded = load_sqlite::<R>(path.clone(), key.clone(), &migrations)
+                        .await
+                        .unwrap_or_else(|e| {
+                            error!("failed to load {key} from {}: {e}", path.display());
+                            None
+                        });
+                    let _ = tx.send(loaded).await;
+                })
+                .detach();
+        });
+        app.add_systems(PreUpdate, receive_sqlite_load::<R>.in_set(LoadSet));
+        app.add_systems(
+            PostUpdate,
+            sync_sqlite::<R>.run_if(resource_exists_and_changed::<R>),
+        );
+    }
+}
+
+fn receive_sqlite_load<R: Resource + Default>(
+    mut commands: Commands,
+    receiver: Res<SqliteLoadReceiver<R>>,
+    mut tracker: ResMut<LoadTracker>,
+) {
+    if let Ok(loaded) = receiver.0.try_recv() {
+        commands.insert_resource(loaded.unwrap_or_default());
+        tracker.mark_loaded::<R>();
+    }
+}
+
+fn sync_sqlite<R: Resource + Clone>(sender: Res<IoSender<R>>, res: Res<R>) {
+    if let Err(err) = sender.enqueue(res.clone()) {
+        error!("{err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_lite::future::block_on;
+
+    /// A fresh database path for `test`, in the temp directory.
+    fn db_path(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bevy_io_sink_sqlite_{test}"));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join("game.db")
+    }
+
+    fn history(path: &Path, key: &str) -> Vec<u32> {
+        let connection = Connection::open(path).unwrap();
+        let mut statement = connection
+            .prepare("SELECT data FROM resource_history WHERE key = ?1 ORDER BY id")
+            .unwrap();
+        let rows = statement
+            .query_map(params![key], |row| row.get::<_, Vec<u8>>(0))
+            .unwrap();
+        rows.map(|data| serde_json::from_slice(&data.unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn loads_the_latest_row() {
+        let path = db_path("latest");
+        let mut sink = SqliteSink::<u32>::new(&path).with_key("score");
+        block_on(async {
+            sink.init().await.unwrap();
+            for score in [1, 2, 3] {
+                sink.write(score).await.unwrap();
+            }
+            sink.close().await.unwrap();
+        });
+
+        let loaded = block_on(load_sqlite::<u32>(&path, "score", &Migrations::default()));
+        assert_eq!(loaded.unwrap(), Some(3));
+        assert!(history(&path, "score").is_empty());
+    }
+
+    #[test]
+    fn history_keeps_every_changed_write() {
+        let path = db_path("history");
+        let mut sink = SqliteSink::<u32>::new(&path)
+            .with_key("score")
+            .with_history(true);
+        block_on(async {
+            sink.init().await.unwrap();
+            for score in [1, 2, 2, 3] {
+                sink.write(score).await.unwrap();
+            }
+            sink.close().await.unwrap();
+        });
+
+        assert_eq!(history(&path, "score"), [1, 2, 3]);
+        let loaded = block_on(load_sqlite::<u32>(&path, "score", &Migrations::default()));
+        assert_eq!(loaded.unwrap(), Some(3));
+    }
+
+    #[test]
+    fn missing_databases_and_keys_load_nothing() {
+        let path = db_path("missing");
+        let loaded = block_on(load_sqlite::<u32>(&path, "score", &Migrations::default()));
+        assert_eq!(loaded.unwrap(), None);
+
+        let mut sink = SqliteSink::<u32>::new(&path).with_key("score");
+        block_on(async {
+            sink.init().await.unwrap();
+            sink.write(1).await.unwrap();
+            sink.close().await.unwrap();
+        });
+        let loaded = block_on(load_sqlite::<u32>(&path, "level", &Migrations::default()));
+        assert_eq!(loaded.unwrap(), None);
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{
+    load, ready::LoadTrackerPlugin, FileSink, IoSender, IoSinkPlugin, IoSinks, LoadSet, LoadTracker,
+};
+use async_channel::{unbounded, Receiver};
+use async_std::path::PathBuf;
+use bevy::{prelude::*, state::state::FreelyMutableState, tasks::IoTaskPool};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::marker::PhantomData;
+
+/// The last entered `S`, sent through the sink of a [`StatePersistPlugin`].
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PersistedState<S>(pub S);
+
+/// Saves `S` whenever it changes and restores it through [`NextState`] at startup, e.g. the
+/// last open menu tab. The state itself must be added with `init_state` or `insert_state`.
+///
+/// ```ignore
+/// app.init_state::<MenuTab>()
+///     .add_plugins(StatePersistPlugin::<MenuTab>::new("saves/menu_tab.json"));
+/// ```
+pub struct StatePersistPlugin<S> {
+    path: PathBuf,
+    _marker: PhantomData<fn() -> S>,
+}
+
+impl<S> StatePersistPlugin<S> {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[derive(Resource)]
+struct StateReceiver<S>(Receiver<Option<S>>);
+
+impl<S> Plugin for StatePersistPlugin<S>
+where
+    S: FreelyMutableState + Serialize + DeserializeOwned,
+{
+    fn build(&self, app: &mut App) {
+        let sink = FileSink::<PersistedState<S>>::new(self.path.clone());
+        app.add_plugins(IoSinkPlugin::<PersistedState<S>, _>::new(sink));
+        app.world_mut()
+            .resource_mut::<IoSinks>()
+            .set_path::<PersistedState<S>>(self.path.clone());
+
+        if !app.is_plugin_added::<LoadTrackerPlugin>() {
+            app.add_plugins(LoadTrackerPlugin);
+        }
+        app.world_mut()
+            .resource_mut::<LoadTracker>()
+            .register::<PersistedState<S>>();
+
+        let (tx, rx) = unbounded();
+        app.insert_resource(StateReceiver::<S>(rx));
+        app.add_systems(PreUpdate, restore_state::<S>.in_set(LoadSet));
+        // Never before the load, the initial state would overwrite the saved one.
+        app.add_systems(
+            Update,
+            save_state::<S>.run_if(
+                state_changed::<S>
+                    .and(|tracker: Res<LoadTracker>| tracker.is_loaded::<PersistedState<S>>()),
+            ),
+        );
+
+        let path = self.path.clone();
+        app.add_systems(Startup, move || {
+            let path = path.clone();
+            let tx = tx.clone();
+            IoTaskPool::get()
+                .spawn(async move {
+                    let state = load::read_save::<S>(&path).await.unwrap_or_else(|e| {
+                        error!("state save {} is unreadable: {e}", path.display());
+                        None
+                    });
+                    let _ = tx.send(state).await;
+                })
+                .detach();
+        });
+    }
+}
+
+fn restore_state<S>(
+    receiver: Res<StateReceiver<S>>,
+    mut next: ResMut<NextState<S>>,
+    mut tracker: ResMut<LoadTracker>,
+) where
+    S: FreelyMutableState,
+{
+    let Ok(state) = receiver.0.try_recv() else {
+        return;
+    };
+    if let Some(state) = state {
+        next.set(state);
+    }
+    tracker.mark_loaded::<PersistedState<S>>();
+}
+
+fn save_state<S>(sender: Res<IoSender<PersistedState<S>>>, state: Res<State<S>>)
+where
+    S: FreelyMutableState,
+{
+    if let Err(err) = sender.enqueue(PersistedState(state.get().clone())) {
+        error!("{err}");
+    }
+}
@@ -0,0 +1,189 @@
+use crate::IoSender;
+use bevy::{platform::time::Instant, prelude::*};
+use std::{
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// How often a sink task signals liveness, and how long without progress counts as a stall.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeartbeatConfig {
+    pub interval: Duration,
+    pub stall_after: Duration,
+}
+
+impl Default for HeartbeatConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            stall_after: Duration::from_secs(5),
+        }
+    }
+}
+
+/// State shared between a sink's [`IoSender`] and its task.
+pub(crate) struct SinkShared {
+    pub(crate) heartbeat: HeartbeatConfig,
+    epoch: Instant,
+    /// Milliseconds since `epoch` at which the task last made progress.
+    last_progress_ms: AtomicU64,
+    written: AtomicU64,
+    dropped: AtomicU64,
+    /// Only counted for writes that report both figures.
+    bytes_written: AtomicU64,
+    bytes_changed: AtomicU64,
+    /// Nanoseconds, `0` until the first write completes.
+    last_write_nanos: AtomicU64,
+    /// Set once the task has exited, nothing reads the channel anymore.
+    dead: AtomicBool,
+}
+
+impl SinkShared {
+    pub(crate) fn new(heartbeat: HeartbeatConfig) -> Self {
+        Self {
+            heartbeat,
+            epoch: Instant::now(),
+            last_progress_ms: AtomicU64::new(0),
+            written: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            bytes_written: AtomicU64::new(0),
+            bytes_changed: AtomicU64::new(0),
+            last_write_nanos: AtomicU64::new(0),
+            dead: AtomicBool::new(false),
+        }
+    }
+
+    /// Called by the task whenever it wakes up, be it for a message or a heartbeat tick.
+    pub(crate) fn beat(&self) {
+        let now = self.epoch.elapsed().as_millis() as u64;
+        self.last_progress_ms.store(now, Ordering::Relaxed);
+    }
+
+    pub(crate) fn since_last_progress(&self) -> Duration {
+        let last = Duration::from_millis(self.last_progress_ms.load(Ordering::Relaxed));
+        self.epoch.elapsed().saturating_sub(last)
+    }
+
+    pub(crate) fn is_dead(&self) -> bool {
+        self.dead.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn record_write(
+        &self,
+        duration: Duration,
+        bytes: Option<u64>,
+        changed: Option<u64>,
+    ) {
+        self.written.fetch_add(1, Ordering::Relaxed);
+        if let (Some(bytes), Some(changed)) = (bytes, changed) {
+            self.bytes_written.fetch_add(bytes, Ordering::Relaxed);
+            self.bytes_changed.fetch_add(changed, Ordering::Relaxed);
+        }
+        self.last_write_nanos
+            .store(duration.as_nanos().max(1) as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_dropped(&self, count: u64) {
+        self.dropped.fetch_add(count, Ordering::Relaxed);
+    }
+}
+
+/// Marks the sink dead when dropped, including when the task unwinds from a panic.
+pub(crate) struct DeadOnDrop(pub(crate) Arc<SinkShared>);
+
+impl Drop for DeadOnDrop {
+    fn drop(&mut self) {
+        self.0.dead.store(true, Ordering::Release);
+    }
+}
+
+/// Queue and throughput figures for the sink of `R`, refreshed every frame in [`PreUpdate`].
+#[derive(Resource, Debug)]
+pub struct IoSinkStats<R> {
+    /// Messages waiting in the channel.
+    pub queue_len: usize,
+    /// Messages successfully written since startup.
+    pub written: u64,
+    /// Messages discarded by the overflow policy, a purge or a closed telemetry gate.
+    pub dropped: u64,
+    pub last_write_duration: Option<Duration>,
+    /// The sink task has exited, further sends fail with [`EnqueueError::SinkDead`](crate::EnqueueError::SinkDead).
+    pub failed: bool,
+    /// Time since the task last signalled liveness, either by handling a message or by its
+    /// heartbeat.
+    pub since_last_progress: Duration,
+    /// No progress for longer than [`HeartbeatConfig::stall_after`].
+    pub stalled: bool,
+    /// Bytes written, for writers that report [`IoWriter::last_write_changed`](crate::IoWriter::last_write_changed).
+    pub bytes_written: u64,
+    /// Of `bytes_written`, those that differed from the previous state.
+    pub bytes_changed: u64,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R> IoSinkStats<R> {
+    /// Bytes written per byte that actually changed. A high value means most of each write
+    /// rewrites unchanged data, and a delta or sharded layout would save IO.
+    pub fn write_amplification(&self) -> Option<f64> {
+        (self.bytes_changed > 0).then(|| self.bytes_written as f64 / self.bytes_changed as f64)
+    }
+}
+
+impl<R> Default for IoSinkStats<R> {
+    fn default() -> Self {
+        Self {
+            queue_len: 0,
+            written: 0,
+            dropped: 0,
+            last_write_duration: None,
+            failed: false,
+            since_last_progress: Duration::ZERO,
+            stalled: false,
+            bytes_written: 0,
+            bytes_changed: 0,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Emitted once when the sink of `R` stops making progress without having failed, e.g. a
+/// write blocked on an unresponsive disk. Use it to trigger watchdog or failover logic.
+#[derive(Event, Debug)]
+pub struct SinkStalled<R> {
+    pub since_last_progress: Duration,
+    _marker: PhantomData<fn() -> R>,
+}
+
+pub(crate) fn update_sink_stats<R>(
+    sender: Res<IoSender<R>>,
+    mut stats: ResMut<IoSinkStats<R>>,
+    mut stalled: EventWriter<SinkStalled<R>>,
+) where
+    R: Send + Sync + 'static,
+{
+    let shared = sender.shared();
+    let last_write_nanos = shared.last_write_nanos.load(Ordering::Relaxed);
+
+    stats.queue_len = sender.len();
+    stats.written = shared.written.load(Ordering::Relaxed);
+    stats.dropped = shared.dropped.load(Ordering::Relaxed);
+    stats.bytes_written = shared.bytes_written.load(Ordering::Relaxed);
+    stats.bytes_changed = shared.bytes_changed.load(Ordering::Relaxed);
+    stats.last_write_duration =
+        (last_write_nanos > 0).then(|| Duration::from_nanos(last_write_nanos));
+    stats.failed = shared.is_dead();
+    stats.since_last_progress = shared.since_last_progress();
+
+    let is_stalled = !stats.failed && stats.since_last_progress > shared.heartbeat.stall_after;
+    if is_stalled && !stats.stalled {
+        stalled.write(SinkStalled {
+            since_last_progress: stats.since_last_progress,
+            _marker: PhantomData,
+        });
+    }
+    stats.stalled = is_stalled;
+}
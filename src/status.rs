@@ -0,0 +1,35 @@
+use crate::SinkErrorKind;
+use bevy::prelude::*;
+use std::marker::PhantomData;
+
+/// Lifecycle of a sink task.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SinkState {
+    /// The task hasn't finished `init` yet.
+    #[default]
+    Initializing,
+    /// Waiting for messages.
+    Idle,
+    Writing,
+    /// The last operation failed, cleared by the next successful write.
+    Errored(SinkErrorKind),
+    /// The task has exited.
+    Closed,
+}
+
+/// Current [`SinkState`] of the sink for `R`, for save indicators and health dashboards.
+#[derive(Resource, Debug, Deref)]
+pub struct SinkStatus<R> {
+    #[deref]
+    pub state: SinkState,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R> Default for SinkStatus<R> {
+    fn default() -> Self {
+        Self {
+            state: SinkState::default(),
+            _marker: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use crate::{IoSinkError, IoWriter, SinkResult};
+use async_std::task::spawn_blocking;
+use serde::Serialize;
+use std::{
+    io::{self, Write},
+    marker::PhantomData,
+};
+
+/// Standard stream a [`StdStreamSink`] writes to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StdStream {
+    #[default]
+    Stdout,
+    Stderr,
+}
+
+/// Writes one JSON record per line to stdout or stderr, like a `JournalSink` without the
+/// file, so headless tools and CI runs can capture persisted state by piping the output.
+///
+/// The stream is locked for the whole line, so records never interleave with each other or
+/// with `println!` output.
+///
+/// ```ignore
+/// app.add_plugins(IoSinkPlugin::<MatchResult, _>::new(StdStreamSink::stdout()));
+/// ```
+pub struct StdStreamSink<R> {
+    stream: StdStream,
+    max_message_size: Option<u64>,
+    last_write_len: Option<u64>,
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R> StdStreamSink<R> {
+    pub fn new(stream: StdStream) -> Self {
+        Self {
+            stream,
+            max_message_size: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn stdout() -> Self {
+        Self::new(StdStream::Stdout)
+    }
+
+    pub fn stderr() -> Self {
+        Self::new(StdStream::Stderr)
+    }
+
+    /// Reject records larger than `bytes` with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+}
+
+fn write_line(stream: StdStream, line: &[u8]) -> io::Result<()> {
+    match stream {
+        StdStream::Stdout => {
+            let mut out = io::stdout().lock();
+            out.write_all(line)?;
+            out.flush()
+        }
+        StdStream::Stderr => io::stderr().lock().write_all(line),
+    }
+}
+
+impl<R> IoWriter<R> for StdStreamSink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn write(&mut self, data: R) -> SinkResult {
+        let mut line = serde_json::to_vec(&data).map_err(IoSinkError::serialization)?;
+        line.push(b'\n');
+        IoSinkError::check_size(line.len(), self.max_message_size)?;
+        let (stream, len) = (self.stream, line.len() as u64);
+        // A full pipe blocks the writer, keep it off the sink task's executor thread.
+        spawn_blocking(move || write_line(stream, &line)).await?;
+        self.last_write_len = Some(len);
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+}
@@ -0,0 +1,79 @@
+//! Where [`FileSinkPlugin`](crate::FileSinkPlugin) saves live by default, see
+//! [`StorageFs`](crate::StorageFs): files natively, `localStorage` keys named after the path
+//! in browsers with the `wasm` feature, or files in the origin private file system with
+//! `opfs`.
+
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+mod imp {
+    use async_std::{io, path::Path};
+
+    pub(crate) async fn read(path: &Path) -> io::Result<Vec<u8>> {
+        async_fs::read(path).await
+    }
+
+    pub(crate) async fn write(path: &Path, bytes: &[u8]) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            async_fs::create_dir_all(parent).await?;
+        }
+        async_fs::write(path, bytes).await
+    }
+
+    pub(crate) async fn copy(from: &Path, to: &Path) -> io::Result<()> {
+        async_fs::copy(from, to).await.map(|_| ())
+    }
+
+    pub(crate) async fn rename(from: &Path, to: &Path) -> io::Result<()> {
+        async_fs::rename(from, to).await
+    }
+
+    pub(crate) async fn remove(path: &Path) -> io::Result<()> {
+        async_fs::remove_file(path).await
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm", not(feature = "opfs")))]
+mod imp {
+    use crate::local_storage;
+    use async_std::{io, path::Path};
+
+    pub(crate) async fn read(path: &Path) -> io::Result<Vec<u8>> {
+        local_storage::get(&path.to_string_lossy()).map(String::into_bytes)
+    }
+
+    pub(crate) async fn write(path: &Path, bytes: &[u8]) -> io::Result<()> {
+        let text = std::str::from_utf8(bytes).map_err(io::Error::other)?;
+        local_storage::set(&path.to_string_lossy(), text)
+    }
+
+    pub(crate) async fn copy(from: &Path, to: &Path) -> io::Result<()> {
+        let text = local_storage::get(&from.to_string_lossy())?;
+        local_storage::set(&to.to_string_lossy(), &text)
+    }
+
+    pub(crate) async fn rename(from: &Path, to: &Path) -> io::Result<()> {
+        copy(from, to).await?;
+        remove(from).await
+    }
+
+    pub(crate) async fn remove(path: &Path) -> io::Result<()> {
+        local_storage::remove(&path.to_string_lossy())
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", feature = "opfs"))]
+mod imp {
+    use async_std::{io, path::Path};
+
+    pub(crate) use crate::opfs::{read, remove, write};
+
+    pub(crate) async fn copy(from: &Path, to: &Path) -> io::Result<()> {
+        write(to, &read(from).await?).await
+    }
+
+    pub(crate) async fn rename(from: &Path, to: &Path) -> io::Result<()> {
+        copy(from, to).await?;
+        remove(from).await
+    }
+}
+
+pub(crate) use imp::{copy, read, remove, rename, write};
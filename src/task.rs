@@ -0,0 +1,350 @@
+use crate::{
+    circuit::Breaker,
+    events::{SinkPhase, TaskReporter},
+    stats::{DeadOnDrop, SinkShared},
+    telemetry::TelemetryGate,
+    CircuitBreaker, DeadLetters, IoSinkError, IoWriter, RetryPolicy, SinkControl, SinkResult,
+    SinkState, SinkTag,
+};
+use async_channel::Receiver;
+use async_std::{sync::Mutex, task};
+use bevy::{platform::time::Instant, prelude::*, tasks::IoTaskPool};
+use futures_lite::{future, FutureExt};
+use std::{
+    any::Any,
+    collections::VecDeque,
+    panic::AssertUnwindSafe,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// What to do when a writer panics inside the sink task.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PanicPolicy {
+    /// Re-run `init` and keep consuming messages, at most `max_restarts` times.
+    Restart { max_restarts: u32 },
+    /// Stop the task, the sink is reported dead.
+    #[default]
+    Disable,
+    /// Re-raise the panic on the main thread in debug builds, behaves like
+    /// [`PanicPolicy::Disable`] in release builds.
+    CrashInDebug,
+}
+
+#[derive(Resource)]
+pub(crate) struct IoSinkTaskData<R, W> {
+    pub(crate) rx: Receiver<R>,
+    pub(crate) writer: Arc<Mutex<W>>,
+    pub(crate) control: Receiver<SinkControl>,
+    pub(crate) shared: Arc<SinkShared>,
+    pub(crate) reporter: TaskReporter,
+    pub(crate) tag: SinkTag,
+    pub(crate) panic_policy: PanicPolicy,
+    pub(crate) pre_init_capacity: usize,
+    pub(crate) retry: Option<RetryPolicy>,
+    pub(crate) dead_letters: Option<DeadLetters<R>>,
+    /// Set when retries or dead letters need a copy of the message that survives `write`.
+    pub(crate) clone: Option<fn(&R) -> R>,
+    pub(crate) circuit_breaker: Option<CircuitBreaker>,
+}
+
+enum TaskEvent<R> {
+    Message(R),
+    Control(SinkControl),
+    Heartbeat,
+    Closed,
+}
+
+struct SinkTask<R, W> {
+    rx: Receiver<R>,
+    control: Receiver<SinkControl>,
+    writer: Arc<Mutex<W>>,
+    shared: Arc<SinkShared>,
+    reporter: TaskReporter,
+    gate: Option<Arc<AtomicBool>>,
+    pre_init_capacity: usize,
+    retry: Option<RetryPolicy>,
+    dead_letters: Option<DeadLetters<R>>,
+    clone: Option<fn(&R) -> R>,
+    breaker: Option<std::sync::Mutex<Breaker>>,
+}
+
+impl<R, W> SinkTask<R, W>
+where
+    R: Send + 'static,
+    W: IoWriter<R> + Send + Sync + 'static,
+{
+    async fn next_event(&self, paused: bool) -> TaskEvent<R> {
+        let next = async {
+            if paused {
+                self.control
+                    .recv()
+                    .await
+                    .map_or(TaskEvent::Closed, TaskEvent::Control)
+            } else {
+                // Controls first, so messages sent after a `Reopen` reach the reopened writer.
+                future::or(
+                    async {
+                        match self.control.recv().await {
+                            Ok(ctrl) => TaskEvent::Control(ctrl),
+                            Err(_) => future::pending().await,
+                        }
+                    },
+                    async {
+                        self.rx
+                            .recv()
+                            .await
+                            .map_or(TaskEvent::Closed, TaskEvent::Message)
+                    },
+                )
+                .await
+            }
+        };
+        future::or(next, async {
+            task::sleep(self.shared.heartbeat.interval).await;
+            TaskEvent::Heartbeat
+        })
+        .await
+    }
+
+    /// Run `init` while buffering whatever gets sent in the meantime, so messages queued at
+    /// startup are replayed in order once the writer is ready.
+    async fn init(&self, writer: &mut W) -> SinkResult<VecDeque<R>> {
+        let mut pending = VecDeque::new();
+        let buffer = async {
+            while let Ok(msg) = self.rx.recv().await {
+                if pending.len() >= self.pre_init_capacity {
+                    pending.pop_front();
+                    self.shared.record_dropped(1);
+                }
+                pending.push_back(msg);
+            }
+            future::pending::<SinkResult>().await
+        };
+        future::or(writer.init(), buffer).await?;
+        Ok(pending)
+    }
+
+    async fn write(&self, writer: &mut W, msg: R) {
+        if self
+            .gate
+            .as_ref()
+            .is_some_and(|g| !g.load(Ordering::Relaxed))
+        {
+            self.shared.record_dropped(1);
+            return;
+        }
+        if let Some(breaker) = &self.breaker {
+            let (allowed, change) = breaker.lock().unwrap().allow();
+            if let Some(state) = change {
+                self.reporter.circuit(state);
+            }
+            if !allowed {
+                self.shared.record_dropped(1);
+                if let Some(dead_letters) = &self.dead_letters {
+                    let error = IoSinkError::Other("circuit breaker open".to_string());
+                    dead_letters.push(msg, &error);
+                }
+                return;
+            }
+        }
+        self.reporter.status(SinkState::Writing);
+        let start = Instant::now();
+        let (result, kept) = match self.clone {
+            Some(clone) => {
+                let policy = self.retry.unwrap_or(RetryPolicy::new(0));
+                let mut attempt = 0;
+                let result = loop {
+                    match writer.write(clone(&msg)).await {
+                        Err(e) if e.is_retryable() && attempt < policy.max_retries => {
+                            warn!("write failed, retrying: {e}");
+                            task::sleep(policy.backoff(attempt)).await;
+                            self.shared.beat();
+                            attempt += 1;
+                        }
+                        result => break result,
+                    }
+                };
+                (result, Some(msg))
+            }
+            None => (writer.write(msg).await, None),
+        };
+        if let Some(breaker) = &self.breaker {
+            let mut breaker = breaker.lock().unwrap();
+            // A bad message says nothing about the health of the backend.
+            let change = match &result {
+                Ok(()) => breaker.record_success(),
+                Err(e) if e.is_retryable() => breaker.record_failure(),
+                Err(_) => None,
+            };
+            if let Some(state) = change {
+                self.reporter.circuit(state);
+            }
+        }
+        match result {
+            Ok(()) => {
+                let duration = start.elapsed();
+                self.shared.record_write(
+                    duration,
+                    writer.last_write_len(),
+                    writer.last_write_changed(),
+                );
+                self.reporter.completed(writer.last_write_len(), duration);
+            }
+            Err(e) => {
+                if let (Some(dead_letters), Some(msg)) = (&self.dead_letters, kept) {
+                    dead_letters.push(msg, &e);
+                }
+                self.reporter.failed(SinkPhase::Write, e);
+            }
+        }
+    }
+
+    /// Write everything sent before a control, which is handled ahead of messages.
+    async fn write_queued(&self, writer: &mut W) {
+        while let Ok(msg) = self.rx.try_recv() {
+            self.write(writer, msg).await;
+        }
+    }
+
+    async fn run(&self) {
+        self.shared.beat();
+        self.reporter.status(SinkState::Initializing);
+        let mut writer_lock = self.writer.lock().await;
+        let pending = match self.init(&mut writer_lock).await {
+            Ok(pending) => pending,
+            Err(e) => {
+                self.reporter.failed(SinkPhase::Init, e);
+                return;
+            }
+        };
+        self.reporter.status(SinkState::Idle);
+        for msg in pending {
+            self.write(&mut writer_lock, msg).await;
+        }
+
+        let mut paused = false;
+        loop {
+            let event = self.next_event(paused).await;
+            self.shared.beat();
+
+            match event {
+                TaskEvent::Message(msg) => self.write(&mut writer_lock, msg).await,
+                TaskEvent::Control(SinkControl::Flush) => {
+                    if !paused {
+                        self.write_queued(&mut writer_lock).await;
+                    }
+                    if let Err(e) = writer_lock.flush().await {
+                        self.reporter.failed(SinkPhase::Flush, e);
+                    }
+                }
+                TaskEvent::Control(SinkControl::Pause) => paused = true,
+                TaskEvent::Control(SinkControl::Resume) => paused = false,
+                TaskEvent::Control(SinkControl::Purge) => {
+                    while self.rx.try_recv().is_ok() {
+                        self.shared.record_dropped(1);
+                    }
+                }
+                TaskEvent::Control(SinkControl::Reopen) => {
+                    // What was queued before belongs to the previous file.
+                    if !paused {
+                        self.write_queued(&mut writer_lock).await;
+                    }
+                    if let Err(e) = writer_lock.close().await {
+                        self.reporter.failed(SinkPhase::Close, e);
+                    }
+                    self.reporter.status(SinkState::Initializing);
+                    match self.init(&mut writer_lock).await {
+                        Ok(pending) => {
+                            self.reporter.status(SinkState::Idle);
+                            for msg in pending {
+                                self.write(&mut writer_lock, msg).await;
+                            }
+                        }
+                        Err(e) => self.reporter.failed(SinkPhase::Init, e),
+                    }
+                }
+                TaskEvent::Control(SinkControl::Wipe) => {
+                    // Queued saves would bring back what is being deleted.
+                    while self.rx.try_recv().is_ok() {
+                        self.shared.record_dropped(1);
+                    }
+                    match writer_lock.wipe().await {
+                        Ok(()) => self.reporter.wiped(),
+                        Err(e) => self.reporter.failed(SinkPhase::Wipe, e),
+                    }
+                }
+                TaskEvent::Heartbeat => {}
+                TaskEvent::Closed => break,
+            }
+        }
+
+        if let Err(e) = writer_lock.close().await {
+            self.reporter.failed(SinkPhase::Close, e);
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "<non-string panic payload>".to_string()
+    }
+}
+
+pub(crate) fn spawn_io_sink_task<R, W>(
+    task_data: Res<IoSinkTaskData<R, W>>,
+    telemetry_gate: Option<Res<TelemetryGate>>,
+) where
+    R: Send + 'static,
+    W: IoWriter<R> + Send + Sync + 'static,
+{
+    let panic_policy = task_data.panic_policy;
+    let sink = SinkTask {
+        rx: task_data.rx.clone(),
+        control: task_data.control.clone(),
+        writer: task_data.writer.clone(),
+        shared: task_data.shared.clone(),
+        reporter: task_data.reporter.clone(),
+        gate: match task_data.tag {
+            SinkTag::Telemetry => telemetry_gate.map(|gate| gate.0.clone()),
+            _ => None,
+        },
+        pre_init_capacity: task_data.pre_init_capacity,
+        retry: task_data.retry,
+        dead_letters: task_data.dead_letters.clone(),
+        clone: task_data.clone,
+        breaker: task_data
+            .circuit_breaker
+            .map(|config| std::sync::Mutex::new(Breaker::new(config))),
+    };
+
+    IoTaskPool::get()
+        .spawn(async move {
+            // Flags the sink dead however the task ends, so senders stop queueing into the void.
+            let _dead_on_drop = DeadOnDrop(sink.shared.clone());
+
+            let mut restarts = 0;
+            while let Err(payload) = AssertUnwindSafe(sink.run()).catch_unwind().await {
+                let message = panic_message(&*payload);
+                let restart = match panic_policy {
+                    PanicPolicy::Restart { max_restarts } => restarts < max_restarts,
+                    PanicPolicy::Disable | PanicPolicy::CrashInDebug => false,
+                };
+                let fatal = panic_policy == PanicPolicy::CrashInDebug && cfg!(debug_assertions);
+                sink.reporter.panicked(message, restart, fatal);
+
+                if !restart {
+                    break;
+                }
+                restarts += 1;
+            }
+            sink.reporter.status(SinkState::Closed);
+        })
+        .detach();
+}
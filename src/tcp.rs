@@ -0,0 +1,142 @@
+use crate::{frame::length_prefixed, IoSinkError, IoWriter, SaveFormat, SinkResult};
+use async_std::{io::WriteExt, net::TcpStream};
+use bevy::prelude::*;
+use serde::Serialize;
+use std::marker::PhantomData;
+
+/// Sends every message to a TCP endpoint as a frame: the length of the encoded message as a
+/// big-endian `u32`, then the message. The connection is tried by `init`, so an endpoint that
+/// isn't up yet doesn't stop the sink, and opened on the next write while it's down; those
+/// writes fail and are retried by the sink's [`RetryPolicy`](crate::RetryPolicy).
+///
+/// ```ignore
+/// app.add_plugins(IoSinkPlugin::<GameState, _>::new(TcpSink::new("127.0.0.1:7878")));
+/// ```
+pub struct TcpSink<R> {
+    addr: String,
+    format: SaveFormat,
+    nodelay: bool,
+    max_message_size: Option<u64>,
+    stream: Option<TcpStream>,
+    last_write_len: Option<u64>,
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R> TcpSink<R> {
+    /// `addr` is a `host:port` pair, resolved on every connection.
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self {
+            addr: addr.into(),
+            format: SaveFormat::default(),
+            nodelay: true,
+            max_message_size: None,
+            stream: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Whether to disable Nagle's algorithm, `true` by default so frames leave immediately.
+    pub fn with_nodelay(mut self, nodelay: bool) -> Self {
+        self.nodelay = nodelay;
+        self
+    }
+
+    /// Reject encoded messages larger than `bytes` with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+
+    async fn connect(&mut self) -> SinkResult<&mut TcpStream> {
+        let stream = match self.stream.take() {
+            Some(stream) => stream,
+            None => {
+                let stream = TcpStream::connect(self.addr.as_str()).await?;
+                stream.set_nodelay(self.nodelay)?;
+                stream
+            }
+        };
+        Ok(self.stream.insert(stream))
+    }
+}
+
+impl<R> IoWriter<R> for TcpSink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        if let Err(e) = self.connect().await {
+            warn!("cannot connect to {}, retrying on write: {e}", self.addr);
+        }
+        Ok(())
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let payload = self.format.encode(&data)?;
+        IoSinkError::check_size(payload.len(), self.max_message_size)?;
+        let frame = length_prefixed(&payload)?;
+
+        let stream = self.connect().await?;
+        if let Err(e) = stream.write_all(&frame).await {
+            // Half a frame may have gone out, only a new connection is in a known state.
+            warn!("lost the connection to {}: {e}", self.addr);
+            self.stream = None;
+            return Err(e.into());
+        }
+        self.last_write_len = Some(frame.len() as u64);
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        if let Some(stream) = self.stream.as_mut() {
+            stream.flush().await?;
+        }
+        Ok(())
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        if let Some(stream) = self.stream.take() {
+            stream.shutdown(std::net::Shutdown::Both)?;
+        }
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::{io::ReadExt, net::TcpListener};
+    use futures_lite::future::block_on;
+
+    #[test]
+    fn connects_on_write_once_the_endpoint_is_up() {
+        block_on(async {
+            // Reserve a free port, then leave it closed until after `init`.
+            let addr = TcpListener::bind("127.0.0.1:0")
+                .await
+                .unwrap()
+                .local_addr()
+                .unwrap();
+            let mut sink = TcpSink::<u32>::new(addr.to_string());
+            sink.init().await.unwrap();
+            assert!(sink.write(1).await.is_err());
+
+            let listener = TcpListener::bind(addr).await.unwrap();
+            sink.write(2).await.unwrap();
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut frame = [0; 5];
+            stream.read_exact(&mut frame).await.unwrap();
+            assert_eq!(frame, [0, 0, 0, 1, b'2']);
+        });
+    }
+}
@@ -0,0 +1,84 @@
+#[cfg(feature = "file")]
+use crate::FileSinkPlugin;
+#[cfg(feature = "file")]
+use async_std::path::PathBuf;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Global opt-out for sinks tagged [`SinkTag::Telemetry`](crate::SinkTag::Telemetry).
+///
+/// Save sinks are never affected. The choice is persisted by [`TelemetryConsentPlugin`].
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+pub struct TelemetryConsent {
+    pub enabled: bool,
+}
+
+impl Default for TelemetryConsent {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// Flag shared with telemetry sink tasks, mirrored from [`TelemetryConsent`].
+#[derive(Resource, Clone)]
+pub(crate) struct TelemetryGate(pub(crate) Arc<AtomicBool>);
+
+impl TelemetryGate {
+    fn new(open: bool) -> Self {
+        Self(Arc::new(AtomicBool::new(open)))
+    }
+}
+
+impl Default for TelemetryGate {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+/// Mirrors [`TelemetryConsent`] into the [`TelemetryGate`], added with the first telemetry
+/// sink so the consent applies whether or not it's persisted.
+pub(crate) struct TelemetryGatePlugin;
+
+impl Plugin for TelemetryGatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TelemetryGate>();
+        app.add_systems(
+            Update,
+            apply_telemetry_consent.run_if(resource_exists_and_changed::<TelemetryConsent>),
+        );
+    }
+}
+
+fn apply_telemetry_consent(consent: Res<TelemetryConsent>, gate: Res<TelemetryGate>) {
+    gate.0.store(consent.enabled, Ordering::Relaxed);
+}
+
+#[cfg(feature = "file")]
+pub struct TelemetryConsentPlugin {
+    path: PathBuf,
+}
+
+#[cfg(feature = "file")]
+impl TelemetryConsentPlugin {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[cfg(feature = "file")]
+impl Plugin for TelemetryConsentPlugin {
+    fn build(&self, app: &mut App) {
+        // Closed until the persisted choice has been loaded.
+        app.insert_resource(TelemetryGate::new(false));
+        app.add_plugins(
+            FileSinkPlugin::<TelemetryConsent>::new(self.path.clone()).with_sync_on_change(true),
+        );
+        if !app.is_plugin_added::<TelemetryGatePlugin>() {
+            app.add_plugins(TelemetryGatePlugin);
+        }
+    }
+}
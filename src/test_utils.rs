@@ -0,0 +1,168 @@
+//! Scaffolding for tests of apps that persist with this crate: a headless [`App`], sinks
+//! backed by memory or a temporary directory, and helpers ticking the app until the sink
+//! task caught up.
+//!
+//! ```ignore
+//! use bevy_io_sink::test_utils::*;
+//!
+//! let mut app = test_app();
+//! let written = app.add_memory_sink::<Score>();
+//! app.world().resource::<IoSender<Score>>().enqueue(Score(3)).unwrap();
+//! assert!(app.update_until_written::<Score>(1, DEFAULT_TIMEOUT));
+//! assert_eq!(written.last(), Some(Score(3)));
+//! ```
+
+use crate::{codec, IoSinkPlugin, IoSinkStats, LoadTracker, MemorySink, MemorySinkHandle};
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Long enough for a loaded CI machine, short enough for a hung test to fail quickly.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A headless app with the task pools, time and frame count set up, ready for sink plugins.
+pub fn test_app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app
+}
+
+/// A fresh directory under the system temp dir, deleted with everything in it on drop.
+#[derive(Debug)]
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos());
+        let name = format!(
+            "bevy_io_sink-{}-{nanos}-{}",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = std::env::temp_dir().join(name);
+        std::fs::create_dir_all(&path).expect("creating a temp dir");
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.0.join(path)
+    }
+}
+
+impl Default for TempDir {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Decode the save at `path` like the loader would, `None` if there is none.
+pub fn read_persisted<R: DeserializeOwned>(path: impl AsRef<Path>) -> Option<R> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).ok().filter(|bytes| !bytes.is_empty())?;
+    match codec::decode(&bytes, &Default::default()) {
+        Ok(value) => Some(value),
+        Err(e) => panic!("{} is not a valid save: {e}", path.display()),
+    }
+}
+
+/// Panic unless the save at `path` decodes to `expected`.
+#[track_caller]
+pub fn assert_persisted<R>(path: impl AsRef<Path>, expected: &R)
+where
+    R: DeserializeOwned + PartialEq + Debug,
+{
+    let path = path.as_ref();
+    match read_persisted::<R>(path) {
+        Some(actual) => assert_eq!(&actual, expected, "save at {}", path.display()),
+        None => panic!("nothing persisted at {}", path.display()),
+    }
+}
+
+pub trait TestAppExt {
+    /// Add an [`IoSinkPlugin<R, _>`] over a [`MemorySink`] and return what it records.
+    fn add_memory_sink<R: Resource>(&mut self) -> MemorySinkHandle<R>;
+
+    /// Add a [`FileSinkPlugin<R>`](crate::FileSinkPlugin) syncing `R` on change to a file in
+    /// `dir` named after the type, and return the file's path.
+    #[cfg(feature = "file")]
+    fn add_temp_file_sink<R>(&mut self, dir: &TempDir) -> PathBuf
+    where
+        R: DeserializeOwned + serde::Serialize + Resource + Clone + Default;
+
+    /// Run updates until `done` holds, yielding to the sink tasks in between. `false` if it
+    /// still doesn't after `timeout`.
+    fn update_until(&mut self, timeout: Duration, done: impl FnMut(&mut World) -> bool) -> bool;
+
+    /// Run updates until the sink of `R` wrote at least `count` messages in total.
+    fn update_until_written<R: Resource>(&mut self, count: u64, timeout: Duration) -> bool {
+        self.update_until(timeout, |world| {
+            world.resource::<IoSinkStats<R>>().written >= count
+        })
+    }
+
+    /// Run updates until the persisted `R` has been loaded.
+    fn update_until_loaded<R: Resource>(&mut self, timeout: Duration) -> bool {
+        self.update_until(timeout, |world| {
+            world
+                .get_resource::<LoadTracker>()
+                .is_some_and(LoadTracker::is_loaded::<R>)
+        })
+    }
+}
+
+impl TestAppExt for App {
+    fn add_memory_sink<R: Resource>(&mut self) -> MemorySinkHandle<R> {
+        let handle = MemorySinkHandle::<R>::default();
+        self.add_plugins(IoSinkPlugin::<R, _>::new(MemorySink::new(handle.clone())));
+        self.insert_resource(handle.clone());
+        handle
+    }
+
+    #[cfg(feature = "file")]
+    fn add_temp_file_sink<R>(&mut self, dir: &TempDir) -> PathBuf
+    where
+        R: DeserializeOwned + serde::Serialize + Resource + Clone + Default,
+    {
+        let name = std::any::type_name::<R>().replace("::", "_");
+        let path = dir.join(format!("{name}.json"));
+        self.add_plugins(crate::FileSinkPlugin::<R>::new(path.clone()).with_sync_on_change(true));
+        path
+    }
+
+    fn update_until(
+        &mut self,
+        timeout: Duration,
+        mut done: impl FnMut(&mut World) -> bool,
+    ) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            self.update();
+            if done(self.world_mut()) {
+                return true;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
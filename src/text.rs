@@ -0,0 +1,98 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, ops::Deref};
+use unicode_normalization::UnicodeNormalization;
+
+const DEFAULT_MAX_CHARS: usize = 256;
+
+/// Normalize `text` to NFC, drop control characters other than newlines and tabs, and keep
+/// at most `max_chars` characters.
+///
+/// The same name typed under different input methods can produce different code point
+/// sequences, NFC makes them compare and hash equal once loaded elsewhere.
+pub fn sanitize(text: &str, max_chars: usize) -> String {
+    text.nfc()
+        .filter(|c| !c.is_control() || matches!(c, '\n' | '\t'))
+        .take(max_chars)
+        .collect()
+}
+
+/// Localized or user-entered text in a save, e.g. a save name or a player's nickname.
+/// Always [`sanitize`]d to `MAX` characters, both when created and when loaded, and loaded
+/// from invalid UTF-8 with replacement characters instead of failing the whole save.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SaveString<const MAX: usize = DEFAULT_MAX_CHARS>(String);
+
+impl<const MAX: usize> SaveString<MAX> {
+    pub fn new(text: impl AsRef<str>) -> Self {
+        Self(sanitize(text.as_ref(), MAX))
+    }
+
+    pub fn from_utf8_lossy(bytes: &[u8]) -> Self {
+        Self::new(String::from_utf8_lossy(bytes))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl<const MAX: usize> Deref for SaveString<MAX> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<const MAX: usize> fmt::Display for SaveString<MAX> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<const MAX: usize> From<&str> for SaveString<MAX> {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl<const MAX: usize> From<String> for SaveString<MAX> {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+impl<const MAX: usize> Serialize for SaveString<MAX> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de, const MAX: usize> Deserialize<'de> for SaveString<MAX> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_string(SaveStringVisitor::<MAX>)
+    }
+}
+
+struct SaveStringVisitor<const MAX: usize>;
+
+impl<const MAX: usize> de::Visitor<'_> for SaveStringVisitor<MAX> {
+    type Value = SaveString<MAX>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a string")
+    }
+
+    fn visit_str<E: de::Error>(self, text: &str) -> Result<Self::Value, E> {
+        Ok(SaveString::new(text))
+    }
+
+    /// Binary formats may hand over the raw bytes of a string written by another tool.
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+        Ok(SaveString::from_utf8_lossy(bytes))
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{IoSinkError, IoSinks};
+use async_channel::{unbounded, Receiver, Sender};
+use async_std::path::{Path, PathBuf};
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::view::screenshot::{Screenshot, ScreenshotCaptured},
+    tasks::IoTaskPool,
+};
+use image::{DynamicImage, ImageFormat};
+use std::{
+    io::{Cursor, ErrorKind},
+    marker::PhantomData,
+};
+
+/// The file next to a save holding its thumbnail, `<save>.thumb.png`.
+pub fn thumbnail_path(save: &Path) -> PathBuf {
+    let mut name = save.as_os_str().to_owned();
+    name.push(".thumb.png");
+    PathBuf::from(name)
+}
+
+/// Read the thumbnail attached to the save at `save`, `None` if it has none. Only the
+/// thumbnail is read, the save itself isn't touched.
+///
+/// ```ignore
+/// let image = read_thumbnail(Path::new("saves/slot1.json")).await?;
+/// let handle = image.map(|image| images.add(image));
+/// ```
+pub async fn read_thumbnail(save: &Path) -> Result<Option<Image>, IoSinkError> {
+    let bytes = match async_fs::read(thumbnail_path(save)).await {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let image = image::load_from_memory_with_format(&bytes, ImageFormat::Png)
+        .map_err(IoSinkError::deserialization)?;
+    Ok(Some(Image::from_dynamic(
+        image,
+        true,
+        RenderAssetUsages::default(),
+    )))
+}
+
+fn encode_thumbnail(image: Image, max_size: UVec2) -> Result<Vec<u8>, IoSinkError> {
+    let image: DynamicImage = image
+        .try_into_dynamic()
+        .map_err(|e| IoSinkError::Other(format!("unsupported thumbnail image: {e}")))?;
+    let mut png = Vec::new();
+    image
+        .thumbnail(max_size.x, max_size.y)
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(IoSinkError::serialization)?;
+    Ok(png)
+}
+
+/// Trigger to screenshot the primary window and attach it to the save of `R`:
+/// `commands.trigger(CaptureThumbnail::<R>::default())`.
+#[derive(Event, Debug)]
+pub struct CaptureThumbnail<R>(PhantomData<fn() -> R>);
+
+impl<R> Default for CaptureThumbnail<R> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// Trigger to attach an image to the save of `R`, replacing its previous thumbnail. The
+/// image is downscaled to the [`ThumbnailPlugin`] size.
+#[derive(Event, Debug)]
+pub struct AttachThumbnail<R> {
+    pub image: Image,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R> AttachThumbnail<R> {
+    pub fn new(image: Image) -> Self {
+        Self {
+            image,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Emitted once the thumbnail of `R`'s save has been written.
+#[derive(Event, Debug)]
+pub struct ThumbnailWritten<R> {
+    pub path: PathBuf,
+    _marker: PhantomData<fn() -> R>,
+}
+
+#[derive(Resource)]
+struct ThumbnailChannel<R> {
+    max_size: UVec2,
+    tx: Sender<Result<PathBuf, IoSinkError>>,
+    rx: Receiver<Result<PathBuf, IoSinkError>>,
+    _marker: PhantomData<fn() -> R>,
+}
+
+/// Stores a thumbnail next to the save of `R`, e.g. for the load menu, see
+/// [`CaptureThumbnail`] and [`read_thumbnail`].
+///
+/// ```ignore
+/// app.add_plugins((
+///     FileSinkPlugin::<World>::new("saves/world.json"),
+///     ThumbnailPlugin::<World>::new(),
+/// ));
+///
+/// fn quicksave(mut commands: Commands) {
+///     commands.save_resource::<World>();
+///     commands.trigger(CaptureThumbnail::<World>::default());
+/// }
+/// ```
+pub struct ThumbnailPlugin<R> {
+    max_size: UVec2,
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R> Default for ThumbnailPlugin<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<R> ThumbnailPlugin<R> {
+    pub fn new() -> Self {
+        Self {
+            max_size: UVec2::new(320, 180),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Downscale thumbnails to fit in `width` x `height` pixels, 320x180 by default. The
+    /// aspect ratio is kept.
+    pub fn with_max_size(mut self, width: u32, height: u32) -> Self {
+        self.max_size = UVec2::new(width, height);
+        self
+    }
+}
+
+impl<R: Resource> Plugin for ThumbnailPlugin<R> {
+    fn build(&self, app: &mut App) {
+        let (tx, rx) = unbounded();
+        app.insert_resource(ThumbnailChannel::<R> {
+            max_size: self.max_size,
+            tx,
+            rx,
+            _marker: PhantomData,
+        });
+        app.add_event::<ThumbnailWritten<R>>();
+        app.add_observer(capture_thumbnail::<R>);
+        app.add_observer(attach_thumbnail::<R>);
+        app.add_systems(PreUpdate, receive_thumbnails::<R>);
+    }
+}
+
+fn capture_thumbnail<R: Resource>(_: Trigger<CaptureThumbnail<R>>, mut commands: Commands) {
+    commands.spawn(Screenshot::primary_window()).observe(
+        |captured: Trigger<ScreenshotCaptured>, mut commands: Commands| {
+            commands.trigger(AttachThumbnail::<R>::new(captured.event().0.clone()));
+        },
+    );
+}
+
+fn attach_thumbnail<R: Resource>(
+    trigger: Trigger<AttachThumbnail<R>>,
+    sinks: Res<IoSinks>,
+    channel: Res<ThumbnailChannel<R>>,
+) {
+    let Some(save) = sinks.path::<R>() else {
+        error!(
+            "{} has no save file to attach a thumbnail to",
+            std::any::type_name::<R>()
+        );
+        return;
+    };
+    let path = thumbnail_path(save);
+    let image = trigger.event().image.clone();
+    let (max_size, tx) = (channel.max_size, channel.tx.clone());
+    IoTaskPool::get()
+        .spawn(async move {
+            let written = async {
+                let png = encode_thumbnail(image, max_size)?;
+                async_fs::write(&path, png).await?;
+                Ok::<_, IoSinkError>(path)
+            };
+            let _ = tx.send(written.await).await;
+        })
+        .detach();
+}
+
+fn receive_thumbnails<R: Resource>(
+    channel: Res<ThumbnailChannel<R>>,
+    mut written: EventWriter<ThumbnailWritten<R>>,
+) {
+    while let Ok(result) = channel.rx.try_recv() {
+        match result {
+            Ok(path) => {
+                written.write(ThumbnailWritten {
+                    path,
+                    _marker: PhantomData,
+                });
+            }
+            Err(e) => error!(
+                "failed to write the thumbnail of {}: {e}",
+                std::any::type_name::<R>()
+            ),
+        }
+    }
+}
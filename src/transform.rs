@@ -0,0 +1,68 @@
+use crate::{IoWriter, SinkResult, WriterCapabilities};
+
+/// Converts every `T` before handing it to a writer of another type, so what gets persisted
+/// can differ from the in-game resource, e.g. without runtime-only fields or as a stable
+/// DTO. Built with [`IoWriterExt::map`](crate::IoWriterExt::map) or
+/// [`IoWriterExt::map_from`](crate::IoWriterExt::map_from).
+///
+/// ```ignore
+/// let sink = FileSink::<PlayerDto>::new("saves/player.json").map(|player: Player| PlayerDto {
+///     name: player.name,
+///     level: player.level,
+/// });
+/// app.add_plugins(IoSinkPlugin::<Player, _>::new(sink));
+/// ```
+pub struct TransformSink<W, F> {
+    inner: W,
+    transform: F,
+}
+
+impl<W, F> TransformSink<W, F> {
+    pub fn new(inner: W, transform: F) -> Self {
+        Self { inner, transform }
+    }
+
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+}
+
+impl<T, R, W, F> IoWriter<T> for TransformSink<W, F>
+where
+    T: Send + 'static,
+    W: IoWriter<R>,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        self.inner.init().await
+    }
+
+    async fn write(&mut self, data: T) -> SinkResult {
+        let data = (self.transform)(data);
+        self.inner.write(data).await
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        self.inner.flush().await
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        self.inner.close().await
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.inner.last_write_len()
+    }
+
+    fn last_write_changed(&self) -> Option<u64> {
+        self.inner.last_write_changed()
+    }
+
+    fn capabilities(&self) -> WriterCapabilities {
+        self.inner.capabilities()
+    }
+
+    async fn wipe(&mut self) -> SinkResult {
+        self.inner.wipe().await
+    }
+}
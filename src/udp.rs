@@ -0,0 +1,96 @@
+use crate::{IoSinkError, IoWriter, SaveFormat, SinkResult};
+use async_std::{
+    io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket},
+};
+use serde::Serialize;
+use std::marker::PhantomData;
+
+/// Largest payload that fits in one Ethernet frame without IP fragmentation.
+const DEFAULT_MAX_DATAGRAM_SIZE: u64 = 1472;
+
+/// Sends every message as a single UDP datagram, for telemetry where losing a message now and
+/// then is fine. Nothing tells whether a datagram arrived, so nothing is retried on loss.
+///
+/// Messages larger than [`with_max_datagram_size`](Self::with_max_datagram_size) would be
+/// fragmented, or dropped by the network, and fail with [`IoSinkError::MessageTooLarge`]
+/// instead, surfacing as a [`SinkFailed<R>`](crate::SinkFailed) event.
+///
+/// ```ignore
+/// app.add_plugins(IoSinkPlugin::<FrameStats, _>::new(UdpSink::new("127.0.0.1:8125")));
+/// ```
+pub struct UdpSink<R> {
+    addr: String,
+    format: SaveFormat,
+    max_datagram_size: u64,
+    socket: Option<UdpSocket>,
+    last_write_len: Option<u64>,
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R> UdpSink<R> {
+    /// `addr` is a `host:port` pair, resolved by `init`.
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self {
+            addr: addr.into(),
+            format: SaveFormat::default(),
+            max_datagram_size: DEFAULT_MAX_DATAGRAM_SIZE,
+            socket: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Reject encoded messages larger than `bytes`, 1472 by default so datagrams fit in one
+    /// Ethernet frame. UDP can't carry more than 65507 bytes in any case.
+    pub fn with_max_datagram_size(mut self, bytes: u64) -> Self {
+        self.max_datagram_size = bytes.min(65507);
+        self
+    }
+}
+
+impl<R> IoWriter<R> for UdpSink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        let target = self
+            .addr
+            .as_str()
+            .to_socket_addrs()
+            .await?
+            .next()
+            .ok_or_else(|| IoSinkError::Other(format!("{} resolves to no address", self.addr)))?;
+        let local: SocketAddr = match target {
+            SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+            SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+        };
+        let socket = UdpSocket::bind(local).await?;
+        socket.connect(target).await?;
+        self.socket = Some(socket);
+        Ok(())
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let datagram = self.format.encode(&data)?;
+        IoSinkError::check_size(datagram.len(), Some(self.max_datagram_size))?;
+        let socket = self.socket.as_ref().ok_or(IoSinkError::NotInitialized)?;
+        match socket.send(&datagram).await {
+            // An earlier datagram found no listener, which is fine for fire-and-forget.
+            Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => {}
+            Err(e) => return Err(e.into()),
+            Ok(_) => {}
+        }
+        self.last_write_len = Some(datagram.len() as u64);
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+}
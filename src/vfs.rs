@@ -0,0 +1,287 @@
+//! The file system [`FileSink`](crate::FileSink) and
+//! [`FileSinkPlugin`](crate::FileSinkPlugin) go through, so saves can live somewhere other
+//! than the platform's storage, e.g. in memory during tests.
+
+use crate::storage;
+use async_std::{
+    io,
+    path::{Path, PathBuf},
+};
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+pub type VfsFuture<'a, T> = Pin<Box<dyn Future<Output = io::Result<T>> + Send + 'a>>;
+
+/// A save kept open by [`FileSink`](crate::FileSink) between writes.
+pub trait VfsFile: Send + Sync + 'static {
+    /// Make `bytes` the whole contents of the file and flush them.
+    fn replace<'a>(&'a mut self, bytes: &'a [u8]) -> VfsFuture<'a, ()>;
+}
+
+/// File operations used by the file sink and loader. Paths are used as given, and missing
+/// files fail with [`io::ErrorKind::NotFound`] like they do on disk.
+pub trait Vfs: Send + Sync + 'static {
+    /// Open `path` for repeated [`VfsFile::replace`] calls, creating it and its parent
+    /// directories if needed. Existing contents are kept until the first replace.
+    fn open<'a>(&'a self, path: &'a Path) -> VfsFuture<'a, Box<dyn VfsFile>>;
+
+    fn read<'a>(&'a self, path: &'a Path) -> VfsFuture<'a, Vec<u8>>;
+
+    /// Replace the contents of `path`, creating it and its parent directories if needed.
+    fn write<'a>(&'a self, path: &'a Path, bytes: &'a [u8]) -> VfsFuture<'a, ()>;
+
+    fn rename<'a>(&'a self, from: &'a Path, to: &'a Path) -> VfsFuture<'a, ()>;
+
+    fn remove<'a>(&'a self, path: &'a Path) -> VfsFuture<'a, ()>;
+
+    fn copy<'a>(&'a self, from: &'a Path, to: &'a Path) -> VfsFuture<'a, ()> {
+        Box::pin(async move { self.write(to, &self.read(from).await?).await })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> VfsFuture<'a, bool> {
+        Box::pin(async move {
+            match self.read(path).await {
+                Ok(_) => Ok(true),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+                Err(e) => Err(e),
+            }
+        })
+    }
+
+    /// The files directly in `dir`, used by [`SaveSlotsPlugin`](crate::SaveSlotsPlugin) to
+    /// find its slots. Unsupported unless implemented.
+    fn list<'a>(&'a self, dir: &'a Path) -> VfsFuture<'a, Vec<PathBuf>> {
+        let unsupported = io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("can't list {}", dir.display()),
+        );
+        Box::pin(async move { Err(unsupported) })
+    }
+}
+
+/// [`Vfs::remove`] every path, ignoring the ones that don't exist.
+pub(crate) async fn remove_all(
+    vfs: &dyn Vfs,
+    paths: impl IntoIterator<Item = PathBuf>,
+) -> io::Result<()> {
+    for path in paths {
+        match vfs.remove(&path).await {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// The platform's storage, used unless another [`Vfs`] is set: files natively,
+/// `localStorage` keys in browsers with the `wasm` feature, or the origin private file
+/// system with `opfs`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StorageFs;
+
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+mod native {
+    use super::VfsFuture;
+    use async_fs::{File, OpenOptions};
+    use async_std::io::{BufWriter, SeekExt, WriteExt};
+    use std::io::SeekFrom;
+
+    pub(super) struct NativeFile(pub(super) BufWriter<File>);
+
+    impl super::VfsFile for NativeFile {
+        /// Rewritten in place, the file is never missing or empty in between.
+        fn replace<'a>(&'a mut self, bytes: &'a [u8]) -> VfsFuture<'a, ()> {
+            Box::pin(async move {
+                self.0.seek(SeekFrom::Start(0)).await?;
+                self.0.write_all(bytes).await?;
+                self.0.get_mut().set_len(bytes.len() as u64).await?;
+                self.0.flush().await
+            })
+        }
+    }
+
+    pub(super) async fn open(path: &async_std::path::Path) -> std::io::Result<NativeFile> {
+        if let Some(parent) = path.parent() {
+            async_fs::create_dir_all(parent).await?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .write(true)
+            .append(false)
+            .open(path)
+            .await?;
+        Ok(NativeFile(BufWriter::with_capacity(64 * 1024, file)))
+    }
+}
+
+/// Browser storage has no open files, every replace is a whole write.
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+struct RewrittenFile(PathBuf);
+
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+impl VfsFile for RewrittenFile {
+    fn replace<'a>(&'a mut self, bytes: &'a [u8]) -> VfsFuture<'a, ()> {
+        Box::pin(storage::write(&self.0, bytes))
+    }
+}
+
+impl Vfs for StorageFs {
+    #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+    fn open<'a>(&'a self, path: &'a Path) -> VfsFuture<'a, Box<dyn VfsFile>> {
+        Box::pin(async move { Ok(Box::new(native::open(path).await?) as Box<dyn VfsFile>) })
+    }
+
+    #[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+    fn open<'a>(&'a self, path: &'a Path) -> VfsFuture<'a, Box<dyn VfsFile>> {
+        let file: Box<dyn VfsFile> = Box::new(RewrittenFile(path.to_path_buf()));
+        Box::pin(async move { Ok(file) })
+    }
+
+    fn read<'a>(&'a self, path: &'a Path) -> VfsFuture<'a, Vec<u8>> {
+        Box::pin(storage::read(path))
+    }
+
+    fn write<'a>(&'a self, path: &'a Path, bytes: &'a [u8]) -> VfsFuture<'a, ()> {
+        Box::pin(storage::write(path, bytes))
+    }
+
+    fn rename<'a>(&'a self, from: &'a Path, to: &'a Path) -> VfsFuture<'a, ()> {
+        Box::pin(storage::rename(from, to))
+    }
+
+    fn remove<'a>(&'a self, path: &'a Path) -> VfsFuture<'a, ()> {
+        Box::pin(storage::remove(path))
+    }
+
+    fn copy<'a>(&'a self, from: &'a Path, to: &'a Path) -> VfsFuture<'a, ()> {
+        Box::pin(storage::copy(from, to))
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+    fn exists<'a>(&'a self, path: &'a Path) -> VfsFuture<'a, bool> {
+        Box::pin(async move { Ok(path.exists().await) })
+    }
+
+    #[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+    fn list<'a>(&'a self, dir: &'a Path) -> VfsFuture<'a, Vec<PathBuf>> {
+        Box::pin(async move {
+            use futures_lite::StreamExt;
+
+            let mut entries = async_fs::read_dir(dir).await?;
+            let mut paths = Vec::new();
+            while let Some(entry) = entries.next().await {
+                paths.push(PathBuf::from(entry?.path()));
+            }
+            Ok(paths)
+        })
+    }
+}
+
+type Files = Arc<Mutex<BTreeMap<PathBuf, Vec<u8>>>>;
+
+/// Files kept in memory, shared between clones, e.g. to run the whole plugin in tests and
+/// look at what it saved.
+///
+/// ```ignore
+/// let fs = MemoryFs::default();
+/// fs.insert("saves/settings.json", br#"{"volume":0.5}"#);
+/// app.add_plugins(FileSinkPlugin::<Settings>::new("saves/settings.json").with_vfs(fs.clone()));
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct MemoryFs {
+    files: Files,
+}
+
+impl MemoryFs {
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<Vec<u8>> {
+        self.files.lock().unwrap().get(path.as_ref()).cloned()
+    }
+
+    pub fn insert(&self, path: impl Into<PathBuf>, bytes: impl Into<Vec<u8>>) {
+        self.files.lock().unwrap().insert(path.into(), bytes.into());
+    }
+
+    /// Every file, sorted.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.files.lock().unwrap().keys().cloned().collect()
+    }
+
+    fn not_found(path: &Path) -> io::Error {
+        io::Error::new(io::ErrorKind::NotFound, path.display().to_string())
+    }
+}
+
+struct MemoryFile {
+    files: Files,
+    path: PathBuf,
+}
+
+impl VfsFile for MemoryFile {
+    fn replace<'a>(&'a mut self, bytes: &'a [u8]) -> VfsFuture<'a, ()> {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(self.path.clone(), bytes.to_vec());
+        Box::pin(async { Ok(()) })
+    }
+}
+
+impl Vfs for MemoryFs {
+    fn open<'a>(&'a self, path: &'a Path) -> VfsFuture<'a, Box<dyn VfsFile>> {
+        self.files
+            .lock()
+            .unwrap()
+            .entry(path.to_path_buf())
+            .or_default();
+        let file: Box<dyn VfsFile> = Box::new(MemoryFile {
+            files: self.files.clone(),
+            path: path.to_path_buf(),
+        });
+        Box::pin(async move { Ok(file) })
+    }
+
+    fn read<'a>(&'a self, path: &'a Path) -> VfsFuture<'a, Vec<u8>> {
+        let read = self.get(path).ok_or_else(|| Self::not_found(path));
+        Box::pin(async move { read })
+    }
+
+    fn write<'a>(&'a self, path: &'a Path, bytes: &'a [u8]) -> VfsFuture<'a, ()> {
+        self.insert(path.to_path_buf(), bytes);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn rename<'a>(&'a self, from: &'a Path, to: &'a Path) -> VfsFuture<'a, ()> {
+        let mut files = self.files.lock().unwrap();
+        let renamed = match files.remove(from) {
+            Some(bytes) => {
+                files.insert(to.to_path_buf(), bytes);
+                Ok(())
+            }
+            None => Err(Self::not_found(from)),
+        };
+        Box::pin(async move { renamed })
+    }
+
+    fn remove<'a>(&'a self, path: &'a Path) -> VfsFuture<'a, ()> {
+        let removed = match self.files.lock().unwrap().remove(path) {
+            Some(_) => Ok(()),
+            None => Err(Self::not_found(path)),
+        };
+        Box::pin(async move { removed })
+    }
+
+    fn list<'a>(&'a self, dir: &'a Path) -> VfsFuture<'a, Vec<PathBuf>> {
+        let files = self.files.lock().unwrap();
+        let paths = files
+            .keys()
+            .filter(|path| path.parent() == Some(dir))
+            .cloned()
+            .collect();
+        Box::pin(async move { Ok(paths) })
+    }
+}
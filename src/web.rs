@@ -0,0 +1,25 @@
+//! Glue for the browser backends. JS values aren't `Send`, so browser calls run on the
+//! event loop and sink tasks only see their results.
+
+use crate::IoSinkError;
+use async_channel::bounded;
+use std::future::Future;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::spawn_local;
+
+pub(crate) fn js_error(value: JsValue) -> IoSinkError {
+    IoSinkError::Other(format!("{value:?}"))
+}
+
+/// Run `f` on the browser's event loop and wait for its result.
+pub(crate) async fn run_local<T, F>(f: impl FnOnce() -> F + 'static) -> Result<T, IoSinkError>
+where
+    T: Send + 'static,
+    F: Future<Output = Result<T, IoSinkError>> + 'static,
+{
+    let (tx, rx) = bounded(1);
+    spawn_local(async move {
+        let _ = tx.send(f().await).await;
+    });
+    rx.recv().await.map_err(|_| IoSinkError::ChannelClosed)?
+}
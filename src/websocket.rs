@@ -0,0 +1,173 @@
+use crate::{IoSinkError, IoWriter, RetryPolicy, SaveFormat, SinkResult};
+use async_tungstenite::{
+    async_std::{connect_async, ConnectStream},
+    tungstenite::Message,
+    WebSocketStream,
+};
+use bevy::prelude::*;
+use futures_util::SinkExt;
+use serde::Serialize;
+use std::{
+    collections::VecDeque,
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
+
+/// Streams every message as a text frame over a WebSocket, e.g. to mirror live game state to
+/// a dashboard. A dropped connection isn't an error: messages are buffered, up to
+/// [`with_max_buffered`](Self::with_max_buffered), and the connection is attempted again on
+/// later writes with the backoff of [`with_reconnect`](Self::with_reconnect).
+///
+/// ```ignore
+/// app.add_plugins(IoSinkPlugin::<GameState, _>::new(WebSocketSink::new("ws://localhost:9001")));
+/// ```
+pub struct WebSocketSink<R> {
+    url: String,
+    format: SaveFormat,
+    reconnect: RetryPolicy,
+    max_buffered: usize,
+    max_message_size: Option<u64>,
+    stream: Option<WebSocketStream<ConnectStream>>,
+    /// Messages not sent yet, oldest first.
+    buffer: VecDeque<String>,
+    /// Failed connection attempts since the last successful one.
+    attempts: u32,
+    next_attempt: Option<Instant>,
+    last_write_len: Option<u64>,
+    _phantom: PhantomData<fn(R)>,
+}
+
+impl<R> WebSocketSink<R> {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            format: SaveFormat::default(),
+            reconnect: RetryPolicy {
+                initial_backoff: Duration::from_millis(500),
+                max_backoff: Duration::from_secs(30),
+                ..default()
+            },
+            max_buffered: 256,
+            max_message_size: None,
+            stream: None,
+            buffer: VecDeque::new(),
+            attempts: 0,
+            next_attempt: None,
+            last_write_len: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn with_format(mut self, format: SaveFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Backoff between connection attempts. `max_retries` is ignored, the sink keeps trying
+    /// for as long as it runs.
+    pub fn with_reconnect(mut self, policy: RetryPolicy) -> Self {
+        self.reconnect = policy;
+        self
+    }
+
+    /// Keep at most `count` messages while disconnected, 256 by default. The oldest are
+    /// dropped first.
+    pub fn with_max_buffered(mut self, count: usize) -> Self {
+        self.max_buffered = count.max(1);
+        self
+    }
+
+    /// Reject encoded messages larger than `bytes` with [`IoSinkError::MessageTooLarge`].
+    pub fn with_max_message_size(mut self, bytes: u64) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+
+    async fn connect(&mut self) {
+        if self.stream.is_some() || self.next_attempt.is_some_and(|at| Instant::now() < at) {
+            return;
+        }
+        match connect_async(self.url.as_str()).await {
+            Ok((stream, _)) => {
+                if self.attempts > 0 {
+                    info!("reconnected to {}", self.url);
+                }
+                self.stream = Some(stream);
+                self.attempts = 0;
+                self.next_attempt = None;
+            }
+            Err(e) => {
+                let backoff = self.reconnect.backoff(self.attempts);
+                warn!(
+                    "cannot connect to {}, retrying in {backoff:?}: {e}",
+                    self.url
+                );
+                self.attempts = self.attempts.saturating_add(1);
+                self.next_attempt = Some(Instant::now() + backoff);
+            }
+        }
+    }
+
+    /// Send the buffered messages, stopping at the first failure.
+    async fn send_buffered(&mut self) {
+        let Some(stream) = self.stream.as_mut() else {
+            return;
+        };
+        while let Some(text) = self.buffer.front() {
+            if let Err(e) = stream.send(Message::text(text.clone())).await {
+                warn!("lost the connection to {}: {e}", self.url);
+                self.stream = None;
+                return;
+            }
+            self.buffer.pop_front();
+        }
+    }
+}
+
+impl<R> IoWriter<R> for WebSocketSink<R>
+where
+    R: Serialize + Send + Sync + 'static,
+{
+    async fn init(&mut self) -> SinkResult {
+        self.connect().await;
+        Ok(())
+    }
+
+    async fn write(&mut self, data: R) -> SinkResult {
+        let json = self.format.encode(&data)?;
+        IoSinkError::check_size(json.len(), self.max_message_size)?;
+        let text = String::from_utf8(json).map_err(IoSinkError::serialization)?;
+        let len = text.len() as u64;
+
+        if self.buffer.len() == self.max_buffered {
+            warn!("{} is unreachable, dropping the oldest message", self.url);
+            self.buffer.pop_front();
+        }
+        self.buffer.push_back(text);
+        self.connect().await;
+        self.send_buffered().await;
+        self.last_write_len = Some(len);
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> SinkResult {
+        self.connect().await;
+        self.send_buffered().await;
+        Ok(())
+    }
+
+    async fn close(&mut self) -> SinkResult {
+        self.send_buffered().await;
+        if let Some(mut stream) = self.stream.take() {
+            stream
+                .close(None)
+                .await
+                .map_err(|e| IoSinkError::Other(format!("closing {}: {e}", self.url)))?;
+        }
+        Ok(())
+    }
+
+    fn last_write_len(&self) -> Option<u64> {
+        self.last_write_len
+    }
+}